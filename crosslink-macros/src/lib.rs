@@ -1,14 +1,131 @@
 use heck::ToSnakeCase;
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::Error as SynError;
+use quote::{format_ident, quote, quote_spanned};
 use syn::parse_macro_input;
+use syn::spanned::Spanned;
 
 mod model;
 use model::*;
 
 extern crate proc_macro;
 
+/// Defines a two-endpoint link and generates everything needed to wire it into a `Router`.
+///
+/// For `link_id: "PingPongLink"` with endpoints `PingerHandle` and `PongerHandle`,
+/// the generated names are part of the public contract and follow a fixed scheme:
+///
+/// - `ping_pong_link`: the link module, the snake-cased `link_id`, unless set with `module:`.
+/// - `ping_pong_link::setup_ping_pong_link`: the setup function, `setup_{module}`.
+/// - `ping_pong_link::PingerHandle`: one nominal handle type per endpoint.
+/// - `ping_pong_link::marker::PingerHandleSend`: the sender marker, `{HandleName}Send`.
+/// - `ping_pong_link::marker::PingerHandleRecv`: the receiver marker, `{HandleName}Recv`.
+/// - `ping_pong_link::PingerReceiver` and `ping_pong_link::PingerSender`: aliases of the
+///   endpoint's channel halves, named after the handle without a trailing `Handle`.
+///   The sender alias is only generated for plain links, and both need `tokio`.
+///
+/// Renaming an endpoint therefore renames its markers and aliases.
+///
+/// The module name can be set apart from the `link_id` with `module:`, right after it,
+/// leaving `link_id` free-form, e.g. for logs. It names the setup function too:
+///
+/// ```ignore
+/// define_crosslink! {
+///     link_id: "Ping <-> Pong v2",
+///     module: ping_pong,
+///     // ...
+/// }
+/// ```
+///
+/// generates `ping_pong` and `ping_pong::setup_ping_pong`, while `LINK_ID` and link
+/// metadata keep the full `link_id`. Without `module:`, a `link_id` that doesn't
+/// snake-case into a valid identifier fails the build.
+///
+/// Each side's `receives` must be the other side's `sends`. The types are compared by
+/// the compiler, so an alias on one side and the aliased type on the other match.
+/// Message types may be given by any path valid at the invocation, including
+/// `self::` and `super::` paths, which are adjusted for the generated module.
+/// They must be owned and `'static`: a reference, or a type naming any lifetime other
+/// than `'static`, fails the build, pointing at it.
+///
+/// `buffer_size` takes any constant `usize` expression, e.g. `DEFAULT_BUF` or
+/// `DEFAULT_BUF * 2`, exposed as `ping_pong_link::BUFFER_SIZE`.
+/// A zero buffer fails the build.
+///
+/// `buffer_size: auto(mem = "1MB")` sizes the buffer from a memory budget instead:
+/// the budget divided by `size_of` the larger of the two message types, and at least 1.
+/// `KB`, `MB` and `GB` are powers of 1000, `KiB`, `MiB` and `GiB` powers of 1024.
+/// A budget beyond the target's `usize`, e.g. `"8GB"` on a 32-bit target, fails the build.
+/// It's a rough heuristic: it only counts the messages themselves, not what they point
+/// to on the heap, nor the channel's own per-message overhead.
+///
+/// Optional trailing flags:
+/// - `instrumented: true` registers both directions with latency instrumentation.
+/// - `acked: true` registers both directions as acknowledged pathways,
+///   carrying `crosslink::ack::Acked<T>` so producers can use `Router::send_acked`.
+/// - `rendezvous: true` registers both directions without buffering: `send` resolves
+///   once the other side takes the message. `buffer_size` is ignored.
+/// - `watch: true` registers both directions as watch pathways, holding only the
+///   latest value sent, starting from the message type's `Default`. Consumers get
+///   watchers with `Router::watch_receiver`, as many as they like, and producers
+///   replace the value with `Router::update_watch`. `buffer_size` is ignored, and
+///   `run_helpers` and `behaviors` aren't available.
+/// - `timestamped: true` stamps every message with the wall-clock time it was sent.
+///   Consumers take `Router::take_timestamped_receiver`, whose `recv` yields
+///   `(SystemTime, Msg)` pairs. `run_helpers` and `behaviors` aren't available.
+/// - `meta: { description: "...", owner: "team-x" }` attaches free-form string
+///   metadata, exposed as `LINK_META` and through `Router::link_meta(LINK_ID)`.
+/// - `reexport_markers: true` adds `pub use marker::*;` to the link module,
+///   so markers can be imported straight from `ping_pong_link`.
+/// - `run_helpers: true` adds one `run_{handle}` function per endpoint, e.g.
+///   `ping_pong_link::run_pinger_handle(&router, handler)`. It takes the endpoint's
+///   receiver and calls `handler` on every message, sending back any `Some` reply,
+///   until the link closes. Not available on `acked` links.
+/// - `behaviors: true` is the `async` counterpart of `run_helpers`: it adds one
+///   `{HandleName}Behavior` trait per endpoint, with an `on_message` method returning the
+///   reply, and a `run_{handle}(&router, &mut behavior)` that drives it the same way.
+///   The driver takes the endpoint's receiver, so an endpoint is either run through its
+///   behavior or read with `take_receiver`, not both. Not available on `acked` links,
+///   and exclusive with `run_helpers`. The traits are `pub`, so the link's message
+///   types must be as well.
+/// - `connect: true` adds `connect(buffer_size_override)`, which builds a fresh `Router`
+///   with only this link on it and returns it shared, along with both handles:
+///   `let (router, pinger, ponger) = ping_pong_link::connect(None);`.
+///   Use `setup_{module}` instead to put several links on one router.
+/// - `framing: true` needs crosslink's `serde` feature. It adds a `TAG: u16` to each
+///   handle, for the message type it sends, and a `Frame` enum with `encode_framed` and
+///   `decode_framed`, which prefix serialized messages with their tag and dispatch on
+///   it. The two directions must carry different types, which must implement
+///   `Serialize` and `DeserializeOwned`.
+///
+/// An endpoint can also declare what it reports to a supervisor, with a third entry:
+/// `PingerHandle { sends: Ping, receives: Pong, errors: PingError }`. This adds a
+/// `{HandleName}Errors` marker per reporting endpoint, and one `ErrorsRecv` marker for
+/// the link. Unlike `sends` and `receives`, which pair the two endpoints up, the error
+/// channel runs from every reporting endpoint to a third party: the supervisor, which
+/// takes it with `Router::take_error_receiver::<marker::ErrorsRecv, PingError>()`.
+/// Endpoints report through `Router::report_error::<marker::PingerHandleErrors, _>(e)`,
+/// and the supervisor gets each error as an `ErrorReport` naming its endpoint. If both
+/// endpoints declare `errors:`, they must report the same type. The channel is always
+/// buffered, with `buffer_size`, whatever the link's flags.
+///
+/// `cfg: feature = "audio"`, or any other `cfg` predicate, compiles the whole link only
+/// when it holds. Everything the macro generates lives in the link module, which the
+/// predicate is put on, so the handles, markers, setup function and re-exports all go
+/// away together. Code outside the macro that names the link, e.g. a
+/// `pub use audio_link::*;` or a call to its setup function, needs the same `#[cfg]`.
+///
+/// `LINK_SCHEMA_HASH` is a `u64` derived from the endpoint names and the message types
+/// each one sends, receives and reports. Binaries built from the same definition with the
+/// same compiler version get the same hash, so components exchanging serialized messages
+/// can compare it at startup to catch version skew. Another rustc may print the type
+/// tokens differently and change the hash, so a mismatch between binaries built with
+/// different toolchains doesn't by itself mean the link changed. It's a best-effort
+/// signal: it hashes type names as written, not their layouts, so changing the fields of
+/// a message type leaves it unchanged, while spelling the same type through another path
+/// or alias changes it. Flags such as `acked` aren't part of it.
+///
+/// With crosslink's `inventory` feature, every link also registers its setup function,
+/// so `Router::collect_all()` can set up all links in the binary at once.
 #[proc_macro]
 #[allow(unused_variables)]
 #[allow(non_snake_case)]
@@ -25,82 +142,477 @@ pub fn define_crosslink(input: TokenStream) -> TokenStream {
     let ep2_sends_type = &parsed.ep2_def.messages.sends_ty;
     let ep2_receives_type = &parsed.ep2_def.messages.receives_ty;
 
-    // Assert opposing directions are the same type
-    // Will be properly validated during compilation
-    assert_eq!(ep1_sends_type, ep2_receives_type);
-    assert_eq!(ep1_receives_type, ep2_sends_type);
-
-    let buffer_usize_val = match parsed.buffer_arg.value.base10_parse::<usize>() {
-        Ok(val) => val,
-        Err(e) => {
-            return SynError::new_spanned(
-                &parsed.buffer_arg.value,
-                format!("Failed to parse usize from buffer_size value: {}", e),
+    let schema_hash = schema_hash(&[&parsed.ep1_def, &parsed.ep2_def]);
+
+    let crosslink_crate_path = quote!(::crosslink);
+
+    // Opposing directions must carry the same type, or one endpoint's sends would never
+    // be received. Checked by the compiler rather than by comparing tokens, so aliases
+    // such as `type Cmd = ControlMessage;` are accepted, through a trait whose
+    // diagnostic explains the mismatch. Spanned so it points at the receiving side's
+    // declaration.
+    let pairing_checks_q = [
+        (ep1_sends_type, ep2_receives_type),
+        (ep2_sends_type, ep1_receives_type),
+    ]
+    .map(|(sends, receives)| {
+        quote_spanned! {receives.span()=>
+            const _: fn() = || {
+                fn paired<Received: #crosslink_crate_path::sender::__ReceivedFrom<Sent>, Sent>() {}
+                paired::<#receives, #sends>();
+            };
+        }
+    });
+
+    let (buffer_size_expr, buffer_size_span) = match &parsed.buffer_arg.value {
+        BufferSize::Count(expr) => (quote!(#expr), expr.span()),
+        // Sized for the larger message type, so neither direction exceeds the budget.
+        // Zero-sized messages count as one byte.
+        // The budget stays a `u64` until it's checked against the target's `usize`,
+        // which may be narrower than the host's.
+        BufferSize::Auto { call, bytes } => {
+            let bytes = proc_macro2::Literal::u64_suffixed(*bytes);
+            (
+                quote_spanned! {call.span()=> {
+                    const fn at_least_one(n: usize) -> usize {
+                        if n == 0 { 1 } else { n }
+                    }
+                    let bytes: u64 = #bytes;
+                    assert!(
+                        bytes <= usize::MAX as u64,
+                        "buffer_size: the memory budget doesn't fit in this target's usize"
+                    );
+                    let ep1 = ::core::mem::size_of::<#ep1_sends_type>();
+                    let ep2 = ::core::mem::size_of::<#ep2_sends_type>();
+                    let largest = if ep1 > ep2 { ep1 } else { ep2 };
+                    at_least_one(bytes as usize / at_least_one(largest))
+                }},
+                call.span(),
             )
-            .to_compile_error()
-            .into();
         }
     };
+    // Spanned so an unsendable message type points at its own declaration.
+    let message_checks_q = [ep1_sends_type, ep2_sends_type].map(|ty| {
+        quote_spanned! {ty.span()=>
+            assert_message::<#ty>();
+        }
+    });
+
+    // Spanned so a zero buffer points at the offending expression.
+    let buffer_size_check_q = quote_spanned! {buffer_size_span=>
+        const _: () = assert!(BUFFER_SIZE > 0, "buffer_size must be greater than zero");
+    };
 
     let sender_marker_ep1 = format_ident!("{}Send", ep1_handle_name);
     let receiver_marker_ep1 = format_ident!("{}Recv", ep1_handle_name);
     let sender_marker_ep2 = format_ident!("{}Send", ep2_handle_name);
     let receiver_marker_ep2 = format_ident!("{}Recv", ep2_handle_name);
 
-    let tx1 = format_ident!(
-        "__tx_{}_{}",
-        link_id_base.to_lowercase(),
-        ep1_handle_name.to_string().to_lowercase()
-    ); // For ep1 sending
-
-    let rx1 = format_ident!(
-        "__rx_{}_{}",
-        link_id_base.to_lowercase(),
-        ep1_handle_name.to_string().to_lowercase()
-    ); // For ep1 receiving
-
-    let tx2 = format_ident!(
-        "__tx_{}_{}",
-        link_id_base.to_lowercase(),
-        ep2_handle_name.to_string().to_lowercase()
-    ); // For ep2 sending
-
-    let rx2 = format_ident!(
-        "__rx_{}_{}",
-        link_id_base.to_lowercase(),
-        ep2_handle_name.to_string().to_lowercase()
-    ); // For ep2 receiving
-
-    let mod_name = format_ident!("{}", link_id_base.to_snake_case());
+    let mod_name = &parsed.module_name;
     let setup_fn_name = format_ident!("setup_{}", mod_name);
 
-    let crosslink_crate_path = quote!(::crosslink);
     let router_path = quote!(#crosslink_crate_path::Router);
 
+    let register_fn = if parsed.instrumented {
+        format_ident!("__internal_register_instrumented")
+    } else if parsed.acked {
+        format_ident!("__internal_register_acked")
+    } else if parsed.rendezvous {
+        format_ident!("__internal_register_rendezvous")
+    } else if parsed.watch {
+        format_ident!("__internal_register_watch")
+    } else if parsed.timestamped {
+        format_ident!("__internal_register_timestamped")
+    } else {
+        format_ident!("__internal_register_pathway")
+    };
+
+    // One pathway per direction: ep1 -> ep2 and ep2 -> ep1
+    let registrations_q = quote! {
+        router.#register_fn::<marker::#sender_marker_ep1, marker::#receiver_marker_ep2, #ep1_sends_type>(buffer_val)
+            .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#sender_marker_ep1), e));
+
+        router.#register_fn::<marker::#sender_marker_ep2, marker::#receiver_marker_ep1, #ep2_sends_type>(buffer_val)
+            .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#sender_marker_ep2), e));
+    };
+
+    // Endpoints declared with `errors:` each get a marker to report through, all
+    // feeding one channel the link's supervisor reads from.
+    let reporters: Vec<(&syn::Ident, &syn::Type)> = [&parsed.ep1_def, &parsed.ep2_def]
+        .into_iter()
+        .filter_map(|ep| Some((&ep.handle_name, ep.messages.errors_ty.as_ref()?)))
+        .collect();
+    let supervisor_marker = format_ident!("ErrorsRecv");
+    if !reporters.is_empty()
+        && let Some(clash) = [ep1_handle_name, ep2_handle_name]
+            .into_iter()
+            .find(|handle| *handle == "Errors")
+    {
+        return syn::Error::new_spanned(
+            clash,
+            "An endpoint named `Errors` would clash with the `ErrorsRecv` marker \
+            of the link's error channel, rename it",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let error_markers: Vec<syn::Ident> = reporters
+        .iter()
+        .map(|(handle, _)| format_ident!("{}Errors", handle))
+        .collect();
+    // Both endpoints report to the same receiver, so they must report the same type.
+    let error_type_check_q = match reporters.as_slice() {
+        [(_, first), (_, second)] => quote_spanned! {second.span()=>
+            const _: fn(#first) -> #second = |error| error;
+        },
+        _ => quote! {},
+    };
+    let error_registrations_q = match reporters.first() {
+        Some((_, error_ty)) => {
+            let reports = reporters.iter().zip(&error_markers).map(|((handle, _), marker)| {
+                quote! {
+                    router.__internal_register_error_reporter::<marker::#marker, #error_ty>(
+                        errors.clone(),
+                        stringify!(#handle),
+                    )
+                    .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#marker), e));
+                }
+            });
+            quote! {
+                let errors = router
+                    .__internal_register_error_channel::<marker::#supervisor_marker, #error_ty>(buffer_val)
+                    .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#supervisor_marker), e));
+                #(#reports)*
+            }
+        }
+        None => quote! {},
+    };
+    let error_markers_q = if reporters.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            #(
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                #[allow(non_snake_case, dead_code)]
+                pub struct #error_markers {
+                    _private: (),
+                }
+            )*
+
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #[allow(non_snake_case, dead_code)]
+            pub struct #supervisor_marker {
+                _private: (),
+            }
+
+            const _: () = {
+                const fn assert_marker<T: Send + Sync + 'static>() {}
+                #(assert_marker::<#error_markers>();)*
+                assert_marker::<#supervisor_marker>();
+            };
+        }
+    };
+    let error_message_checks_q = reporters.iter().map(|(_, ty)| {
+        quote_spanned! {ty.span()=>
+            assert_message::<#ty>();
+        }
+    });
+
+    let take_fn = if parsed.instrumented {
+        format_ident!("take_instrumented_receiver")
+    } else if parsed.rendezvous {
+        format_ident!("take_rendezvous_receiver")
+    } else {
+        format_ident!("take_receiver")
+    };
+
+    let endpoints = [
+        (
+            ep1_handle_name,
+            &sender_marker_ep1,
+            &receiver_marker_ep1,
+            ep1_sends_type,
+            ep1_receives_type,
+        ),
+        (
+            ep2_handle_name,
+            &sender_marker_ep2,
+            &receiver_marker_ep2,
+            ep2_sends_type,
+            ep2_receives_type,
+        ),
+    ];
+
+    let run_helpers_q = if parsed.run_helpers {
+        let helpers = endpoints.into_iter().map(|(handle, sender_marker, receiver_marker, sends, receives)| {
+            let run_fn_name = format_ident!("run_{}", handle.to_string().to_snake_case());
+            let doc = format!(
+                " Runs the `{}` endpoint: calls `handler` on every message it receives and \
+                sends back any `Some` reply, until the link closes.",
+                handle
+            );
+            quote! {
+                #[doc = #doc]
+                #[allow(dead_code)]
+                pub async fn #run_fn_name(
+                    router: &#router_path,
+                    mut handler: impl FnMut(#receives) -> Option<#sends>,
+                ) -> Result<(), #crosslink_crate_path::CommsError> {
+                    let mut receiver = router.#take_fn::<marker::#receiver_marker, #receives>()?;
+                    while let Some(msg) = receiver.recv().await {
+                        if let Some(reply) = handler(msg) {
+                            router.send::<marker::#sender_marker, #sends>(reply).await?;
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        });
+        quote! { #(#helpers)* }
+    } else {
+        quote! {}
+    };
+
+    let behaviors_q = if parsed.behaviors {
+        let behaviors = endpoints.into_iter().map(|(handle, sender_marker, receiver_marker, sends, receives)| {
+            let trait_name = format_ident!("{}Behavior", handle);
+            let run_fn_name = format_ident!("run_{}", handle.to_string().to_snake_case());
+            let trait_doc = format!(
+                " The behavior of the `{}` endpoint, driven by [`{}`].",
+                handle, run_fn_name
+            );
+            let run_doc = format!(
+                " Runs the `{}` endpoint: awaits `behavior.on_message` for every message it \
+                receives and sends back any `Some` reply, until the link closes.",
+                handle
+            );
+            quote! {
+                #[doc = #trait_doc]
+                pub trait #trait_name: Send {
+                    /// Handles one received message, returning the reply to send back, if any.
+                    fn on_message(
+                        &mut self,
+                        msg: #receives,
+                    ) -> impl ::core::future::Future<Output = Option<#sends>> + Send;
+                }
+
+                #[doc = #run_doc]
+                #[allow(dead_code)]
+                pub async fn #run_fn_name(
+                    router: &#router_path,
+                    behavior: &mut impl #trait_name,
+                ) -> Result<(), #crosslink_crate_path::CommsError> {
+                    let mut receiver = router.#take_fn::<marker::#receiver_marker, #receives>()?;
+                    while let Some(msg) = receiver.recv().await {
+                        if let Some(reply) = behavior.on_message(msg).await {
+                            router.send::<marker::#sender_marker, #sends>(reply).await?;
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        });
+        quote! { #(#behaviors)* }
+    } else {
+        quote! {}
+    };
+
+    let framing_q = if parsed.framing {
+        let tags = [ep1_sends_type, ep2_sends_type].map(message_tag);
+        if tags[0] == tags[1] {
+            let reason =
+                if quote!(#ep1_sends_type).to_string() == quote!(#ep2_sends_type).to_string() {
+                    "`framing` tells messages apart by type, but both endpoints send this type"
+                } else {
+                    "`framing` tags of the two message types collide, \
+                wrap one of them in a newtype to change its tag"
+                };
+            return syn::Error::new_spanned(ep2_sends_type, reason)
+                .to_compile_error()
+                .into();
+        }
+        let [ep1_tag, ep2_tag] = tags;
+        quote! {
+            #crosslink_crate_path::__framing! {
+                #ep1_handle_name: #ep1_sends_type = #ep1_tag,
+                #ep2_handle_name: #ep2_sends_type = #ep2_tag,
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let connect_q = if parsed.connect {
+        quote! {
+            /// Builds a `Router` carrying only this link, and returns it shared along with
+            /// both handles. `buffer_size_override` is passed on to the setup function.
+            #[allow(dead_code)]
+            pub fn connect(
+                buffer_size_override: Option<usize>,
+            ) -> (
+                ::std::sync::Arc<#router_path>,
+                #ep1_handle_name,
+                #ep2_handle_name,
+            ) {
+                let mut router = #router_path::new();
+                let (ep1, ep2) = #setup_fn_name(&mut router, buffer_size_override);
+                (router.into_shared(), ep1, ep2)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // `PingerHandle` gets `PingerReceiver` and `PingerSender`, any other name the
+    // suffixes as is.
+    let alias_base = |handle: &syn::Ident| {
+        let name = handle.to_string();
+        match name.strip_suffix("Handle") {
+            Some(base) if !base.is_empty() => base.to_string(),
+            _ => name,
+        }
+    };
+    if alias_base(ep1_handle_name) == alias_base(ep2_handle_name) {
+        return syn::Error::new_spanned(
+            ep2_handle_name,
+            format!(
+                "`{}` and `{}` would share the `{}Receiver` and `{}Sender` aliases, \
+                rename one of them",
+                ep1_handle_name,
+                ep2_handle_name,
+                alias_base(ep2_handle_name),
+                alias_base(ep2_handle_name)
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+    let flavor = if parsed.instrumented {
+        format_ident!("instrumented")
+    } else if parsed.acked {
+        format_ident!("acked")
+    } else if parsed.rendezvous {
+        format_ident!("rendezvous")
+    } else if parsed.watch {
+        format_ident!("watch")
+    } else if parsed.timestamped {
+        format_ident!("timestamped")
+    } else {
+        format_ident!("plain")
+    };
+    let aliases = endpoints
+        .into_iter()
+        .map(|(handle, _, _, sends, receives)| {
+            let receiver_alias = format_ident!("{}Receiver", alias_base(handle));
+            let sender_alias = format_ident!("{}Sender", alias_base(handle));
+            quote! { #handle: #receiver_alias = #receives, #sender_alias = #sends; }
+        });
+    let channel_aliases_q = quote! {
+        #crosslink_crate_path::__channel_aliases! {
+            #flavor;
+            #(#aliases)*
+        }
+    };
+
+    let marker_reexport_q = if parsed.reexport_markers {
+        quote! { pub use marker::*; }
+    } else {
+        quote! {}
+    };
+
+    let meta_entries_q = parsed
+        .meta_arg
+        .iter()
+        .flat_map(|meta| meta.entries.iter())
+        .map(|entry| {
+            let key = entry.key.to_string();
+            let value = &entry.value;
+            quote! { (#key, #value) }
+        });
+
+    // Everything generated lives in the link module, so gating it gates the whole link.
+    let cfg_q = parsed.cfg_arg.as_ref().map(|cfg| {
+        let predicate = &cfg.predicate;
+        quote! { #[cfg(#predicate)] }
+    });
+
     let definitions_q = quote! {
+        #cfg_q
         pub mod #mod_name {
             use super::*;
 
             pub mod marker {
                 use super::*;
 
+                // Type-level tokens only: the private field keeps them from being
+                // constructed outside this module.
+
                 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
                 #[allow(non_snake_case, dead_code)]
-                pub struct #sender_marker_ep1;
+                pub struct #sender_marker_ep1 {
+                    _private: (),
+                }
 
                 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
                 #[allow(non_snake_case, dead_code)]
-                pub struct #receiver_marker_ep1;
+                pub struct #receiver_marker_ep1 {
+                    _private: (),
+                }
 
                 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
                 #[allow(non_snake_case, dead_code)]
-                pub struct #sender_marker_ep2;
+                pub struct #sender_marker_ep2 {
+                    _private: (),
+                }
 
                 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
                 #[allow(non_snake_case, dead_code)]
-                pub struct #receiver_marker_ep2;
+                pub struct #receiver_marker_ep2 {
+                    _private: (),
+                }
+
+                // The router keys pathways by these, across threads.
+                const _: () = {
+                    const fn assert_marker<T: Send + Sync + 'static>() {}
+                    assert_marker::<#sender_marker_ep1>();
+                    assert_marker::<#receiver_marker_ep1>();
+                    assert_marker::<#sender_marker_ep2>();
+                    assert_marker::<#receiver_marker_ep2>();
+                };
+
+                #error_markers_q
             }
 
+            #marker_reexport_q
+
+            /// The `link_id` this module was generated from.
+            #[allow(dead_code)]
+            pub const LINK_ID: &str = #link_id_base;
+
+            /// The default buffer size of each direction, from the `buffer_size` argument.
+            #[allow(dead_code)]
+            pub const BUFFER_SIZE: usize = #buffer_size_expr;
+
+            #buffer_size_check_q
+
+            /// Hash of the endpoints' names and the message types each one sends and
+            /// receives, as written in the definition.
+            #[allow(dead_code)]
+            pub const LINK_SCHEMA_HASH: u64 = #schema_hash;
+
+            // Messages are moved, never cloned, so this is all a link needs from them.
+            const _: () = {
+                const fn assert_message<T: #crosslink_crate_path::sender::ConcreteSenderTrait>() {}
+                #(#message_checks_q)*
+                #(#error_message_checks_q)*
+            };
+
+            #(#pairing_checks_q)*
+            #error_type_check_q
+
+            /// Metadata attached to the link through its `meta` block.
+            #[allow(dead_code)]
+            pub const LINK_META: &[(&str, &str)] = &[#(#meta_entries_q),*];
 
             #[derive(Debug, Clone, Copy)]
             #[allow(non_snake_case, dead_code)]
@@ -110,6 +622,8 @@ pub fn define_crosslink(input: TokenStream) -> TokenStream {
             #[allow(non_snake_case, dead_code)]
             pub struct #ep2_handle_name;
 
+            #channel_aliases_q
+
             #[allow(dead_code)]
             pub fn #setup_fn_name(
                 router: &mut #router_path,
@@ -118,29 +632,77 @@ pub fn define_crosslink(input: TokenStream) -> TokenStream {
                 #ep1_handle_name,
                 #ep2_handle_name,
             ) {
-                let buffer_val = buffer_size_override.unwrap_or(#buffer_usize_val);
-
-                // Channel for ep1_sends_ty (sent by ep1, received by ep2)
-                let (#tx1, #rx2) = ::tokio::sync::mpsc::channel::<#ep1_sends_type>(buffer_val);
-                // Channel for ep2_sends_ty (sent by ep2, received by ep1)
-                let (#tx2, #rx1) = ::tokio::sync::mpsc::channel::<#ep2_sends_type>(buffer_val);
+                let buffer_val = buffer_size_override.unwrap_or(BUFFER_SIZE);
 
-                router.__internal_register_sender::<marker::#sender_marker_ep1, #ep1_sends_type>(#tx1)
-                    .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#sender_marker_ep1), e));
+                router.__internal_register_link_meta(LINK_ID, LINK_META)
+                    .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", LINK_ID, e));
 
-                router.__internal_register_receiver::<marker::#receiver_marker_ep1, #ep2_sends_type>(#rx1)
-                    .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#receiver_marker_ep1), e));
+                #registrations_q
+                #error_registrations_q
 
-                router.__internal_register_sender::<marker::#sender_marker_ep2, #ep2_sends_type>(#tx2)
-                    .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#sender_marker_ep2), e));
-
-                router.__internal_register_receiver::<marker::#receiver_marker_ep2, #ep1_sends_type>(#rx2)
-                    .unwrap_or_else(|e| panic!("Macro Setup Error ({}): {}", stringify!(#receiver_marker_ep2), e));
+                router.__internal_register_link_direction::<marker::#sender_marker_ep1>(
+                    LINK_ID,
+                    stringify!(#ep1_handle_name),
+                    stringify!(#ep2_handle_name),
+                );
+                router.__internal_register_link_direction::<marker::#sender_marker_ep2>(
+                    LINK_ID,
+                    stringify!(#ep2_handle_name),
+                    stringify!(#ep1_handle_name),
+                );
 
                 (#ep1_handle_name, #ep2_handle_name)
             }
+
+            #crosslink_crate_path::__register_link!(LINK_ID, #setup_fn_name);
+
+            #framing_q
+            #connect_q
+            #run_helpers_q
+            #behaviors_q
         }
     };
 
     definitions_q.into()
 }
+
+/// FNV-1a over one line per endpoint, `{Handle} sends {Type} receives {Type}`, followed
+/// by ` errors {Type}` for endpoints declaring one, sorted so the order endpoints are
+/// declared in doesn't matter. Types are taken as the tokens written, which proc-macro2
+/// prints the same way from one build to the next as long as the compiler version doesn't
+/// change.
+fn schema_hash(endpoints: &[&EndpointDef]) -> u64 {
+    let mut lines: Vec<String> = endpoints
+        .iter()
+        .map(|ep| {
+            let sends = &ep.messages.sends_ty;
+            let receives = &ep.messages.receives_ty;
+            let mut line = format!(
+                "{} sends {} receives {}",
+                ep.handle_name,
+                quote!(#sends),
+                quote!(#receives)
+            );
+            if let Some(errors) = &ep.messages.errors_ty {
+                line.push_str(&format!(" errors {}", quote!(#errors)));
+            }
+            line
+        })
+        .collect();
+    lines.sort();
+
+    fnv1a(lines.join("\n").as_bytes())
+}
+
+/// The wire tag of `ty` for `framing: true`: its written tokens hashed, then folded
+/// down to 16 bits, so the same type spelled the same way gets the same tag in any link.
+fn message_tag(ty: &syn::Type) -> u16 {
+    let hash = fnv1a(quote!(#ty).to_string().as_bytes());
+    (hash ^ (hash >> 16) ^ (hash >> 32) ^ (hash >> 48)) as u16
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
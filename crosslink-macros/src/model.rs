@@ -1,7 +1,13 @@
+use heck::ToSnakeCase;
+use quote::ToTokens;
 use syn::{
-    Error as SynError, Ident, LitInt, LitStr, Result as SynResult, Token, Type, braced,
+    Error as SynError, Expr, ExprLit, Ident, Lifetime, LifetimeParam, Lit, LitBool, LitStr, Meta,
+    Path, Result as SynResult, Token, Type, braced,
     parse::{Parse, ParseStream},
+    punctuated::Punctuated,
     token,
+    visit::{self, Visit},
+    visit_mut::{self, VisitMut},
 };
 
 /// LinkIdArg:
@@ -29,6 +35,39 @@ impl Parse for LinkIdArg {
     }
 }
 
+/// ModuleArg:
+/// `module: example_link`
+pub struct ModuleArg {
+    pub _kw: Ident,
+    pub _col: Token![:],
+    pub name: Ident,
+    pub _com: Token![,],
+}
+
+impl Parse for ModuleArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let _kw: Ident = input.parse()?;
+        if _kw != "module" {
+            return Err(SynError::new_spanned(&_kw, "Expected 'module' keyword"));
+        }
+        let _col = input.parse()?;
+        // `Ident`'s parser already turns down keywords, the common mistake.
+        let name = input.parse().map_err(|e| {
+            SynError::new(
+                e.span(),
+                "Expected a module name, e.g. `module: ping_pong`, which must be a valid identifier",
+            )
+        })?;
+
+        Ok(ModuleArg {
+            _kw,
+            _col,
+            name,
+            _com: input.parse()?,
+        })
+    }
+}
+
 pub struct EndpointMessages {
     pub _sends_kw: Ident,
     pub _s_col: Token![:],
@@ -38,6 +77,8 @@ pub struct EndpointMessages {
     pub _r_col: Token![:],
     pub receives_ty: Type,
     pub _r_com: Option<Token![,]>,
+    /// The type the endpoint reports to the link's supervisor, from `errors: ...`.
+    pub errors_ty: Option<Type>,
 }
 
 impl Parse for EndpointMessages {
@@ -48,7 +89,9 @@ impl Parse for EndpointMessages {
         }
 
         let _s_col = input.parse()?;
-        let sends_ty = input.parse()?;
+        let mut sends_ty = input.parse()?;
+        ensure_owned(&sends_ty)?;
+        RebaseRelativePaths.visit_type_mut(&mut sends_ty);
         let _s_com = input.parse()?;
         let _rec_kw = input.parse()?;
         if _rec_kw != "receives" {
@@ -56,8 +99,24 @@ impl Parse for EndpointMessages {
         }
 
         let _r_col = input.parse()?;
-        let receives_ty = input.parse()?;
-        let _r_com = input.parse().ok();
+        let mut receives_ty = input.parse()?;
+        ensure_owned(&receives_ty)?;
+        RebaseRelativePaths.visit_type_mut(&mut receives_ty);
+        let _r_com: Option<Token![,]> = input.parse().ok();
+
+        let mut errors_ty = None;
+        if _r_com.is_some() && !input.is_empty() {
+            let errors_kw: Ident = input.parse()?;
+            if errors_kw != "errors" {
+                return Err(SynError::new_spanned(errors_kw, "Expected 'errors'"));
+            }
+            input.parse::<Token![:]>()?;
+            let mut ty = input.parse()?;
+            ensure_owned(&ty)?;
+            RebaseRelativePaths.visit_type_mut(&mut ty);
+            input.parse::<Option<Token![,]>>()?;
+            errors_ty = Some(ty);
+        }
 
         Ok(Self {
             _sends_kw,
@@ -68,10 +127,92 @@ impl Parse for EndpointMessages {
             _r_col,
             receives_ty,
             _r_com,
+            errors_ty,
         })
     }
 }
 
+/// Channels take ownership of every message, so references can never go through them.
+fn ensure_owned(ty: &Type) -> SynResult<()> {
+    match ty {
+        Type::Reference(reference) => Err(SynError::new_spanned(
+            ty,
+            format!(
+                "Message types must be owned and 'static, since the channel takes ownership \
+                of every message. Use `{}` instead, or an owned handle such as `Arc<...>`.",
+                reference.elem.to_token_stream()
+            ),
+        )),
+        Type::Group(group) => ensure_owned(&group.elem),
+        Type::Paren(paren) => ensure_owned(&paren.elem),
+        _ => ensure_static(ty),
+    }
+}
+
+/// A message outlives the scope it was sent from, so it can't hold on to a borrow.
+/// Caught here to point at the lifetime, rather than at a bound deep in generated code.
+fn ensure_static(ty: &Type) -> SynResult<()> {
+    let mut lifetimes = Lifetimes::default();
+    lifetimes.visit_type(ty);
+    match lifetimes
+        .used
+        .iter()
+        .find(|lifetime| lifetime.ident != "static" && !lifetimes.bound.contains(lifetime))
+    {
+        Some(borrowed) => Err(SynError::new_spanned(
+            borrowed,
+            format!(
+                "Message types must be owned and 'static, since a message may outlive \
+                whatever it borrowed from, but `{}` ties this one to a borrow. Make the \
+                type own its data instead, e.g. through `String` or `Arc<...>`.",
+                borrowed
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Every lifetime a type names, and the ones it introduces itself with `for<...>`,
+/// which don't borrow from anything.
+#[derive(Default)]
+struct Lifetimes {
+    used: Vec<Lifetime>,
+    bound: Vec<Lifetime>,
+}
+
+impl<'ast> Visit<'ast> for Lifetimes {
+    fn visit_lifetime_param(&mut self, param: &'ast LifetimeParam) {
+        self.bound.push(param.lifetime.clone());
+        visit::visit_lifetime_param(self, param);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+        self.used.push(lifetime.clone());
+    }
+}
+
+/// Message types are spelled out in the generated link module, one level below the
+/// invocation, so paths starting with `self` or `super` get one more `super`.
+/// Other paths resolve the same in both places: absolute ones as they are, and
+/// relative ones through the module's `use super::*;`.
+struct RebaseRelativePaths;
+
+impl VisitMut for RebaseRelativePaths {
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        if path.leading_colon.is_none()
+            && let Some(first) = path.segments.first_mut()
+        {
+            if first.ident == "self" {
+                first.ident = Ident::new("super", first.ident.span());
+            } else if first.ident == "super" {
+                let span = first.ident.span();
+                path.segments.insert(0, Ident::new("super", span).into());
+            }
+        }
+        visit_mut::visit_path_mut(self, path);
+    }
+}
+
 pub struct EndpointDef {
     pub handle_name: Ident,
     pub _brace: token::Brace,
@@ -103,10 +244,93 @@ impl Parse for EndpointDef {
     }
 }
 
+/// BufferSize:
+/// `16`, `DEFAULT_BUF * 2` or `auto(mem = "1MB")`
+pub enum BufferSize {
+    Count(Expr),
+    /// A memory budget per direction, in bytes, to divide by the message size.
+    Auto {
+        call: Expr,
+        bytes: u64,
+    },
+}
+
+impl BufferSize {
+    fn from_expr(expr: Expr) -> SynResult<Self> {
+        let Expr::Call(call) = &expr else {
+            return Ok(Self::Count(expr));
+        };
+        let Expr::Path(func) = &*call.func else {
+            return Ok(Self::Count(expr));
+        };
+        if !func.path.is_ident("auto") {
+            return Ok(Self::Count(expr));
+        }
+
+        let mut args = call.args.iter();
+        let (Some(Expr::Assign(arg)), None) = (args.next(), args.next()) else {
+            return Err(SynError::new_spanned(
+                &call.args,
+                "Expected a single `mem = \"...\"` argument, e.g. `auto(mem = \"1MB\")`",
+            ));
+        };
+        if !matches!(&*arg.left, Expr::Path(key) if key.path.is_ident("mem")) {
+            return Err(SynError::new_spanned(&arg.left, "Expected 'mem'"));
+        }
+        let bytes = match &*arg.right {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(budget),
+                ..
+            }) => parse_bytes(&budget.value()).ok_or_else(|| {
+                SynError::new_spanned(
+                    budget,
+                    "Expected a size such as \"512KB\", \"1MB\" or \"2GiB\"",
+                )
+            })?,
+            other => {
+                return Err(SynError::new_spanned(
+                    other,
+                    "Expected a string literal, e.g. `mem = \"1MB\"`",
+                ));
+            }
+        };
+        if bytes == 0 {
+            return Err(SynError::new_spanned(
+                &arg.right,
+                "Memory budget must be greater than zero",
+            ));
+        }
+
+        Ok(Self::Auto { call: expr, bytes })
+    }
+}
+
+/// Parses sizes like `"4096"`, `"64KB"`, `"1MB"` or `"1MiB"`: KB, MB and GB are powers
+/// of 1000, KiB, MiB and GiB powers of 1024.
+fn parse_bytes(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '_')
+        .unwrap_or(size.len());
+    let (count, unit) = size.split_at(split);
+    let count: u64 = count.replace('_', "").parse().ok()?;
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        _ => return None,
+    };
+    count.checked_mul(multiplier)
+}
+
 pub struct BufferArg {
     pub _kw: Ident,
     pub _col: Token![:],
-    pub value: LitInt,
+    pub value: BufferSize,
     pub _com: Option<Token![,]>,
 }
 
@@ -120,26 +344,324 @@ impl Parse for BufferArg {
         Ok(Self {
             _kw,
             _col: input.parse()?,
+            value: BufferSize::from_expr(input.parse()?)?,
+            _com: input.parse().ok(),
+        })
+    }
+}
+
+/// FlagArg:
+/// `instrumented: true`
+pub struct FlagArg {
+    pub kw: Ident,
+    pub _col: Token![:],
+    pub value: LitBool,
+    pub _com: Option<Token![,]>,
+}
+
+impl Parse for FlagArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        Ok(Self {
+            kw: input.parse()?,
+            _col: input.parse()?,
             value: input.parse()?,
             _com: input.parse().ok(),
         })
     }
 }
 
+/// MetaEntry:
+/// `owner: "team-x"`
+pub struct MetaEntry {
+    pub key: Ident,
+    pub _col: Token![:],
+    pub value: LitStr,
+}
+
+impl Parse for MetaEntry {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        Ok(Self {
+            key: input.parse()?,
+            _col: input.parse()?,
+            value: input.parse()?,
+        })
+    }
+}
+
+/// MetaArg:
+/// `meta: { description: "...", owner: "team-x" }`
+pub struct MetaArg {
+    pub _kw: Ident,
+    pub _col: Token![:],
+    pub _brace: token::Brace,
+    pub entries: Punctuated<MetaEntry, Token![,]>,
+    pub _com: Option<Token![,]>,
+}
+
+impl Parse for MetaArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let _kw: Ident = input.parse()?;
+        if _kw != "meta" {
+            return Err(SynError::new_spanned(&_kw, "Expected 'meta'"));
+        }
+        let _col = input.parse()?;
+
+        let content;
+        let _brace = braced!(content in input);
+        let entries: Punctuated<MetaEntry, Token![,]> =
+            content.parse_terminated(MetaEntry::parse, Token![,])?;
+
+        for (i, entry) in entries.iter().enumerate() {
+            if entries.iter().take(i).any(|prev| prev.key == entry.key) {
+                return Err(SynError::new_spanned(
+                    &entry.key,
+                    format!("Duplicate meta key '{}'", entry.key),
+                ));
+            }
+        }
+
+        Ok(Self {
+            _kw,
+            _col,
+            _brace,
+            entries,
+            _com: input.parse().ok(),
+        })
+    }
+}
+
+/// CfgArg:
+/// `cfg: feature = "audio"`, or any other `cfg` predicate, e.g. `cfg: all(unix, test)`
+pub struct CfgArg {
+    pub _kw: Ident,
+    pub _col: Token![:],
+    pub predicate: Meta,
+    pub _com: Option<Token![,]>,
+}
+
+impl Parse for CfgArg {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let _kw: Ident = input.parse()?;
+        if _kw != "cfg" {
+            return Err(SynError::new_spanned(&_kw, "Expected 'cfg'"));
+        }
+        Ok(Self {
+            _kw,
+            _col: input.parse()?,
+            predicate: input.parse()?,
+            _com: input.parse().ok(),
+        })
+    }
+}
+
 pub struct DefineCommsLinkInput {
     pub link_id_arg: LinkIdArg,
+    /// From the `module` argument if given, the snake-cased `link_id` otherwise.
+    pub module_name: Ident,
     pub ep1_def: EndpointDef,
     pub ep2_def: EndpointDef,
     pub buffer_arg: BufferArg,
+    pub instrumented: bool,
+    pub acked: bool,
+    pub rendezvous: bool,
+    pub watch: bool,
+    pub timestamped: bool,
+    pub reexport_markers: bool,
+    pub run_helpers: bool,
+    pub behaviors: bool,
+    pub connect: bool,
+    pub framing: bool,
+    pub meta_arg: Option<MetaArg>,
+    pub cfg_arg: Option<CfgArg>,
 }
 
+const KNOWN_FLAGS: &[&str] = &[
+    "instrumented",
+    "acked",
+    "rendezvous",
+    "watch",
+    "timestamped",
+    "reexport_markers",
+    "run_helpers",
+    "behaviors",
+    "connect",
+    "framing",
+];
+
+/// Links are point-to-point: anything beyond two endpoints needs its own link.
+///
+/// Not configurable, since the generated code only ever wires one pair of endpoints.
+/// A tunable cap on pairwise channels only makes sense once links support more.
+pub const MAX_ENDPOINTS: usize = 2;
+
 impl Parse for DefineCommsLinkInput {
     fn parse(input: ParseStream) -> SynResult<Self> {
+        let link_id_arg: LinkIdArg = input.parse()?;
+        let module_name = if input.peek(Ident) && input.fork().parse::<Ident>()? == "module" {
+            input.parse::<ModuleArg>()?.name
+        } else {
+            let link_id = &link_id_arg.name;
+            syn::parse_str::<Ident>(&link_id.value().to_snake_case()).map_err(|_| {
+                SynError::new_spanned(
+                    link_id,
+                    format!(
+                        "link_id {} doesn't make a valid module name, \
+                        set one with `module: ...` after the link_id",
+                        link_id.to_token_stream()
+                    ),
+                )
+            })?
+        };
+        let ep1_def = input.parse()?;
+        let ep2_def = input.parse()?;
+
+        let mut extra_endpoints: Vec<EndpointDef> = Vec::new();
+        while input.peek(Ident) && input.peek2(token::Brace) {
+            extra_endpoints.push(input.parse()?);
+        }
+        if let Some(first_extra) = extra_endpoints.first() {
+            return Err(SynError::new_spanned(
+                &first_extra.handle_name,
+                format!(
+                    "Too many endpoints: found {}, but a link supports at most {}. \
+                    Define one link per pair of endpoints and compose them on the same Router \
+                    (e.g. with `Router::pipe`) to fan messages out.",
+                    2 + extra_endpoints.len(),
+                    MAX_ENDPOINTS
+                ),
+            ));
+        }
+
+        let buffer_arg = input.parse()?;
+
+        let mut instrumented = false;
+        let mut acked = false;
+        let mut rendezvous = false;
+        let mut watch = false;
+        let mut timestamped = false;
+        let mut reexport_markers = false;
+        let mut run_helpers = false;
+        let mut behaviors = false;
+        let mut connect = false;
+        let mut framing = false;
+        let mut meta_arg: Option<MetaArg> = None;
+        let mut cfg_arg: Option<CfgArg> = None;
+        while !input.is_empty() {
+            if input.fork().parse::<Ident>()? == "cfg" {
+                let cfg: CfgArg = input.parse()?;
+                if cfg_arg.is_some() {
+                    return Err(SynError::new_spanned(&cfg._kw, "Duplicate 'cfg' argument"));
+                }
+                cfg_arg = Some(cfg);
+                continue;
+            }
+
+            if input.fork().parse::<Ident>()? == "meta" {
+                let meta: MetaArg = input.parse()?;
+                if meta_arg.is_some() {
+                    return Err(SynError::new_spanned(&meta._kw, "Duplicate 'meta' block"));
+                }
+                meta_arg = Some(meta);
+                continue;
+            }
+
+            let flag: FlagArg = input.parse()?;
+            let value = flag.value.value;
+            match flag.kw.to_string().as_str() {
+                "instrumented" => instrumented = value,
+                "acked" => acked = value,
+                "rendezvous" => rendezvous = value,
+                "watch" => watch = value,
+                "timestamped" => timestamped = value,
+                "reexport_markers" => reexport_markers = value,
+                "run_helpers" => run_helpers = value,
+                "behaviors" => behaviors = value,
+                "connect" => connect = value,
+                "framing" => framing = value,
+                other => {
+                    return Err(SynError::new_spanned(
+                        &flag.kw,
+                        format!(
+                            "Unknown option '{}', expected one of: {}",
+                            other,
+                            KNOWN_FLAGS.join(", ")
+                        ),
+                    ));
+                }
+            }
+
+            if [instrumented, acked, rendezvous, watch, timestamped]
+                .iter()
+                .filter(|f| **f)
+                .count()
+                > 1
+            {
+                return Err(SynError::new_spanned(
+                    &flag.kw,
+                    "Only one of 'instrumented', 'acked', 'rendezvous', 'watch' and \
+                    'timestamped' can be set on a link",
+                ));
+            }
+
+            if watch && (run_helpers || behaviors) {
+                return Err(SynError::new_spanned(
+                    &flag.kw,
+                    "'run_helpers' and 'behaviors' can't be used on 'watch' links, \
+                    which hold a latest value rather than a stream of messages",
+                ));
+            }
+
+            if timestamped && (run_helpers || behaviors) {
+                return Err(SynError::new_spanned(
+                    &flag.kw,
+                    "'run_helpers' and 'behaviors' can't be used on 'timestamped' links, \
+                    whose consumers receive every message along with its timestamp",
+                ));
+            }
+
+            if run_helpers && acked {
+                return Err(SynError::new_spanned(
+                    &flag.kw,
+                    "'run_helpers' can't be used on 'acked' links, \
+                    whose consumers decide when to ack each message",
+                ));
+            }
+
+            if behaviors && acked {
+                return Err(SynError::new_spanned(
+                    &flag.kw,
+                    "'behaviors' can't be used on 'acked' links, \
+                    whose consumers decide when to ack each message",
+                ));
+            }
+
+            if behaviors && run_helpers {
+                return Err(SynError::new_spanned(
+                    &flag.kw,
+                    "'behaviors' and 'run_helpers' both generate the `run_*` functions, \
+                    only one of them can be set",
+                ));
+            }
+        }
+
         Ok(Self {
-            link_id_arg: input.parse()?,
-            ep1_def: input.parse()?,
-            ep2_def: input.parse()?,
-            buffer_arg: input.parse()?,
+            link_id_arg,
+            module_name,
+            ep1_def,
+            ep2_def,
+            buffer_arg,
+            instrumented,
+            acked,
+            rendezvous,
+            watch,
+            timestamped,
+            reexport_markers,
+            run_helpers,
+            behaviors,
+            connect,
+            framing,
+            meta_arg,
+            cfg_arg,
         })
     }
 }
@@ -0,0 +1,73 @@
+// Shared by every bench target, each of which only uses part of it.
+#![allow(dead_code)]
+
+use crosslink::Router;
+
+pub const BATCH: usize = 1024;
+
+pub struct BenchSend;
+pub struct BenchRecv;
+
+/// A representative mid-sized message: a few scalars and a heap allocation.
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub id: u64,
+    pub price: f64,
+    pub quantity: u32,
+    pub symbol: String,
+}
+
+impl Order {
+    pub fn new(id: u64) -> Self {
+        Self {
+            id,
+            price: 101.25,
+            quantity: 10,
+            symbol: String::from("XLNK"),
+        }
+    }
+}
+
+pub struct FillerSend<const N: usize>;
+pub struct FillerRecv<const N: usize>;
+
+/// The largest number of filler pathways [`register_fillers`] can add.
+pub const MAX_FILLERS: usize = 64;
+
+/// Surrounds the benched pathways with `count` others, so lookups don't hit a trivial map.
+pub fn register_fillers(router: &mut Router, count: usize) {
+    assert!(count <= MAX_FILLERS, "at most {MAX_FILLERS} fillers");
+
+    macro_rules! fillers {
+        ($($hi:literal)*; $lo:tt) => {$( fillers!(@row $hi; $lo); )*};
+        (@row $hi:literal; ($($lo:literal)*)) => {$(
+            if $hi * 8 + $lo < count {
+                router
+                    .__internal_register_pathway::<
+                        FillerSend<{ $hi * 8 + $lo }>,
+                        FillerRecv<{ $hi * 8 + $lo }>,
+                        u64,
+                    >(1)
+                    .expect("Failed to register filler pathway");
+            }
+        )*};
+    }
+    fillers!(0 1 2 3 4 5 6 7; (0 1 2 3 4 5 6 7));
+}
+
+/// A router with the benched pathway, `fillers` other pathways, and the benched
+/// pathway's receiver.
+pub fn router_with<Msg>(fillers: usize) -> (Router, tokio::sync::mpsc::Receiver<Msg>)
+where
+    Msg: Send + Sync + 'static + std::fmt::Debug,
+{
+    let mut router = Router::new();
+    register_fillers(&mut router, fillers);
+    router
+        .__internal_register_pathway::<BenchSend, BenchRecv, Msg>(BATCH)
+        .expect("Failed to register pathway");
+    let rx = router
+        .take_receiver::<BenchRecv, Msg>()
+        .expect("Failed to take receiver");
+    (router, rx)
+}
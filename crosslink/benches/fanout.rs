@@ -0,0 +1,53 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use crosslink::Router;
+use tokio::sync::mpsc;
+
+mod common;
+use common::{BATCH, FillerRecv, FillerSend, Order};
+
+const FANOUT: usize = 8;
+
+/// One producer delivering each message to `FANOUT` pathways.
+fn fanout(c: &mut Criterion) {
+    let mut router = Router::new();
+    let mut receivers: Vec<mpsc::Receiver<Order>> = Vec::with_capacity(FANOUT);
+
+    macro_rules! consumers {
+        ($($n:literal)*) => {$(
+            router
+                .__internal_register_pathway::<FillerSend<$n>, FillerRecv<$n>, Order>(BATCH)
+                .expect("Failed to register pathway");
+            receivers.push(
+                router
+                    .take_receiver::<FillerRecv<$n>, Order>()
+                    .expect("Failed to take receiver"),
+            );
+        )*};
+    }
+    consumers!(0 1 2 3 4 5 6 7);
+
+    let mut group = c.benchmark_group("fanout");
+    group.throughput(Throughput::Elements((BATCH * FANOUT) as u64));
+
+    group.bench_function("try_send", |b| {
+        b.iter(|| {
+            for i in 0..BATCH as u64 {
+                let order = Order::new(i);
+                macro_rules! deliver {
+                    ($($n:literal)*) => {$(
+                        router.try_send::<FillerSend<$n>, Order>(order.clone()).unwrap();
+                    )*};
+                }
+                deliver!(0 1 2 3 4 5 6 7);
+            }
+            for rx in &mut receivers {
+                while rx.try_recv().is_ok() {}
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, fanout);
+criterion_main!(benches);
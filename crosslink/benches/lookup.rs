@@ -0,0 +1,27 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+mod common;
+use common::{BATCH, BenchSend, MAX_FILLERS, router_with};
+
+/// Cost of finding the pathway among a growing number of others.
+fn lookup_cost(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    group.throughput(Throughput::Elements(BATCH as u64));
+
+    for fillers in [0, 8, MAX_FILLERS] {
+        let (router, mut rx) = router_with::<u64>(fillers);
+        group.bench_with_input(BenchmarkId::new("try_send", fillers), &fillers, |b, _| {
+            b.iter(|| {
+                for i in 0..BATCH as u64 {
+                    router.try_send::<BenchSend, u64>(i).unwrap();
+                }
+                while rx.try_recv().is_ok() {}
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, lookup_cost);
+criterion_main!(benches);
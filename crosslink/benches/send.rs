@@ -0,0 +1,89 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use crosslink::Router;
+
+mod common;
+use common::{BATCH, BenchRecv, BenchSend, Order, register_fillers, router_with};
+
+fn send_throughput(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to build runtime");
+
+    let (router, mut rx) = router_with::<u64>(0);
+    let (order_router, mut order_rx) = router_with::<Order>(0);
+
+    let mut group = c.benchmark_group("send");
+    group.throughput(Throughput::Elements(BATCH as u64));
+
+    group.bench_function("send", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                for i in 0..BATCH as u64 {
+                    router.send::<BenchSend, u64>(i).await.unwrap();
+                }
+            });
+            while rx.try_recv().is_ok() {}
+        })
+    });
+
+    group.bench_function("try_send", |b| {
+        b.iter(|| {
+            for i in 0..BATCH as u64 {
+                router.try_send::<BenchSend, u64>(i).unwrap();
+            }
+            while rx.try_recv().is_ok() {}
+        })
+    });
+
+    group.bench_function("try_send_order", |b| {
+        b.iter(|| {
+            for i in 0..BATCH as u64 {
+                order_router
+                    .try_send::<BenchSend, Order>(Order::new(i))
+                    .unwrap();
+            }
+            while order_rx.try_recv().is_ok() {}
+        })
+    });
+
+    group.finish();
+}
+
+fn frozen_vs_router(c: &mut Criterion) {
+    let (router, mut rx) = router_with::<u64>(32);
+    let mut frozen_router = Router::new();
+    register_fillers(&mut frozen_router, 32);
+    frozen_router
+        .__internal_register_pathway::<BenchSend, BenchRecv, u64>(BATCH)
+        .expect("Failed to register pathway");
+    let frozen = frozen_router.freeze();
+    let mut frozen_rx = frozen
+        .take_receiver::<BenchRecv, u64>()
+        .expect("Failed to take receiver");
+
+    let mut group = c.benchmark_group("frozen");
+    group.throughput(Throughput::Elements(BATCH as u64));
+
+    group.bench_function("router", |b| {
+        b.iter(|| {
+            for i in 0..BATCH as u64 {
+                router.try_send::<BenchSend, u64>(i).unwrap();
+            }
+            while rx.try_recv().is_ok() {}
+        })
+    });
+
+    group.bench_function("frozen_router", |b| {
+        b.iter(|| {
+            for i in 0..BATCH as u64 {
+                frozen.try_send::<BenchSend, u64>(i).unwrap();
+            }
+            while frozen_rx.try_recv().is_ok() {}
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, send_throughput, frozen_vs_router);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use crosslink::Router;
+
+mod common;
+use common::{BATCH, BenchRecv, BenchSend, register_fillers};
+
+/// Cost of taking a receiver out of a freshly set up router.
+fn take_receiver(c: &mut Criterion) {
+    let mut group = c.benchmark_group("take_receiver");
+
+    for fillers in [0, 32] {
+        group.bench_function(BenchmarkId::from_parameter(fillers), |b| {
+            b.iter_batched(
+                || {
+                    let mut router = Router::new();
+                    register_fillers(&mut router, fillers);
+                    router
+                        .__internal_register_pathway::<BenchSend, BenchRecv, u64>(BATCH)
+                        .expect("Failed to register pathway");
+                    router
+                },
+                |router| {
+                    let rx = router.take_receiver::<BenchRecv, u64>().unwrap();
+                    // Dropped outside the measurement, along with the router.
+                    (router, rx)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, take_receiver);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+use crosslink::define_crosslink;
+use std::sync::Arc;
+
+pub use counter_link::{CounterBehavior, connect, marker::*, run_counter};
+
+define_crosslink! {
+    link_id: "CounterLink",
+    Client {
+        sends: Add,
+        receives: Total,
+    },
+    Counter {
+        sends: Total,
+        receives: Add,
+    },
+    buffer_size: 8,
+    behaviors: true,
+    connect: true,
+}
+
+#[derive(Debug, Clone)]
+pub struct Add(u64);
+
+#[derive(Debug, Clone)]
+pub struct Total(u64);
+
+/// Keeps a running total, replying with it every time it crosses a multiple of ten.
+struct RunningTotal {
+    total: u64,
+}
+
+impl CounterBehavior for RunningTotal {
+    async fn on_message(&mut self, Add(amount): Add) -> Option<Total> {
+        let before = self.total / 10;
+        self.total += amount;
+        tokio::task::yield_now().await;
+        (self.total / 10 > before).then_some(Total(self.total))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The counter link is the only one this app needs, so `connect` sets up its router.
+    let (router, _client, _counter) = connect(None);
+
+    // The generated `run_counter` takes the counter's receiver and awaits the behavior
+    // on every message, so nothing else may take that receiver.
+    let counter_router = Arc::clone(&router);
+    let counter = tokio::spawn(async move {
+        let mut behavior = RunningTotal { total: 0 };
+        run_counter(&counter_router, &mut behavior).await
+    });
+
+    let mut totals = router.take_receiver::<ClientRecv, Total>()?;
+    for amount in [4, 7, 3, 12] {
+        router.send::<ClientSend, _>(Add(amount)).await?;
+    }
+    for _ in 0..2 {
+        if let Some(Total(total)) = totals.recv().await {
+            println!("[Client] total crossed to {}", total);
+        }
+    }
+
+    // As with `run_helpers`, the counter task holds the router, so it is stopped explicitly.
+    counter.abort();
+    let _ = counter.await;
+    Ok(())
+}
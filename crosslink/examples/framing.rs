@@ -0,0 +1,76 @@
+//! Messages of a link framed with their tag, as they would be written to a socket
+//! shared by both directions, and dispatched back to their type on the other end.
+//!
+//! Run with `cargo run --example framing --features serde`.
+
+use crosslink::{
+    define_crosslink,
+    framing::{self, Format},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Query {
+    pub key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Answer {
+    pub key: String,
+    pub value: Option<u64>,
+}
+
+define_crosslink! {
+    link_id: "LookupLink",
+    Client { sends: Query, receives: Answer },
+    Server { sends: Answer, receives: Query },
+    buffer_size: 8,
+    framing: true,
+}
+
+use lookup_link::{Client, Frame, Server};
+
+/// Frames payloads as JSON.
+struct Json;
+
+impl Format for Json {
+    type Error = serde_json::Error;
+
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec(value)
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Self::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+// Both tags go over the same connection, so they must differ.
+const _: () = assert!(framing::tags_distinct(&[Client::TAG, Server::TAG]));
+
+fn main() {
+    let outgoing = [
+        Frame::Client(Query {
+            key: "answer".into(),
+        }),
+        Frame::Server(Answer {
+            key: "answer".into(),
+            value: Some(42),
+        }),
+    ];
+
+    let wire: Vec<Vec<u8>> = outgoing
+        .iter()
+        .map(|frame| frame.encode_framed::<Json>().unwrap())
+        .collect();
+
+    for bytes in &wire {
+        match Frame::decode_framed::<Json>(bytes).unwrap() {
+            Frame::Client(query) => println!("server got {:?}", query),
+            Frame::Server(answer) => println!("client got {:?}", answer),
+        }
+    }
+
+    let unknown = [0xff, 0xff, b'{', b'}'];
+    println!("{}", Frame::decode_framed::<Json>(&unknown).unwrap_err());
+}
@@ -0,0 +1,120 @@
+use crosslink::{Router, define_crosslink};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+pub use job_link::{marker::*, setup_job_link};
+
+define_crosslink! {
+    link_id: "JobLink",
+    Producer {
+        sends: Job,
+        receives: Done,
+    },
+    Worker {
+        sends: Done,
+        receives: Job,
+    },
+    buffer_size: 4,
+}
+
+#[derive(Debug)]
+pub struct Job(u32);
+
+#[derive(Debug)]
+pub struct Done(u32);
+
+/// A cancellation token built on `watch`: cheap to clone, and every clone sees
+/// `cancel` at once, whenever it starts waiting. `tokio_util`'s `CancellationToken`
+/// slots in the same way.
+#[derive(Clone)]
+struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    fn new() -> (watch::Sender<bool>, Self) {
+        let (cancel, token) = watch::channel(false);
+        (cancel, Self(token))
+    }
+
+    async fn cancelled(&mut self) {
+        // Only fails once the sender is dropped, which counts as cancelled too.
+        let _ = self.0.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// Confirmations are best-effort: once `main` stops reading them, a full buffer must
+/// not hold the worker up, so they're sent with `try_send`.
+fn confirm(router: &Router, Job(id): Job) {
+    let _ = router.try_send::<WorkerSend, _>(Done(id));
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut router = Router::new();
+    setup_job_link(&mut router, None);
+    let router = router.into_shared();
+    let (cancel, shutdown) = Shutdown::new();
+
+    // The producer stops queueing new jobs as soon as shutdown starts.
+    let producer_router = Arc::clone(&router);
+    let mut producer_shutdown = shutdown.clone();
+    let producer = tokio::spawn(async move {
+        let mut next = 0;
+        loop {
+            tokio::select! {
+                // Checked first, so no job is sent once shutdown has started.
+                biased;
+                _ = producer_shutdown.cancelled() => break,
+                sent = producer_router.send::<ProducerSend, _>(Job(next)) => {
+                    sent?;
+                    next += 1;
+                }
+            }
+        }
+        println!("[Producer] Stopped after queueing {} jobs", next);
+        Ok::<_, crosslink::CommsError>(next)
+    });
+
+    // The worker handles jobs until shutdown, then finishes whatever is still buffered,
+    // so every job the producer queued gets done.
+    let worker_router = Arc::clone(&router);
+    let mut worker_shutdown = shutdown;
+    let mut jobs = router.take_receiver::<WorkerRecv, Job>()?;
+    let worker = tokio::spawn(async move {
+        let mut handled = 0;
+        loop {
+            tokio::select! {
+                _ = worker_shutdown.cancelled() => break,
+                Some(job) = jobs.recv() => {
+                    confirm(&worker_router, job);
+                    handled += 1;
+                }
+            }
+        }
+        while let Ok(job) = jobs.try_recv() {
+            confirm(&worker_router, job);
+            handled += 1;
+        }
+        println!("[Worker] Drained and stopped after {} jobs", handled);
+        Ok::<_, crosslink::CommsError>(handled)
+    });
+
+    // Shut down once the first few jobs are confirmed, rather than after some delay.
+    let mut done = router.take_receiver::<ProducerRecv, Done>()?;
+    for _ in 0..3 {
+        if let Some(Done(id)) = done.recv().await {
+            println!("[Main] Job {} done", id);
+        }
+    }
+    println!("[Main] Shutting down");
+    cancel.send_replace(true);
+
+    // Both tasks are joined: nothing is left running when `main` returns.
+    let queued = producer.await??;
+    let handled = worker.await??;
+    assert_eq!(
+        queued, handled,
+        "every queued job is handled before shutdown"
+    );
+    println!("[Main] All {} jobs handled, exiting", handled);
+    Ok(())
+}
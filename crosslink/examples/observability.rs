@@ -0,0 +1,85 @@
+use crosslink::{Router, define_crosslink};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+pub use telemetry_link::{marker::*, setup_telemetry_link};
+
+/// Shared by every telemetry link.
+const TELEMETRY_BUFFER: usize = 16;
+
+define_crosslink! {
+    link_id: "TelemetryLink",
+    SensorHandle {
+        sends: Reading,
+        receives: Command,
+    },
+    ControllerHandle {
+        sends: Command,
+        receives: Reading,
+    },
+    buffer_size: TELEMETRY_BUFFER,
+    // Without it, sends are still counted and traced, but no latencies are recorded.
+    instrumented: true,
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+struct Reading(f64);
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+enum Command {
+    Calibrate,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Spans and events, at trace level to see every message.
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .init();
+
+    // An in-memory recorder, standing in for e.g. a Prometheus exporter.
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    recorder.install()?;
+
+    let mut router = Router::new();
+    setup_telemetry_link(&mut router, None);
+
+    let mut readings = router.take_instrumented_receiver::<ControllerHandleRecv, Reading>()?;
+
+    for i in 0..5 {
+        router
+            .send::<SensorHandleSend, _>(Reading(i as f64))
+            .await?;
+    }
+    for _ in 0..5 {
+        readings.recv().await;
+    }
+
+    // Nobody took the sensor's receiver, so these fill the buffer and start failing.
+    for _ in 0..20 {
+        let _ = router.try_send::<ControllerHandleSend, _>(Command::Calibrate);
+    }
+
+    println!("\n--- Metrics snapshot ---");
+    for (key, _, _, value) in snapshotter.snapshot().into_vec() {
+        let labels: Vec<_> = key
+            .key()
+            .labels()
+            .map(|l| format!("{}={}", l.key(), l.value()))
+            .collect();
+        let value = match value {
+            DebugValue::Counter(n) => n.to_string(),
+            DebugValue::Gauge(g) => g.to_string(),
+            DebugValue::Histogram(h) => format!("{} samples", h.len()),
+        };
+        println!("{} {{{}}} {}", key.key().name(), labels.join(", "), value);
+    }
+
+    // The same measurements, straight from the router.
+    println!("\n--- Latency ---");
+    println!("{:?}", router.pathway_latency::<SensorHandleSend>());
+
+    Ok(())
+}
@@ -37,7 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     setup_ping_pong_link(&mut router, None);
 
     // New Arc so we can pass it around
-    let router = Arc::new(router);
+    let router = router.into_shared();
 
     // Freely clonable into any tokio task
     let pinger_router = Arc::clone(&router);
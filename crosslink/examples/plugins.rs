@@ -0,0 +1,66 @@
+//! Links defined in independent modules, standing in for plugins, all set up by
+//! `Router::collect_all` without listing their setup functions anywhere.
+//!
+//! Run with `cargo run --example plugins --features inventory`.
+
+use crosslink::{Router, define_crosslink, registry};
+
+mod audio {
+    use super::*;
+
+    #[derive(Debug)]
+    pub struct Play(pub &'static str);
+
+    define_crosslink! {
+        link_id: "AudioLink",
+        Player { sends: (), receives: Play },
+        Mixer { sends: Play, receives: () },
+        buffer_size: 8,
+        reexport_markers: true,
+    }
+}
+
+mod video {
+    use super::*;
+
+    #[derive(Debug)]
+    pub struct Frame(pub u64);
+
+    // Same handle names as the audio plugin: the markers are still distinct types.
+    define_crosslink! {
+        link_id: "VideoLink",
+        Player { sends: (), receives: Frame },
+        Mixer { sends: Frame, receives: () },
+        buffer_size: 8,
+        reexport_markers: true,
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut links: Vec<_> = registry::links().map(|link| link.link_id()).collect();
+    links.sort_unstable();
+    println!("Collected links: {links:?}");
+
+    let router = Router::collect_all().into_shared();
+
+    let mut sounds = router
+        .take_receiver::<audio::audio_link::PlayerRecv, audio::Play>()
+        .unwrap();
+    let mut frames = router
+        .take_receiver::<video::video_link::PlayerRecv, video::Frame>()
+        .unwrap();
+
+    router
+        .send::<audio::audio_link::MixerSend, _>(audio::Play("chime.ogg"))
+        .await
+        .unwrap();
+    router
+        .send::<video::video_link::MixerSend, _>(video::Frame(1))
+        .await
+        .unwrap();
+
+    let audio::Play(sound) = sounds.recv().await.unwrap();
+    let video::Frame(frame) = frames.recv().await.unwrap();
+    println!("Audio player plays {sound}, video player shows frame {frame}");
+}
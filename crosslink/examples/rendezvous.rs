@@ -0,0 +1,87 @@
+use crosslink::{Router, define_crosslink};
+use std::sync::Arc;
+
+pub use rendezvous_link::{marker::*, setup_rendezvous_link};
+
+define_crosslink! {
+    link_id: "RendezvousLink",
+    PingerHandle {
+        sends: Ping,
+        receives: Pong,
+    },
+    PongerHandle {
+        sends: Pong,
+        receives: Ping,
+    },
+    buffer_size: 1,
+    rendezvous: true,
+}
+
+#[derive(Debug, Clone)]
+struct Ping(u32);
+
+#[derive(Debug, Clone)]
+struct Pong(u32);
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut router = Router::new();
+    setup_rendezvous_link(&mut router, None);
+    let router = router.into_shared();
+
+    let pinger_router = Arc::clone(&router);
+    let pinger = tokio::spawn(async move {
+        // Rendezvous pathways hand out their own receiver type,
+        // which releases the sender as each message is taken.
+        let mut pinger_rx = pinger_router
+            .take_rendezvous_receiver::<PingerHandleRecv, Pong>()
+            .expect("Pinger failed to take Pong receiver");
+
+        for i in 0..3 {
+            println!("[Pinger] Sending: {:?}", Ping(i));
+
+            // Resolves only once the Ponger has taken the Ping,
+            // nothing is ever buffered in between.
+            pinger_router
+                .send::<PingerHandleSend, _>(Ping(i))
+                .await
+                .expect("Pinger send failed");
+            println!("[Pinger] Ping {} was taken", i);
+
+            // The Ponger is blocked in its own send until we get here.
+            // Had we sent again instead of receiving, both sides would wait on
+            // each other forever: the classic rendezvous deadlock.
+            match pinger_rx.recv().await {
+                Some(Pong(n)) => println!("[Pinger] Received: Pong({})", n),
+                None => break,
+            }
+        }
+        println!("[Pinger] Finished.");
+    });
+
+    let ponger_router = Arc::clone(&router);
+    let ponger = tokio::spawn(async move {
+        let mut ponger_rx = ponger_router
+            .take_rendezvous_receiver::<PongerHandleRecv, Ping>()
+            .expect("Ponger failed to take Ping receiver");
+
+        for _ in 0..3 {
+            let Some(Ping(n)) = ponger_rx.recv().await else {
+                break;
+            };
+            println!("[Ponger] Received: Ping({})", n);
+
+            ponger_router
+                .send::<PongerHandleSend, _>(Pong(n))
+                .await
+                .expect("Ponger send failed");
+        }
+        println!("[Ponger] Finished.");
+    });
+
+    // Every exchange is a handoff, so both tasks run to completion in lockstep
+    // and can simply be joined.
+    pinger.await?;
+    ponger.await?;
+    Ok(())
+}
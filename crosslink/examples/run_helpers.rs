@@ -0,0 +1,63 @@
+use crosslink::{Router, define_crosslink};
+use std::sync::Arc;
+
+pub use echo_link::{marker::*, run_echo_server, setup_echo_link};
+
+define_crosslink! {
+    link_id: "EchoLink",
+    Client {
+        sends: Request,
+        receives: Reply,
+    },
+    EchoServer {
+        sends: Reply,
+        receives: Request,
+    },
+    buffer_size: 8,
+    run_helpers: true,
+}
+
+#[derive(Debug, Clone)]
+enum Request {
+    Echo(String),
+    Ignore,
+}
+
+#[derive(Debug, Clone)]
+struct Reply(String);
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut router = Router::new();
+    setup_echo_link(&mut router, None);
+    let router = router.into_shared();
+
+    // The whole server is a handler: the generated `run_echo_server` takes the
+    // receiver, loops on it and sends back whatever the handler returns.
+    let server_router = Arc::clone(&router);
+    let server = tokio::spawn(async move {
+        run_echo_server(&server_router, |request| match request {
+            Request::Echo(text) => Some(Reply(text.to_uppercase())),
+            Request::Ignore => None,
+        })
+        .await
+    });
+
+    let mut replies = router.take_receiver::<ClientRecv, Reply>()?;
+    for text in ["hello", "crosslink"] {
+        router
+            .send::<ClientSend, _>(Request::Echo(text.to_string()))
+            .await?;
+        router.send::<ClientSend, _>(Request::Ignore).await?;
+
+        if let Some(Reply(reply)) = replies.recv().await {
+            println!("[Client] {} -> {}", text, reply);
+        }
+    }
+
+    // `run_echo_server` returns once its link closes, but the server task holds the
+    // router that owns the link's senders, so here it has to be stopped explicitly.
+    server.abort();
+    let _ = server.await;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! A request/response pathway handed to code that only knows `tower::Service`, as
+//! middleware or a client library would take it.
+//!
+//! Run with `cargo run --example tower_service --features tower`.
+
+use std::{future::poll_fn, sync::Arc};
+
+use crosslink::{CommsError, Router, define_crosslink, service::Request};
+use tower::Service;
+
+#[derive(Debug)]
+pub struct Lookup(u32);
+
+define_crosslink! {
+    link_id: "LookupLink",
+    Client { sends: Request<Lookup, String>, receives: () },
+    Server { sends: (), receives: Request<Lookup, String> },
+    buffer_size: 4,
+    reexport_markers: true,
+}
+
+use lookup_link::{ClientSend, ServerRecv};
+
+/// Knows nothing about routers or pathways.
+async fn resolve<S>(service: &mut S, ids: &[u32]) -> Result<Vec<String>, S::Error>
+where
+    S: Service<Lookup, Response = String>,
+{
+    let mut names = Vec::with_capacity(ids.len());
+    for &id in ids {
+        poll_fn(|cx| service.poll_ready(cx)).await?;
+        names.push(service.call(Lookup(id)).await?);
+    }
+    Ok(names)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), CommsError> {
+    let mut router = Router::new();
+    lookup_link::setup_lookup_link(&mut router, None);
+    let router = Arc::new(router);
+
+    let mut requests = router.take_receiver::<ServerRecv, Request<Lookup, String>>()?;
+    tokio::spawn(async move {
+        while let Some(request) = requests.recv().await {
+            let (Lookup(id), reply) = request.into_parts();
+            let _ = reply.send(format!("user-{id}"));
+        }
+    });
+
+    let mut service = router.as_service::<ClientSend, Lookup, String>();
+    let names = resolve(&mut service, &[1, 2, 3]).await?;
+    assert_eq!(names, ["user-1", "user-2", "user-3"]);
+    println!("{names:?}");
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    pin::Pin,
+};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    backend::{ChannelBackend, TrySendError},
+    error::CommsError,
+    sender::{ConcreteSenderTrait, DynSender, Undelivered, downcast_error, try_send_error},
+};
+
+/// A message received on an acknowledged pathway.
+///
+/// The producer's `Router::send_acked` resolves once [`Acked::ack`] is called.
+/// Dropping the wrapper without acking resolves it with `CommsError::NotAcked`,
+/// so a consumer that fails mid-processing is visible to the producer.
+///
+/// Consumers can also report why processing failed with [`Acked::fail`], which
+/// `Router::send_expecting_result` hands back to the producer.
+#[derive(Debug)]
+pub struct Acked<T> {
+    msg: T,
+    ack_tx: Option<oneshot::Sender<Reply>>,
+}
+
+/// What a consumer answered to an acknowledged message.
+#[derive(Debug)]
+pub(crate) enum Reply {
+    Ack,
+    /// Holds the consumer's error, type-erased since the pathway doesn't know its type.
+    Failed(Box<dyn Any + Send>),
+}
+
+impl<T> Acked<T> {
+    /// The payload, for processing before acking.
+    pub fn message(&self) -> &T {
+        &self.msg
+    }
+
+    /// Acknowledges the message as processed and returns the payload.
+    pub fn ack(self) -> T {
+        self.reply(Reply::Ack)
+    }
+
+    /// Reports that processing the message failed with `error`, and returns the payload.
+    ///
+    /// The producer gets the error back from `Router::send_expecting_result`, as long as
+    /// it expects the same error type. `Router::send_acked` sees a plain `NotAcked`.
+    pub fn fail<E: Send + 'static>(self, error: E) -> T {
+        self.reply(Reply::Failed(Box::new(error)))
+    }
+
+    /// Acks or fails the message depending on `result`, and returns the payload.
+    pub fn respond<E: Send + 'static>(self, result: Result<(), E>) -> T {
+        match result {
+            Ok(()) => self.ack(),
+            Err(error) => self.fail(error),
+        }
+    }
+
+    /// Unwraps the payload without acknowledging it.
+    pub fn into_inner(self) -> T {
+        self.msg
+    }
+
+    fn reply(mut self, reply: Reply) -> T {
+        if let Some(ack_tx) = self.ack_tx.take() {
+            // The producer may have stopped waiting, that's fine.
+            let _ = ack_tx.send(reply);
+        }
+        self.msg
+    }
+}
+
+/// A sender for acknowledged pathways.
+///
+/// It accepts bare `T`s from `Router::send`, which are delivered without anyone
+/// waiting on the ack, as well as pre-built `Acked<T>`s from `Router::send_acked`.
+#[derive(Debug)]
+pub(crate) struct AckSender<B: ChannelBackend, T: ConcreteSenderTrait> {
+    pub sender: B::Sender<Acked<T>>,
+}
+
+impl<B: ChannelBackend, T: ConcreteSenderTrait> AckSender<B, T> {
+    fn into_acked(msg_any: Box<dyn Any + Send>) -> Result<Acked<T>, CommsError> {
+        match msg_any.downcast::<Acked<T>>() {
+            Ok(acked) => Ok(*acked),
+            Err(msg_any) => match msg_any.downcast::<T>() {
+                Ok(concrete_msg) => Ok(Acked {
+                    msg: *concrete_msg,
+                    ack_tx: None,
+                }),
+                Err(_) => Err(downcast_error::<T>()),
+            },
+        }
+    }
+
+    /// Hands a message the channel refused back in the form it was sent in: a bare `T`
+    /// unless it came with an ack to resolve.
+    fn refused(acked: Acked<T>, closed: bool) -> Undelivered {
+        match (acked.ack_tx.is_some(), closed) {
+            (true, true) => Undelivered::closed(acked),
+            (true, false) => Undelivered::full(acked),
+            (false, true) => Undelivered::closed(acked.msg),
+            (false, false) => Undelivered::full(acked.msg),
+        }
+    }
+}
+
+impl<B: ChannelBackend, T: ConcreteSenderTrait> DynSender for AckSender<B, T> {
+    fn send_erased(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        let acked = match Self::into_acked(msg_any) {
+            Ok(acked) => acked,
+            Err(e) => return Box::pin(async { Err(e) }),
+        };
+
+        let sender_clone = self.sender.clone();
+        Box::pin(async move {
+            B::send(&sender_clone, acked).await.map_err(|_| {
+                CommsError::SendFailed(
+                    format!(
+                        "Failed to send message of type {}: channel closed",
+                        std::any::type_name::<T>(),
+                    ),
+                    None,
+                )
+            })
+        })
+    }
+
+    fn try_send_erased(&self, msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        B::try_send(&self.sender, Self::into_acked(msg_any)?).map_err(try_send_error::<T, _>)
+    }
+
+    fn send_returning(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Undelivered>> + Send>> {
+        let acked = match Self::into_acked(msg_any) {
+            Ok(acked) => acked,
+            Err(e) => return Box::pin(async { Err(Undelivered::lost(e)) }),
+        };
+
+        let sender_clone = self.sender.clone();
+        Box::pin(async move {
+            B::send(&sender_clone, acked)
+                .await
+                .map_err(|acked| Self::refused(acked, true))
+        })
+    }
+
+    fn try_send_returning(&self, msg_any: Box<dyn Any + Send>) -> Result<(), Undelivered> {
+        let acked = Self::into_acked(msg_any).map_err(Undelivered::lost)?;
+        B::try_send(&self.sender, acked).map_err(|e| match e {
+            TrySendError::Closed(acked) => Self::refused(acked, true),
+            TrySendError::Full(acked) => Self::refused(acked, false),
+        })
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn buffered(&self) -> Option<usize> {
+        B::buffered(&self.sender)
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        B::is_closed(&self.sender)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(AckSender::<B, T> {
+            sender: self.sender.clone(),
+        })
+    }
+}
+
+/// Wraps `msg` for `Router::send_acked`, returning the receiving end of its ack.
+pub(crate) fn acked<T>(msg: T) -> (Acked<T>, oneshot::Receiver<Reply>) {
+    let (ack_tx, ack_rx) = oneshot::channel();
+    (
+        Acked {
+            msg,
+            ack_tx: Some(ack_tx),
+        },
+        ack_rx,
+    )
+}
@@ -0,0 +1,165 @@
+use std::{fmt::Debug, future::Future};
+
+/// Error returned by [`ChannelBackend::try_send`], handing the message back.
+#[derive(Debug)]
+pub enum TrySendError<T> {
+    /// The channel's buffer is full.
+    Full(T),
+    /// The receiving side is gone.
+    Closed(T),
+}
+
+/// A channel implementation that pathways can be built on.
+///
+/// Crosslink only needs to create a channel and push messages into it.
+/// Receiving is left to whatever receiver type the backend hands out, so the
+/// receiving side keeps the backend's own API (`recv`, streams, etc.).
+///
+/// Tokio's `mpsc` is the default, behind the `tokio` feature.
+/// Other executors can plug in their own channels (e.g. `async-channel`)
+/// by implementing this trait and registering pathways with
+/// [`Router::register_sender_with`](crate::Router::register_sender_with) and
+/// [`Router::register_receiver_with`](crate::Router::register_receiver_with).
+pub trait ChannelBackend: Debug + Send + Sync + 'static {
+    type Sender<T: Send + 'static>: Clone + Send + Sync + Debug + 'static;
+    type Receiver<T: Send + 'static>: Send + Debug + 'static;
+
+    /// Creates a bounded channel holding at most `buffer` messages.
+    fn channel<T: Send + 'static>(buffer: usize) -> (Self::Sender<T>, Self::Receiver<T>);
+
+    /// Sends a message, waiting for capacity if the channel is full.
+    /// Hands the message back if the receiving side is gone.
+    fn send<T: Send + 'static>(
+        sender: &Self::Sender<T>,
+        msg: T,
+    ) -> impl Future<Output = Result<(), T>> + Send;
+
+    /// Sends a message only if there's room for it right now, without waiting.
+    fn try_send<T: Send + 'static>(sender: &Self::Sender<T>, msg: T)
+    -> Result<(), TrySendError<T>>;
+
+    /// Number of messages currently buffered in the channel.
+    fn len<T: Send + 'static>(receiver: &Self::Receiver<T>) -> usize;
+
+    /// Number of messages currently buffered in the channel, as seen from the sending
+    /// side, or `None` if the backend can't tell, which is the default.
+    fn buffered<T: Send + 'static>(sender: &Self::Sender<T>) -> Option<usize> {
+        let _ = sender;
+        None
+    }
+
+    /// Whether the receiving side is gone, as seen from the sending side, or `None` if
+    /// the backend can't tell, which is the default.
+    fn is_closed<T: Send + 'static>(sender: &Self::Sender<T>) -> Option<bool> {
+        let _ = sender;
+        None
+    }
+}
+
+/// The default backend, built on `tokio::sync::mpsc`.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioBackend;
+
+#[cfg(feature = "tokio")]
+impl ChannelBackend for TokioBackend {
+    type Sender<T: Send + 'static> = tokio::sync::mpsc::Sender<T>;
+    type Receiver<T: Send + 'static> = tokio::sync::mpsc::Receiver<T>;
+
+    fn channel<T: Send + 'static>(buffer: usize) -> (Self::Sender<T>, Self::Receiver<T>) {
+        tokio::sync::mpsc::channel(buffer)
+    }
+
+    async fn send<T: Send + 'static>(sender: &Self::Sender<T>, msg: T) -> Result<(), T> {
+        sender.send(msg).await.map_err(|e| e.0)
+    }
+
+    fn try_send<T: Send + 'static>(
+        sender: &Self::Sender<T>,
+        msg: T,
+    ) -> Result<(), TrySendError<T>> {
+        sender.try_send(msg).map_err(|e| match e {
+            tokio::sync::mpsc::error::TrySendError::Full(msg) => TrySendError::Full(msg),
+            tokio::sync::mpsc::error::TrySendError::Closed(msg) => TrySendError::Closed(msg),
+        })
+    }
+
+    fn len<T: Send + 'static>(receiver: &Self::Receiver<T>) -> usize {
+        receiver.len()
+    }
+
+    fn buffered<T: Send + 'static>(sender: &Self::Sender<T>) -> Option<usize> {
+        // Counts reserved permits too, which are about to be filled anyway.
+        Some(sender.max_capacity() - sender.capacity())
+    }
+
+    fn is_closed<T: Send + 'static>(sender: &Self::Sender<T>) -> Option<bool> {
+        Some(sender.is_closed())
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub use tokio::sync::{mpsc as __mpsc, watch as __watch};
+
+/// Generates the channel type aliases of a link, one pair per endpoint. What an
+/// endpoint receives on depends on the link's flavor, and only plain links hand out a
+/// sender to name.
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __channel_aliases {
+    (plain; $($handle:ident: $receiver:ident = $receives:ty, $sender:ident = $sends:ty;)*) => {
+        $(
+            $crate::__channel_aliases!(@receiver $handle $receiver
+                = $crate::backend::__mpsc::Receiver<$receives>, "Router::take_receiver");
+
+            #[doc = concat!(
+                "The channel `", stringify!($handle), "` sends on, as lent out by `Router::with_sender`."
+            )]
+            #[allow(dead_code)]
+            pub type $sender = $crate::backend::__mpsc::Sender<$sends>;
+        )*
+    };
+    (acked; $($handle:ident: $receiver:ident = $receives:ty, $sender:ident = $sends:ty;)*) => {
+        $(
+            $crate::__channel_aliases!(@receiver $handle $receiver
+                = $crate::backend::__mpsc::Receiver<$crate::ack::Acked<$receives>>,
+                "Router::take_receiver");
+        )*
+    };
+    (instrumented; $($handle:ident: $receiver:ident = $receives:ty, $sender:ident = $sends:ty;)*) => {
+        $(
+            $crate::__channel_aliases!(@receiver $handle $receiver
+                = $crate::latency::InstrumentedReceiver<$receives>,
+                "Router::take_instrumented_receiver");
+        )*
+    };
+    (rendezvous; $($handle:ident: $receiver:ident = $receives:ty, $sender:ident = $sends:ty;)*) => {
+        $(
+            $crate::__channel_aliases!(@receiver $handle $receiver
+                = $crate::rendezvous::RendezvousReceiver<$receives>,
+                "Router::take_rendezvous_receiver");
+        )*
+    };
+    (timestamped; $($handle:ident: $receiver:ident = $receives:ty, $sender:ident = $sends:ty;)*) => {
+        $(
+            $crate::__channel_aliases!(@receiver $handle $receiver
+                = $crate::envelope::TimestampedReceiver<$receives>,
+                "Router::take_timestamped_receiver");
+        )*
+    };
+    (watch; $($handle:ident: $receiver:ident = $receives:ty, $sender:ident = $sends:ty;)*) => {
+        $(
+            $crate::__channel_aliases!(@receiver $handle $receiver
+                = $crate::backend::__watch::Receiver<$receives>, "Router::watch_receiver");
+        )*
+    };
+    (@receiver $handle:ident $receiver:ident = $ty:ty, $taken_with:literal) => {
+        #[doc = concat!(
+            "The receiver `", stringify!($handle), "` reads from, as returned by `", $taken_with, "`."
+        )]
+        #[allow(dead_code)]
+        pub type $receiver = $ty;
+    };
+}
@@ -0,0 +1,182 @@
+use std::{
+    any::{Any, TypeId},
+    pin::Pin,
+    sync::Arc,
+};
+
+use tokio::sync::mpsc;
+
+use crate::{error::CommsError, frozen::FrozenRouter, router::Router, sender::ConcreteSenderTrait};
+
+/// Sending and taking receivers by marker, as components need them, so they can depend
+/// on `impl MessageBus` rather than on a `Router`, and take a stub in tests.
+///
+/// Implemented by [`Router`], [`FrozenRouter`] and an `Arc` of either. The methods are
+/// generic over the marker and message types, so the trait can't be used as
+/// `dyn MessageBus`: see [`DynMessageBus`] for that.
+pub trait MessageBus: Send + Sync {
+    /// Sends a message on the pathway of `SenderMarker`, like `Router::send`.
+    fn send<SenderMarker, Msg>(
+        &self,
+        message: Msg,
+    ) -> impl Future<Output = Result<(), CommsError>> + Send
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait;
+
+    /// Takes the receiver of the pathway of `ReceiverMarker`, like
+    /// `Router::take_receiver`.
+    fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait;
+}
+
+impl MessageBus for Router {
+    fn send<SenderMarker, Msg>(
+        &self,
+        message: Msg,
+    ) -> impl Future<Output = Result<(), CommsError>> + Send
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        Router::send::<SenderMarker, Msg>(self, message)
+    }
+
+    #[track_caller]
+    fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        Router::take_receiver::<ReceiverMarker, Msg>(self)
+    }
+}
+
+impl MessageBus for FrozenRouter {
+    fn send<SenderMarker, Msg>(
+        &self,
+        message: Msg,
+    ) -> impl Future<Output = Result<(), CommsError>> + Send
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        FrozenRouter::send::<SenderMarker, Msg>(self, message)
+    }
+
+    #[track_caller]
+    fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        FrozenRouter::take_receiver::<ReceiverMarker, Msg>(self)
+    }
+}
+
+impl<B: MessageBus> MessageBus for Arc<B> {
+    fn send<SenderMarker, Msg>(
+        &self,
+        message: Msg,
+    ) -> impl Future<Output = Result<(), CommsError>> + Send
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        B::send::<SenderMarker, Msg>(self, message)
+    }
+
+    #[track_caller]
+    fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        B::take_receiver::<ReceiverMarker, Msg>(self)
+    }
+}
+
+/// The object-safe counterpart of [`MessageBus`], for components holding an
+/// `Arc<dyn DynMessageBus>`, e.g. to pick the bus at runtime.
+///
+/// Markers and messages go through as `TypeId`s and `Box<dyn Any>`, so what the
+/// compiler checks with `MessageBus` is only checked at runtime here, and every send
+/// boxes its message and its future. The typed `send` and `take_receiver` on
+/// `dyn DynMessageBus` wrap the erased methods, which are all a stub has to implement.
+/// Implemented by [`Router`].
+pub trait DynMessageBus: Send + Sync {
+    /// Sends a boxed message on the pathway whose sender marker has the `TypeId`
+    /// `sender_marker`, like `Router::send_erased`.
+    fn send_erased(
+        &self,
+        sender_marker: TypeId,
+        message: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send + '_>>;
+
+    /// Takes the receiver of the pathway whose receiver marker has the `TypeId`
+    /// `receiver_marker`, as a boxed `mpsc::Receiver` of the type whose `TypeId` is
+    /// `msg_type`, like `Router::take_receiver_erased`.
+    fn take_receiver_erased(
+        &self,
+        receiver_marker: TypeId,
+        msg_type: TypeId,
+    ) -> Result<Box<dyn Any + Send>, CommsError>;
+}
+
+impl dyn DynMessageBus {
+    /// Sends a message on the pathway of `SenderMarker`.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send<SenderMarker, Msg>(&self, message: Msg) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.send_erased(TypeId::of::<SenderMarker>(), Box::new(message))
+            .await
+    }
+
+    /// Takes the receiver of the pathway of `ReceiverMarker`.
+    ///
+    /// Fails with `CommsError::TypeMismatch` if the bus hands out anything but an
+    /// `mpsc::Receiver<Msg>`, which is then dropped.
+    pub fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.take_receiver_erased(TypeId::of::<ReceiverMarker>(), TypeId::of::<Msg>())?
+            .downcast::<mpsc::Receiver<Msg>>()
+            .map(|receiver| *receiver)
+            .map_err(|_| {
+                CommsError::TypeMismatch(
+                    format!(
+                        "Receiver '{}' isn't an mpsc::Receiver<{}>.",
+                        std::any::type_name::<ReceiverMarker>(),
+                        std::any::type_name::<Msg>()
+                    ),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                )
+            })
+    }
+}
+
+impl DynMessageBus for Router {
+    fn send_erased(
+        &self,
+        sender_marker: TypeId,
+        message: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send + '_>> {
+        Box::pin(Router::send_erased(self, sender_marker, message))
+    }
+
+    #[track_caller]
+    fn take_receiver_erased(
+        &self,
+        receiver_marker: TypeId,
+        msg_type: TypeId,
+    ) -> Result<Box<dyn Any + Send>, CommsError> {
+        Router::take_receiver_erased(self, receiver_marker, msg_type)
+    }
+}
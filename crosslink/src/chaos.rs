@@ -0,0 +1,94 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use tokio::{sync::mpsc, time::Instant};
+
+/// Faults injected into a pathway by `Router::chaos`.
+///
+/// Every message is first kept or dropped with `drop_prob`, then delivered `delay`
+/// after it arrived, and followed by a copy of itself with `dup_prob`. The default
+/// injects nothing, so fields can be set one at a time:
+///
+/// ```
+/// # use crosslink::chaos::ChaosConfig;
+/// let config = ChaosConfig { drop_prob: 0.1, seed: Some(7), ..Default::default() };
+/// # assert_eq!(config.dup_prob, 0.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChaosConfig {
+    /// Latency added to every message. Messages keep their order, so a burst is
+    /// delayed as a whole rather than spread out.
+    pub delay: Duration,
+    /// Probability, between 0 and 1, that a message is dropped.
+    pub drop_prob: f64,
+    /// Probability, between 0 and 1, that a delivered message is delivered twice.
+    pub dup_prob: f64,
+    /// Seeds the drop and duplication decisions. With a fixed seed, the same sequence
+    /// of messages is always dropped and duplicated the same way; without one, each
+    /// run picks a seed of its own.
+    pub seed: Option<u64>,
+}
+
+/// SplitMix64: tiny, fast, and plenty random for fault injection.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: Option<u64>) -> Self {
+        Self(seed.unwrap_or_else(|| RandomState::new().build_hasher().finish()))
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // The top 53 bits, as a float in [0, 1).
+        ((z >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+/// Forwards `upstream` into `downstream`, with the faults of `config`.
+///
+/// Messages are read and stamped as soon as they arrive, then held back in a buffer of
+/// the same capacity until their delay is up, so the delay is latency, not throughput.
+/// Ends once `upstream` is closed and drained, or `downstream` is dropped.
+pub(crate) async fn inject<Msg: Clone>(
+    mut upstream: mpsc::Receiver<Msg>,
+    downstream: mpsc::Sender<Msg>,
+    config: ChaosConfig,
+) {
+    let (delayed_tx, mut delayed_rx) = mpsc::channel(downstream.max_capacity());
+    let delay = config.delay;
+
+    let read = async move {
+        let mut rng = Rng::new(config.seed);
+        while let Some(msg) = upstream.recv().await {
+            let arrived = Instant::now();
+            if rng.chance(config.drop_prob) {
+                continue;
+            }
+            let duplicate = rng.chance(config.dup_prob);
+            if delayed_tx.send((arrived, msg, duplicate)).await.is_err() {
+                return;
+            }
+        }
+    };
+
+    let write = async move {
+        while let Some((arrived, msg, duplicate)) = delayed_rx.recv().await {
+            tokio::time::sleep_until(arrived + delay).await;
+            let duplicate = duplicate.then(|| msg.clone());
+            for msg in std::iter::once(msg).chain(duplicate) {
+                if downstream.send(msg).await.is_err() {
+                    return;
+                }
+            }
+        }
+    };
+
+    tokio::join!(read, write);
+}
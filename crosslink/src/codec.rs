@@ -0,0 +1,146 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::{self, Debug},
+    pin::Pin,
+    sync::Arc,
+};
+
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+use crate::{
+    backend::{ChannelBackend, TokioBackend},
+    error::CommsError,
+    sender::{ConcreteSenderTrait, DynSender, closed_error, downcast_error, try_send_error},
+};
+
+type Encode<Domain, Wire> = Box<dyn Fn(Domain) -> Wire + Send + Sync>;
+type Decode<Domain, Wire> = Box<dyn Fn(Wire) -> Domain + Send + Sync>;
+
+/// The hooks of a codec pathway, shared by its sender and [`DecodingReceiver`].
+pub(crate) struct Codec<Domain, Wire> {
+    encode: Encode<Domain, Wire>,
+    decode: Decode<Domain, Wire>,
+}
+
+impl<Domain, Wire> Codec<Domain, Wire> {
+    pub fn new(
+        encode: impl Fn(Domain) -> Wire + Send + Sync + 'static,
+        decode: impl Fn(Wire) -> Domain + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            encode: Box::new(encode),
+            decode: Box::new(decode),
+        }
+    }
+}
+
+impl<Domain, Wire> Debug for Codec<Domain, Wire> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Codec")
+            .field("domain", &std::any::type_name::<Domain>())
+            .field("wire", &std::any::type_name::<Wire>())
+            .finish()
+    }
+}
+
+/// A sender that accepts `Domain`s and encodes them into `Wire`s on the way in.
+#[derive(Debug)]
+pub(crate) struct CodecSender<Domain, Wire: Send + 'static> {
+    pub sender: mpsc::Sender<Wire>,
+    pub codec: Arc<Codec<Domain, Wire>>,
+}
+
+impl<Domain, Wire> DynSender for CodecSender<Domain, Wire>
+where
+    Domain: ConcreteSenderTrait,
+    Wire: Send + Sync + 'static + Debug,
+{
+    fn send_erased(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        match msg_any.downcast::<Domain>() {
+            Ok(concrete_msg) => {
+                let wire = (self.codec.encode)(*concrete_msg);
+                let sender_clone = self.sender.clone();
+                Box::pin(async move {
+                    TokioBackend::send(&sender_clone, wire)
+                        .await
+                        .map_err(|_| closed_error::<Domain>())
+                })
+            }
+            Err(_) => Box::pin(async { Err(downcast_error::<Domain>()) }),
+        }
+    }
+
+    fn try_send_erased(&self, msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        let concrete_msg = msg_any
+            .downcast::<Domain>()
+            .map_err(|_| downcast_error::<Domain>())?;
+        TokioBackend::try_send(&self.sender, (self.codec.encode)(*concrete_msg))
+            .map_err(try_send_error::<Domain, _>)
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<Domain>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<Domain>()
+    }
+
+    fn buffered(&self) -> Option<usize> {
+        TokioBackend::buffered(&self.sender)
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        TokioBackend::is_closed(&self.sender)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(CodecSender {
+            sender: self.sender.clone(),
+            codec: Arc::clone(&self.codec),
+        })
+    }
+}
+
+/// Receiving half of a codec pathway, decoding each `Wire` back into a `Domain`.
+#[derive(Debug)]
+pub struct DecodingReceiver<Domain, Wire> {
+    receiver: mpsc::Receiver<Wire>,
+    codec: Arc<Codec<Domain, Wire>>,
+}
+
+impl<Domain, Wire> DecodingReceiver<Domain, Wire> {
+    pub(crate) fn new(receiver: mpsc::Receiver<Wire>, codec: Arc<Codec<Domain, Wire>>) -> Self {
+        Self { receiver, codec }
+    }
+
+    /// Receives and decodes the next message, same as `mpsc::Receiver::recv`.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: a message is decoded in the same poll it's received
+    /// in, so dropping a pending call, e.g. in a `tokio::select!`, never loses one.
+    pub async fn recv(&mut self) -> Option<Domain> {
+        let wire = self.receiver.recv().await?;
+        Some((self.codec.decode)(wire))
+    }
+
+    /// Receives and decodes a message if one is buffered, without waiting.
+    pub fn try_recv(&mut self) -> Result<Domain, TryRecvError> {
+        self.receiver
+            .try_recv()
+            .map(|wire| (self.codec.decode)(wire))
+    }
+
+    /// Number of messages buffered, still encoded.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+}
@@ -0,0 +1,46 @@
+use std::any::{Any, TypeId};
+
+use tokio::sync::mpsc;
+
+use crate::{error::CommsError, hash::TypeIdMap, sender::Undelivered};
+
+/// Dead-letter channels set with `Router::set_dead_letter`, keyed by message type.
+#[derive(Debug, Default)]
+pub(crate) struct DeadLetters {
+    /// Each value is the `mpsc::Sender<Msg>` for its `Msg` key.
+    senders: TypeIdMap<Box<dyn Any + Send + Sync>>,
+}
+
+impl DeadLetters {
+    pub fn set<Msg: Send + 'static>(&mut self, sender: mpsc::Sender<Msg>) {
+        self.senders.insert(TypeId::of::<Msg>(), Box::new(sender));
+    }
+
+    pub fn get<Msg: Send + 'static>(&self) -> Option<&mpsc::Sender<Msg>> {
+        // Keeps the common case, no dead letters at all, to a length check.
+        if self.senders.is_empty() {
+            return None;
+        }
+        self.senders
+            .get(&TypeId::of::<Msg>())
+            .and_then(|sender| sender.downcast_ref())
+    }
+}
+
+/// Pushes a message the channel handed back because it's closed into its dead-letter
+/// channel, returning the original error either way.
+///
+/// Never waits: if the dead-letter channel is full or closed, the message is dropped.
+/// Messages handed back for any other reason, e.g. a full channel, are dropped too.
+pub(crate) fn forward<Msg: Send + 'static>(
+    undelivered: Undelivered,
+    dead_letter: &mpsc::Sender<Msg>,
+) -> CommsError {
+    if undelivered.error.is_disconnected()
+        && let Some(message) = undelivered.message
+        && let Ok(message) = message.downcast::<Msg>()
+    {
+        let _ = dead_letter.try_send(*message);
+    }
+    undelivered.error
+}
@@ -0,0 +1,91 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use tokio::sync::mpsc;
+
+type KeyFn<Msg, K> = Box<dyn Fn(&Msg) -> K + Send + Sync>;
+
+/// A receiver that skips messages whose key it delivered recently, as returned by
+/// `Router::take_deduped_receiver`.
+///
+/// Only the keys of the last `window` messages delivered are remembered, in a set and
+/// a queue of that size, so memory stays bounded no matter how long it runs. That also
+/// makes it best-effort: a duplicate arriving after `window` other messages is no
+/// longer recognized, and delivered again. Size the window for how far apart the
+/// source can redeliver a message, e.g. a retry relay's attempts.
+pub struct DedupReceiver<Msg, K> {
+    receiver: mpsc::Receiver<Msg>,
+    key_fn: KeyFn<Msg, K>,
+    window: usize,
+    seen: HashSet<K>,
+    order: VecDeque<K>,
+    skipped: u64,
+}
+
+impl<Msg, K: Hash + Eq + Clone> DedupReceiver<Msg, K> {
+    pub(crate) fn new(
+        receiver: mpsc::Receiver<Msg>,
+        key_fn: impl Fn(&Msg) -> K + Send + Sync + 'static,
+        window: usize,
+    ) -> Self {
+        Self {
+            receiver,
+            key_fn: Box::new(key_fn),
+            window,
+            seen: HashSet::with_capacity(window),
+            order: VecDeque::with_capacity(window),
+            skipped: 0,
+        }
+    }
+
+    /// Receives the next message whose key isn't among the last `window` delivered,
+    /// dropping any duplicate on the way. Returns `None` once the channel is closed
+    /// and drained.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: it only awaits `mpsc::Receiver::recv`, and a message
+    /// is either delivered or dropped as a duplicate in the poll it was received in.
+    pub async fn recv(&mut self) -> Option<Msg> {
+        loop {
+            let msg = self.receiver.recv().await?;
+            let key = (self.key_fn)(&msg);
+            if self.seen.contains(&key) {
+                self.skipped += 1;
+                continue;
+            }
+            if self.order.len() == self.window
+                && let Some(oldest) = self.order.pop_front()
+            {
+                self.seen.remove(&oldest);
+            }
+            self.seen.insert(key.clone());
+            self.order.push_back(key);
+            return Some(msg);
+        }
+    }
+
+    /// How many duplicates were dropped so far.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Unwraps the underlying receiver, forgetting the keys seen.
+    pub fn into_inner(self) -> mpsc::Receiver<Msg> {
+        self.receiver
+    }
+}
+
+impl<Msg, K> Debug for DedupReceiver<Msg, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupReceiver")
+            .field("receiver", &self.receiver)
+            .field("window", &self.window)
+            .field("remembered", &self.order.len())
+            .field("skipped", &self.skipped)
+            .finish()
+    }
+}
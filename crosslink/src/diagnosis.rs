@@ -0,0 +1,86 @@
+use std::fmt;
+
+/// What the router knows about a receiver and the senders that could feed it, as
+/// returned by `Router::diagnose`, to triage a `recv` that never returns.
+///
+/// Its `Display` output sums the findings up in a sentence or two.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    /// The receiver marker's type name.
+    pub receiver_marker: &'static str,
+    /// Whether a receiver was registered for the marker at all.
+    pub registered: bool,
+    /// Whether the receiver was taken out of the router, presumably by the task waiting
+    /// on it. Always `false` when not registered.
+    pub taken: bool,
+    /// What the receiver's channel carries, e.g. `Envelope<Job>` on an instrumented
+    /// pathway, if registered.
+    pub message_type: Option<&'static str>,
+    /// Every sender that may feed the receiver, sorted by marker name.
+    pub senders: Vec<SenderDiagnosis>,
+}
+
+/// A sender that may feed a diagnosed receiver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderDiagnosis {
+    /// The sender marker's type name.
+    pub marker: &'static str,
+    /// Whether the sender was registered together with the receiver, as the other half
+    /// of its pathway. Otherwise, it only takes the type the receiver's channel
+    /// carries, and may just as well feed an unrelated channel.
+    pub paired: bool,
+    /// Whether the sender's channel has no receiver left, or `None` if it can't tell.
+    /// A closed sender next to a receiver that's still waiting means the two aren't
+    /// on the same channel, e.g. after `reset_pathway` swapped it.
+    pub closed: Option<bool>,
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(message_type) = self.message_type else {
+            return write!(
+                f,
+                "No receiver is registered for '{}'.",
+                self.receiver_marker
+            );
+        };
+
+        write!(
+            f,
+            "Receiver '{}' carries '{}' and is {}. ",
+            self.receiver_marker,
+            message_type,
+            if self.taken {
+                "taken"
+            } else {
+                "still in the router"
+            }
+        )?;
+
+        let live: Vec<&str> = self
+            .senders
+            .iter()
+            .filter(|sender| sender.closed != Some(true))
+            .map(|sender| sender.marker)
+            .collect();
+        match (self.senders.is_empty(), live.as_slice()) {
+            (true, _) => write!(f, "No sender in the router could feed it."),
+            (false, []) => write!(
+                f,
+                "Every sender that could feed it is closed: none is on the channel it reads."
+            ),
+            (false, live) => write!(f, "Senders that may feed it: {}.", live.join(", ")),
+        }
+    }
+}
+
+/// The messages buffered in a pathway, as copied out by `Router::snapshot_pending`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSnapshot<Msg> {
+    /// Every message that was buffered, oldest first.
+    pub messages: Vec<Msg>,
+    /// The messages that couldn't be pushed back, oldest first, typically because
+    /// producers filled the buffer in the meantime. They're no longer in the pathway:
+    /// resend them or treat them as lost.
+    pub unsent: Vec<Msg>,
+}
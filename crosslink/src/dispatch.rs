@@ -0,0 +1,477 @@
+#[cfg(feature = "tokio")]
+use std::time::Duration;
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+};
+
+use crate::{
+    backend::ChannelBackend,
+    error::CommsError,
+    receiver::{ConcreteReceiver, ReceiverSlot},
+    sender::{ConcreteSenderTrait, SenderSlot, Undelivered},
+    telemetry,
+};
+#[cfg(feature = "tokio")]
+use crate::{
+    dead_letter::{self, DeadLetters},
+    retry::RetryPolicy,
+    tap::Tap,
+};
+
+// The send and take paths shared by `Router` and `FrozenRouter`,
+// which only differ in how they look slots up.
+
+/// The pathway a send goes through, as reported in errors, taps and telemetry.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pathway {
+    pub marker: TypeId,
+    pub name: &'static str,
+}
+
+impl Pathway {
+    pub fn of<SenderMarker: 'static>() -> Self {
+        Self {
+            marker: TypeId::of::<SenderMarker>(),
+            name: std::any::type_name::<SenderMarker>(),
+        }
+    }
+}
+
+/// Router-wide hooks run around every send.
+#[derive(Debug, Default)]
+pub(crate) struct SendHooks {
+    #[cfg(feature = "tokio")]
+    pub tap: Option<Tap>,
+    #[cfg(feature = "tokio")]
+    pub dead_letters: DeadLetters,
+}
+
+/// Checks the sender found for `SenderMarker`, if any, accepts `Msg`.
+pub(crate) fn checked_sender<SenderMarker, Msg>(
+    slot: Option<&SenderSlot>,
+) -> Result<&SenderSlot, CommsError>
+where
+    SenderMarker: Any + Send + Sync + 'static,
+    Msg: ConcreteSenderTrait,
+{
+    let msg_type_id_to_send = TypeId::of::<Msg>();
+
+    match slot {
+        Some(slot) => {
+            let dyn_sender = &slot.sender;
+            if dyn_sender.accepts_message_type_id() != msg_type_id_to_send {
+                return Err(CommsError::InternalInconsistency(format!(
+                    "Metadata mismatch for link '{}', pathway '{}'.
+                    Expected type '{}' for sending, but sender is configured for '{}'.",
+                    std::any::type_name::<SenderMarker>(),
+                    dyn_sender.message_type_name(),
+                    std::any::type_name::<Msg>(),
+                    dyn_sender.message_type_name()
+                )));
+            }
+            Ok(slot)
+        }
+        None => Err(CommsError::PathwayNotFound(
+            format!(
+                "No pathway configured for marker type '{}' that accepts message type '{}'.
+                Ensure this message type is defined for sending on this link",
+                std::any::type_name::<SenderMarker>(),
+                std::any::type_name::<Msg>()
+            ),
+            Some(TypeId::of::<SenderMarker>()),
+        )),
+    }
+}
+
+pub(crate) async fn send<SenderMarker, Msg>(
+    slot: Result<&SenderSlot, CommsError>,
+    hooks: &SendHooks,
+    message: Msg,
+) -> Result<(), CommsError>
+where
+    SenderMarker: Any + Send + Sync + 'static,
+    Msg: ConcreteSenderTrait,
+{
+    send_on(slot, hooks, Pathway::of::<SenderMarker>(), message).await
+}
+
+/// Same as [`send`], for a pathway only known at runtime.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    name = "crosslink.send",
+    level = "trace",
+    skip_all,
+    fields(pathway = pathway.name),
+))]
+pub(crate) async fn send_on<Msg>(
+    slot: Result<&SenderSlot, CommsError>,
+    hooks: &SendHooks,
+    pathway: Pathway,
+    message: Msg,
+) -> Result<(), CommsError>
+where
+    Msg: ConcreteSenderTrait,
+{
+    #[cfg(feature = "tokio")]
+    let tapped = hooks.tap.as_ref().map(|tap| (tap, tap.render(&message)));
+    #[cfg(not(feature = "tokio"))]
+    let _ = hooks;
+
+    let result = match slot {
+        Ok(slot) => {
+            slot.gate.opened().await;
+            let result = send_erased(slot, hooks, message).await;
+            slot.counters.record(&result);
+            result
+        }
+        Err(e) => Err(e),
+    }
+    .map_err(|e| e.with_marker(pathway.marker));
+
+    #[cfg(feature = "tokio")]
+    if let (Ok(()), Some((tap, copy))) = (&result, tapped) {
+        tap.forward(pathway.name, copy);
+    }
+
+    telemetry::sent(pathway.name, &result);
+    result
+}
+
+/// Same as [`send_on`], for a message whose type is only known at runtime.
+///
+/// The sender's own downcast catches a message of the wrong type. Dead letters
+/// are looked up by message type, so they don't apply here.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    name = "crosslink.send",
+    level = "trace",
+    skip_all,
+    fields(pathway = pathway.name),
+))]
+pub(crate) async fn send_boxed(
+    slot: Result<&SenderSlot, CommsError>,
+    hooks: &SendHooks,
+    pathway: Pathway,
+    message: Box<dyn Any + Send>,
+) -> Result<(), CommsError> {
+    #[cfg(feature = "tokio")]
+    let tapped = match (&hooks.tap, &slot) {
+        (Some(tap), Ok(slot)) => tap
+            .render_erased(slot.render, &*message)
+            .map(|copy| (tap, copy)),
+        _ => None,
+    };
+    #[cfg(not(feature = "tokio"))]
+    let _ = hooks;
+
+    let result = match slot {
+        Ok(slot) => {
+            slot.gate.opened().await;
+            let result = slot.sender.send_erased(message).await;
+            slot.counters.record(&result);
+            result
+        }
+        Err(e) => Err(e),
+    }
+    .map_err(|e| e.with_marker(pathway.marker));
+
+    #[cfg(feature = "tokio")]
+    if let (Ok(()), Some((tap, copy))) = (&result, tapped) {
+        tap.forward(pathway.name, copy);
+    }
+
+    telemetry::sent(pathway.name, &result);
+    result
+}
+
+pub(crate) fn try_send<SenderMarker, Msg>(
+    slot: Result<&SenderSlot, CommsError>,
+    hooks: &SendHooks,
+    message: Msg,
+) -> Result<(), CommsError>
+where
+    SenderMarker: Any + Send + Sync + 'static,
+    Msg: ConcreteSenderTrait,
+{
+    #[cfg(feature = "tokio")]
+    let tapped = hooks.tap.as_ref().map(|tap| (tap, tap.render(&message)));
+    #[cfg(not(feature = "tokio"))]
+    let _ = hooks;
+
+    let result = slot
+        .and_then(|slot| {
+            let result = if slot.gate.is_paused() {
+                Err(CommsError::ChannelFull(format!(
+                    "Pathway '{}' is paused",
+                    std::any::type_name::<SenderMarker>()
+                )))
+            } else {
+                try_send_erased(slot, hooks, message)
+            };
+            slot.counters.record(&result);
+            result
+        })
+        .map_err(|e| e.with_marker(TypeId::of::<SenderMarker>()));
+
+    #[cfg(feature = "tokio")]
+    if let (Ok(()), Some((tap, copy))) = (&result, tapped) {
+        tap.forward(std::any::type_name::<SenderMarker>(), copy);
+    }
+
+    telemetry::sent(std::any::type_name::<SenderMarker>(), &result);
+    result
+}
+
+/// Same as [`try_send`] for each message in turn, handing back the ones the pathway had
+/// no room for, in their original order.
+///
+/// Stops at the first other failure: the messages after it are dropped, the failed
+/// one goes to its dead-letter channel if there is one, and the error is returned.
+pub(crate) fn try_send_all<SenderMarker, Msg>(
+    slot: Result<&SenderSlot, CommsError>,
+    hooks: &SendHooks,
+    messages: Vec<Msg>,
+) -> Result<Vec<Msg>, CommsError>
+where
+    SenderMarker: Any + Send + Sync + 'static,
+    Msg: ConcreteSenderTrait,
+{
+    #[cfg(not(feature = "tokio"))]
+    let _ = hooks;
+
+    let pathway = Pathway::of::<SenderMarker>();
+    let slot = slot.map_err(|e| e.with_marker(pathway.marker))?;
+    let mut rejected = Vec::new();
+
+    for message in messages {
+        #[cfg(feature = "tokio")]
+        let tapped = hooks.tap.as_ref().map(|tap| (tap, tap.render(&message)));
+
+        let outcome = if slot.gate.is_paused() {
+            Err(Undelivered::full(message))
+        } else {
+            slot.sender.try_send_returning(Box::new(message))
+        };
+        let result = outcome.as_ref().map(|_| ()).map_err(|u| u.error.clone());
+        slot.counters.record(&result);
+        telemetry::sent(pathway.name, &result);
+
+        match outcome {
+            Ok(()) =>
+            {
+                #[cfg(feature = "tokio")]
+                if let Some((tap, copy)) = tapped {
+                    tap.forward(pathway.name, copy);
+                }
+            }
+            // A full pathway that couldn't hand the message back, e.g. an encoding
+            // one, lost it, so it's reported like any other failure.
+            Err(undelivered) if undelivered.error.is_full() => {
+                match undelivered.message.map(|m| m.downcast::<Msg>()) {
+                    Some(Ok(returned)) => rejected.push(*returned),
+                    _ => return Err(undelivered.error.with_marker(pathway.marker)),
+                }
+            }
+            Err(undelivered) => {
+                #[cfg(feature = "tokio")]
+                let error = match hooks.dead_letters.get::<Msg>() {
+                    Some(dead_letter) => dead_letter::forward(undelivered, dead_letter),
+                    None => undelivered.error,
+                };
+                #[cfg(not(feature = "tokio"))]
+                let error = undelivered.error;
+                return Err(error.with_marker(pathway.marker));
+            }
+        }
+    }
+
+    Ok(rejected)
+}
+
+/// Same as [`try_send`], retried with backoff for as long as the pathway is full.
+#[cfg(feature = "tokio")]
+pub(crate) async fn send_with_retry<SenderMarker, Msg>(
+    slot: Result<&SenderSlot, CommsError>,
+    hooks: &SendHooks,
+    message: Msg,
+    policy: RetryPolicy,
+) -> Result<(), CommsError>
+where
+    SenderMarker: Any + Send + Sync + 'static,
+    Msg: ConcreteSenderTrait,
+{
+    let pathway = Pathway::of::<SenderMarker>();
+    let tapped = hooks.tap.as_ref().map(|tap| (tap, tap.render(&message)));
+
+    let result = match slot {
+        Ok(slot) => {
+            let result = retry_erased(slot, hooks, pathway, message, policy).await;
+            slot.counters.record(&result);
+            result
+        }
+        Err(e) => Err(e),
+    }
+    .map_err(|e| e.with_marker(pathway.marker));
+
+    if let (Ok(()), Some((tap, copy))) = (&result, tapped) {
+        tap.forward(pathway.name, copy);
+    }
+
+    telemetry::sent(pathway.name, &result);
+    result
+}
+
+#[cfg(feature = "tokio")]
+async fn retry_erased<Msg: ConcreteSenderTrait>(
+    slot: &SenderSlot,
+    hooks: &SendHooks,
+    pathway: Pathway,
+    mut message: Msg,
+    policy: RetryPolicy,
+) -> Result<(), CommsError> {
+    let mut delay = policy.initial_delay;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let reason = if slot.gate.is_paused() {
+            format!("Pathway '{}' is paused", pathway.name)
+        } else {
+            match slot.sender.try_send_returning(Box::new(message)) {
+                Ok(()) => return Ok(()),
+                Err(undelivered) if undelivered.error.is_full() => {
+                    // Only a message handed back can be retried.
+                    match undelivered.message.map(|m| m.downcast::<Msg>()) {
+                        Some(Ok(returned)) => message = *returned,
+                        _ => return Err(undelivered.error),
+                    }
+                    match undelivered.error {
+                        CommsError::ChannelFull(reason) => reason,
+                        other => other.to_string(),
+                    }
+                }
+                Err(undelivered) => {
+                    return Err(match hooks.dead_letters.get::<Msg>() {
+                        Some(dead_letter) => dead_letter::forward(undelivered, dead_letter),
+                        None => undelivered.error,
+                    });
+                }
+            }
+        };
+
+        if attempts >= policy.max_attempts {
+            return Err(CommsError::ChannelFull(format!(
+                "{}, gave up after {} attempts",
+                reason, attempts
+            )));
+        }
+        tokio::time::sleep(delay).await;
+        delay =
+            Duration::try_from_secs_f64(delay.as_secs_f64() * policy.multiplier).unwrap_or(delay);
+    }
+}
+
+/// Sends through the slot, handing failed messages to their dead-letter channel, if any.
+async fn send_erased<Msg: ConcreteSenderTrait>(
+    slot: &SenderSlot,
+    hooks: &SendHooks,
+    message: Msg,
+) -> Result<(), CommsError> {
+    #[cfg(feature = "tokio")]
+    if let Some(dead_letter) = hooks.dead_letters.get::<Msg>() {
+        return slot
+            .sender
+            .send_returning(Box::new(message))
+            .await
+            .map_err(|undelivered| dead_letter::forward(undelivered, dead_letter));
+    }
+    #[cfg(not(feature = "tokio"))]
+    let _ = hooks;
+
+    slot.sender.send_erased(Box::new(message)).await
+}
+
+fn try_send_erased<Msg: ConcreteSenderTrait>(
+    slot: &SenderSlot,
+    hooks: &SendHooks,
+    message: Msg,
+) -> Result<(), CommsError> {
+    #[cfg(feature = "tokio")]
+    if let Some(dead_letter) = hooks.dead_letters.get::<Msg>() {
+        return slot
+            .sender
+            .try_send_returning(Box::new(message))
+            .map_err(|undelivered| dead_letter::forward(undelivered, dead_letter));
+    }
+    #[cfg(not(feature = "tokio"))]
+    let _ = hooks;
+
+    slot.sender.try_send_erased(Box::new(message))
+}
+
+/// Takes the receiver out of the slot found for `ReceiverMarker`, if any.
+///
+/// With debug assertions on, the caller is recorded as the receiver's taker,
+/// and named in the error of any later take.
+#[track_caller]
+pub(crate) fn take_receiver<B, ReceiverMarker, Msg>(
+    slot: Option<&ReceiverSlot>,
+) -> Result<B::Receiver<Msg>, CommsError>
+where
+    B: ChannelBackend,
+    ReceiverMarker: Any + Send + Sync + 'static,
+    Msg: Send + 'static + Debug + Sync,
+{
+    let expected_msg_type_id = TypeId::of::<Msg>();
+
+    match slot {
+        Some(slot) => {
+            if slot.msg_type_id != expected_msg_type_id {
+                return Err(CommsError::TypeMismatch(
+                    format!(
+                        "Expected type '{}' for receiving.",
+                        std::any::type_name::<Msg>(),
+                    ),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                ));
+            }
+
+            let mut recv_guard = slot.lock();
+
+            if let Some(dyn_receiver) = recv_guard.take() {
+                match dyn_receiver.into_any().downcast::<ConcreteReceiver<B, Msg>>() {
+                    Ok(concrete_box_recv) => {
+                        slot.mark_taken();
+                        Ok(concrete_box_recv.receiver)
+                    }
+                    Err(_) => Err(CommsError::InternalInconsistency(format!(
+                        "Critical: Downcast to ConcreteReceiver<{}> failed for key '{}' after TypeId match.
+                        Ensure the pathway was registered with backend '{}'.",
+                        std::any::type_name::<ReceiverMarker>(),
+                        std::any::type_name::<Msg>(),
+                        std::any::type_name::<B>()
+                    ))),
+                }
+            } else if let Some(taken_at) = slot.taken_at() {
+                Err(CommsError::InternalInconsistency(format!(
+                    "Failed to take receiver for link '{}' and handle '{}': already taken at {}.",
+                    std::any::type_name::<ReceiverMarker>(),
+                    std::any::type_name::<Msg>(),
+                    taken_at
+                )))
+            } else {
+                Err(CommsError::InternalInconsistency(format!(
+                    "Failed to take receiver for link '{}' and handle '{}'.",
+                    std::any::type_name::<ReceiverMarker>(),
+                    std::any::type_name::<Msg>()
+                )))
+            }
+        }
+        None => Err(CommsError::PathwayNotFound(
+            format!(
+                "No receiver for link '{}' and handle '{}' found.",
+                std::any::type_name::<ReceiverMarker>(),
+                std::any::type_name::<Msg>()
+            ),
+            Some(TypeId::of::<ReceiverMarker>()),
+        )),
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt::{self, Debug},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::{
+    error::CommsError,
+    stats::{PathwayCounters, PathwayStats},
+};
+
+/// One pathway of a [`DynamicRouter`], as described in configuration.
+///
+/// Pathways are one-way: a request/reply pair is two specs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkSpec {
+    /// Name the pathway is sent on and received from.
+    pub name: String,
+    /// Type tag every message on the pathway must carry.
+    pub message_type: String,
+    pub buffer_size: usize,
+}
+
+/// A type-erased message, tagged with the name of its type.
+///
+/// The tag is what a [`DynamicRouter`] checks on send: the payload itself is only
+/// inspected by whoever downcasts it on the receiving side.
+pub struct DynamicMessage {
+    type_tag: String,
+    payload: Box<dyn Any + Send>,
+}
+
+impl DynamicMessage {
+    pub fn new<T: Any + Send>(type_tag: impl Into<String>, payload: T) -> Self {
+        Self::from_boxed(type_tag, Box::new(payload))
+    }
+
+    /// Wraps an already boxed payload, e.g. the output of a deserializer.
+    pub fn from_boxed(type_tag: impl Into<String>, payload: Box<dyn Any + Send>) -> Self {
+        Self {
+            type_tag: type_tag.into(),
+            payload,
+        }
+    }
+
+    pub fn type_tag(&self) -> &str {
+        &self.type_tag
+    }
+
+    pub fn payload(&self) -> &(dyn Any + Send) {
+        &*self.payload
+    }
+
+    pub fn into_payload(self) -> Box<dyn Any + Send> {
+        self.payload
+    }
+
+    /// Takes the payload out as a `T`, or hands the message back if it's another type.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        match self.payload.downcast::<T>() {
+            Ok(payload) => Ok(*payload),
+            Err(payload) => Err(Self {
+                type_tag: self.type_tag,
+                payload,
+            }),
+        }
+    }
+}
+
+impl Debug for DynamicMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicMessage")
+            .field("type_tag", &self.type_tag)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+struct DynamicPathway {
+    message_type: String,
+    sender: mpsc::Sender<DynamicMessage>,
+    receiver: Mutex<Option<mpsc::Receiver<DynamicMessage>>>,
+    counters: Arc<PathwayCounters>,
+}
+
+/// A router whose pathways are named by strings and registered at runtime, e.g. from
+/// a list of [`LinkSpec`]s loaded from configuration.
+///
+/// This is the untyped counterpart of [`Router`](crate::Router), for infrastructure
+/// whose topology isn't known at compile time. Nothing is checked by the compiler:
+/// every message is a [`DynamicMessage`], and a send only checks that its type tag
+/// matches the pathway's `message_type`. Both routers share their error type and
+/// send counters, but nothing else: pathways of one can't be reached from the other.
+#[derive(Debug, Default)]
+pub struct DynamicRouter {
+    pathways: HashMap<String, DynamicPathway>,
+}
+
+// Shared across tasks behind an `Arc`, same as `Router`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<DynamicRouter>();
+};
+
+impl DynamicRouter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Builds a router with one pathway per spec, failing on the first duplicate name.
+    pub fn from_specs(specs: impl IntoIterator<Item = LinkSpec>) -> Result<Self, CommsError> {
+        let mut router = Self::new();
+        for spec in specs {
+            router.register(spec)?;
+        }
+        Ok(router)
+    }
+
+    /// Registers a pathway described by `spec`.
+    ///
+    /// # Panics
+    /// Panics if `spec.buffer_size` is 0, same as `tokio::sync::mpsc::channel`.
+    pub fn register(&mut self, spec: LinkSpec) -> Result<(), CommsError> {
+        if self.pathways.contains_key(&spec.name) {
+            return Err(CommsError::PathwayAlreadyRegistered(format!(
+                "Dynamic pathway '{}' already registered.",
+                spec.name
+            )));
+        }
+
+        let (sender, receiver) = mpsc::channel(spec.buffer_size);
+        self.pathways.insert(
+            spec.name,
+            DynamicPathway {
+                message_type: spec.message_type,
+                sender,
+                receiver: Mutex::new(Some(receiver)),
+                counters: Arc::default(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Sends a message on the pathway named `pathway`, waiting for capacity.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send(&self, pathway: &str, message: DynamicMessage) -> Result<(), CommsError> {
+        let entry = self.checked_pathway(pathway, &message)?;
+        let result = entry
+            .sender
+            .send(message)
+            .await
+            .map_err(|_| closed_error(pathway));
+        entry.counters.record(&result);
+        result
+    }
+
+    /// Sends a message only if the pathway has room for it right now.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub fn try_send(&self, pathway: &str, message: DynamicMessage) -> Result<(), CommsError> {
+        let entry = self.checked_pathway(pathway, &message)?;
+        let result = entry.sender.try_send(message).map_err(|e| match e {
+            TrySendError::Full(_) => CommsError::ChannelFull(format!(
+                "No capacity left on dynamic pathway '{}'.",
+                pathway
+            )),
+            TrySendError::Closed(_) => closed_error(pathway),
+        });
+        entry.counters.record(&result);
+        result
+    }
+
+    /// Takes the receiving half of the pathway named `pathway`.
+    pub fn take_receiver(
+        &self,
+        pathway: &str,
+    ) -> Result<mpsc::Receiver<DynamicMessage>, CommsError> {
+        self.pathway(pathway)?
+            .receiver
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .ok_or_else(|| {
+                CommsError::RecvFailed(format!(
+                    "Receiver for dynamic pathway '{}' was already taken.",
+                    pathway
+                ))
+            })
+    }
+
+    /// Returns the send counters of the pathway named `pathway`, if it exists.
+    pub fn stats(&self, pathway: &str) -> Option<PathwayStats> {
+        self.pathways
+            .get(pathway)
+            .map(|entry| entry.counters.snapshot())
+    }
+
+    /// Names of all registered pathways, in no particular order.
+    pub fn pathways(&self) -> impl Iterator<Item = &str> {
+        self.pathways.keys().map(String::as_str)
+    }
+
+    fn pathway(&self, pathway: &str) -> Result<&DynamicPathway, CommsError> {
+        self.pathways.get(pathway).ok_or_else(|| {
+            CommsError::PathwayNotFound(format!("No dynamic pathway named '{}'.", pathway), None)
+        })
+    }
+
+    fn checked_pathway(
+        &self,
+        pathway: &str,
+        message: &DynamicMessage,
+    ) -> Result<&DynamicPathway, CommsError> {
+        let entry = self.pathway(pathway)?;
+        if entry.message_type != message.type_tag {
+            let error = CommsError::TypeMismatch(
+                format!(
+                    "Dynamic pathway '{}' carries '{}', got a message tagged '{}'.",
+                    pathway, entry.message_type, message.type_tag
+                ),
+                None,
+            );
+            entry.counters.record(&Err(error.clone()));
+            return Err(error);
+        }
+        Ok(entry)
+    }
+}
+
+fn closed_error(pathway: &str) -> CommsError {
+    CommsError::SendFailed(format!("Dynamic pathway '{}' is closed.", pathway), None)
+}
@@ -0,0 +1,172 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    pin::Pin,
+    time::{Instant, SystemTime},
+};
+
+#[cfg(feature = "tokio")]
+use tokio::sync::mpsc;
+
+use crate::{
+    backend::ChannelBackend,
+    backend::TrySendError,
+    error::CommsError,
+    sender::{
+        ConcreteSenderTrait, DynSender, Undelivered, closed_error, downcast_error, try_send_error,
+    },
+};
+
+/// Wrapper carrying per-message metadata alongside the payload.
+/// Pathways that need to stamp messages (latency instrumentation, timestamped links)
+/// carry `Envelope<T>` on the channel instead of a bare `T`.
+///
+/// Every envelope is stamped twice when sent: with an `Instant`, to measure how long
+/// the message took, and with a `SystemTime`, to tell when it was produced. Only the
+/// `Instant` is monotonic, so latencies can't come out negative when the system clock
+/// is adjusted, but it means nothing outside this process. The `SystemTime` can be
+/// stored, compared with other processes' clocks or shown to a user, at the cost of
+/// following the clock's jumps.
+#[derive(Debug, Clone)]
+pub struct Envelope<T> {
+    msg: T,
+    sent_at: Instant,
+    timestamp: SystemTime,
+}
+
+impl<T> Envelope<T> {
+    pub(crate) fn new(msg: T) -> Self {
+        Self {
+            msg,
+            sent_at: Instant::now(),
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    /// When the message was handed to `Router::send`.
+    pub fn sent_at(&self) -> Instant {
+        self.sent_at
+    }
+
+    /// When the message was handed to `Router::send`, by the system clock.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Unwraps the payload.
+    pub fn into_inner(self) -> T {
+        self.msg
+    }
+}
+
+/// Receiving half of a timestamped pathway, as returned by
+/// `Router::take_timestamped_receiver`: hands out every message along with the
+/// wall-clock time it was sent.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TimestampedReceiver<T> {
+    receiver: mpsc::Receiver<Envelope<T>>,
+}
+
+#[cfg(feature = "tokio")]
+impl<T> TimestampedReceiver<T> {
+    pub(crate) fn new(receiver: mpsc::Receiver<Envelope<T>>) -> Self {
+        Self { receiver }
+    }
+
+    /// Receives the next message and its timestamp, or `None` once the channel is
+    /// closed and drained.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe, like `mpsc::Receiver::recv`, which is all it awaits.
+    pub async fn recv(&mut self) -> Option<(SystemTime, T)> {
+        let envelope = self.receiver.recv().await?;
+        Some((envelope.timestamp(), envelope.into_inner()))
+    }
+
+    /// Unwraps the underlying receiver of envelopes.
+    pub fn into_inner(self) -> mpsc::Receiver<Envelope<T>> {
+        self.receiver
+    }
+}
+
+/// A sender that accepts bare `T`s and wraps them in an [`Envelope`] on the way in,
+/// so `Router::send` stays the same regardless of what the channel carries.
+#[derive(Debug)]
+pub(crate) struct EnvelopeSender<B: ChannelBackend, T: ConcreteSenderTrait> {
+    pub sender: B::Sender<Envelope<T>>,
+}
+
+impl<B: ChannelBackend, T: ConcreteSenderTrait> DynSender for EnvelopeSender<B, T> {
+    fn send_erased(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        match msg_any.downcast::<T>() {
+            Ok(concrete_msg) => {
+                let sender_clone = self.sender.clone();
+                Box::pin(async move {
+                    B::send(&sender_clone, Envelope::new(*concrete_msg))
+                        .await
+                        .map_err(|_| closed_error::<T>())
+                })
+            }
+            Err(_) => Box::pin(async { Err(downcast_error::<T>()) }),
+        }
+    }
+
+    fn try_send_erased(&self, msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        let concrete_msg = msg_any.downcast::<T>().map_err(|_| downcast_error::<T>())?;
+        B::try_send(&self.sender, Envelope::new(*concrete_msg)).map_err(try_send_error::<T, _>)
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn buffered(&self) -> Option<usize> {
+        B::buffered(&self.sender)
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        B::is_closed(&self.sender)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(EnvelopeSender::<B, T> {
+            sender: self.sender.clone(),
+        })
+    }
+
+    fn send_returning(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Undelivered>> + Send>> {
+        match msg_any.downcast::<T>() {
+            Ok(concrete_msg) => {
+                let sender_clone = self.sender.clone();
+                Box::pin(async move {
+                    B::send(&sender_clone, Envelope::new(*concrete_msg))
+                        .await
+                        .map_err(|envelope| Undelivered::closed(envelope.into_inner()))
+                })
+            }
+            Err(_) => Box::pin(async { Err(Undelivered::lost(downcast_error::<T>())) }),
+        }
+    }
+
+    fn try_send_returning(&self, msg_any: Box<dyn Any + Send>) -> Result<(), Undelivered> {
+        let concrete_msg = msg_any
+            .downcast::<T>()
+            .map_err(|_| Undelivered::lost(downcast_error::<T>()))?;
+        B::try_send(&self.sender, Envelope::new(*concrete_msg)).map_err(|e| match e {
+            TrySendError::Closed(envelope) => Undelivered::closed(envelope.into_inner()),
+            TrySendError::Full(envelope) => Undelivered::full(envelope.into_inner()),
+        })
+    }
+}
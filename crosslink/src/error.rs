@@ -1,19 +1,31 @@
+use std::any::TypeId;
+
+/// Errors returned by the [`Router`](crate::Router).
+///
+/// Variants tied to a specific pathway carry the `TypeId` of the marker involved,
+/// when known, so errors can be routed programmatically with [`CommsError::marker`].
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum CommsError {
     #[error("Send failed: {0}")]
-    SendFailed(String),
+    SendFailed(String, Option<TypeId>),
+
+    #[error("Channel full: {0}")]
+    ChannelFull(String),
 
     #[error("Receive failed: {0}")]
     RecvFailed(String),
 
     #[error("Type mismatch: {0}")]
-    TypeMismatch(String),
+    TypeMismatch(String, Option<TypeId>),
 
     #[error("Pathway already registered: {0}")]
     PathwayAlreadyRegistered(String),
 
     #[error("Pathway not found: {0}")]
-    PathwayNotFound(String),
+    PathwayNotFound(String, Option<TypeId>),
+
+    #[error("Ambiguous pathway: {0}")]
+    AmbiguousPathway(String),
 
     #[error("Link not found: {0}")]
     LinkNotFound(String),
@@ -21,6 +33,113 @@ pub enum CommsError {
     #[error("Message type not mapped for link: {0}")]
     MessageTypeNotMappedForLink(String),
 
+    #[error("Message not acknowledged: {0}")]
+    NotAcked(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
     #[error("Internal inconsistency: {0}")]
     InternalInconsistency(String),
 }
+
+impl CommsError {
+    /// The `TypeId` of the marker of the pathway that failed, if known.
+    ///
+    /// ```
+    /// # use std::any::TypeId;
+    /// # use crosslink::{Router, define_crosslink};
+    /// # define_crosslink! {
+    /// #     link_id: "FooLink",
+    /// #     Foo { sends: u32, receives: () },
+    /// #     Bar { sends: (), receives: u32 },
+    /// #     buffer_size: 1,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use foo_link::{BarRecv, FooSend};
+    /// # static RESTARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+    /// # fn restart_foo() { RESTARTED.store(true, std::sync::atomic::Ordering::SeqCst); }
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// # let mut router = Router::new();
+    /// # foo_link::setup_foo_link(&mut router, None);
+    /// # drop(router.take_receiver::<BarRecv, u32>().unwrap());
+    /// # let msg = 1u32;
+    /// if let Err(e) = router.send::<FooSend, _>(msg).await {
+    ///     if e.marker() == Some(TypeId::of::<FooSend>()) {
+    ///         restart_foo();
+    ///     }
+    /// }
+    /// # assert!(RESTARTED.load(std::sync::atomic::Ordering::SeqCst));
+    /// # }
+    /// ```
+    pub fn marker(&self) -> Option<TypeId> {
+        match self {
+            CommsError::SendFailed(_, marker)
+            | CommsError::TypeMismatch(_, marker)
+            | CommsError::PathwayNotFound(_, marker) => *marker,
+            _ => None,
+        }
+    }
+
+    /// Whether the other side of the pathway is gone, so sending again can't succeed.
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # define_crosslink! {
+    /// #     link_id: "FooLink",
+    /// #     Foo { sends: u32, receives: () },
+    /// #     Bar { sends: (), receives: u32 },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use foo_link::{BarRecv, FooSend};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # foo_link::setup_foo_link(&mut router, None);
+    /// # drop(router.take_receiver::<BarRecv, u32>()?);
+    /// # let (tx, mut source) = tokio::sync::mpsc::channel(4);
+    /// # tx.send(1u32).await.unwrap();
+    /// while let Some(msg) = source.recv().await {
+    ///     match router.send::<FooSend, _>(msg).await {
+    ///         Err(e) if e.is_disconnected() => break,
+    ///         other => other?,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, CommsError::SendFailed(..))
+    }
+
+    /// Whether the pathway had no capacity left, which may clear up on a later attempt.
+    pub fn is_full(&self) -> bool {
+        matches!(self, CommsError::ChannelFull(_))
+    }
+
+    /// Whether the operation gave up after its deadline.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, CommsError::Timeout(_))
+    }
+
+    /// Whether the pathway or link asked for isn't registered on the router.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            CommsError::PathwayNotFound(..) | CommsError::LinkNotFound(_)
+        )
+    }
+
+    /// Fills in the marker, unless the error already names one.
+    pub(crate) fn with_marker(mut self, marker_id: TypeId) -> Self {
+        if let CommsError::SendFailed(_, marker)
+        | CommsError::TypeMismatch(_, marker)
+        | CommsError::PathwayNotFound(_, marker) = &mut self
+        {
+            marker.get_or_insert(marker_id);
+        }
+        self
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    task::{Context, Poll},
+};
+
+use tokio::sync::mpsc;
+
+use crate::{error::CommsError, receiver::ConcreteReceiverTrait, router::Router};
+
+/// Builds a [`WeightedReceiver`] out of the receivers of several pathways, as started
+/// by `Router::fan_in`.
+///
+/// Each [`source`](Self::source) takes its pathway's receiver right away. If the
+/// builder is dropped before [`build`](Self::build), e.g. because a later source failed
+/// and `?` bailed out, every receiver taken so far is handed back to the router, so a
+/// failed fan-in leaves the pathways as they were.
+#[must_use = "the sources' receivers go back to the router unless `build` is called"]
+pub struct FanInBuilder<'a, Msg: ConcreteReceiverTrait> {
+    router: &'a Router,
+    consumer: &'static str,
+    sources: Vec<Source<Msg>>,
+}
+
+struct Source<Msg> {
+    name: &'static str,
+    receiver: mpsc::Receiver<Msg>,
+    weight: u32,
+    return_to: fn(&Router, mpsc::Receiver<Msg>) -> Result<(), CommsError>,
+}
+
+impl<'a, Msg: ConcreteReceiverTrait + Sync> FanInBuilder<'a, Msg> {
+    pub(crate) fn new(router: &'a Router, consumer: &'static str) -> Self {
+        Self {
+            router,
+            consumer,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Adds the pathway of `ReceiverMarker` as a source, which gets up to `weight`
+    /// messages in a row whenever its turn comes.
+    ///
+    /// Fails like `Router::take_receiver` does, e.g. if the receiver was already taken,
+    /// which includes adding the same source twice.
+    ///
+    /// # Panics
+    /// Panics if `weight` is 0: such a source would never be read.
+    #[track_caller]
+    pub fn source<ReceiverMarker>(mut self, weight: u32) -> Result<Self, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+    {
+        assert!(
+            weight > 0,
+            "fan-in source weights must be greater than zero"
+        );
+
+        let receiver = self.router.take_receiver::<ReceiverMarker, Msg>()?;
+        self.sources.push(Source {
+            name: std::any::type_name::<ReceiverMarker>(),
+            receiver,
+            weight,
+            return_to: |router, receiver| router.return_receiver::<ReceiverMarker, Msg>(receiver),
+        });
+        Ok(self)
+    }
+
+    /// Merges the sources added so far into one receiver.
+    pub fn build(mut self) -> WeightedReceiver<Msg> {
+        let sources = std::mem::take(&mut self.sources);
+        let credit = sources.first().map_or(0, |source| source.weight);
+        WeightedReceiver {
+            consumer: self.consumer,
+            sources: sources
+                .into_iter()
+                .map(|source| WeightedSource {
+                    name: source.name,
+                    receiver: Some(source.receiver),
+                    weight: source.weight,
+                })
+                .collect(),
+            cursor: 0,
+            credit,
+        }
+    }
+}
+
+impl<Msg: ConcreteReceiverTrait> Drop for FanInBuilder<'_, Msg> {
+    fn drop(&mut self) {
+        for source in self.sources.drain(..) {
+            // Only fails if the slot is gone or refilled, and then there's nowhere to put it.
+            let _ = (source.return_to)(self.router, source.receiver);
+        }
+    }
+}
+
+impl<Msg: ConcreteReceiverTrait> Debug for FanInBuilder<'_, Msg> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FanInBuilder")
+            .field("consumer", &self.consumer)
+            .field(
+                "sources",
+                &self
+                    .sources
+                    .iter()
+                    .map(|source| (source.name, source.weight))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct WeightedSource<Msg> {
+    name: &'static str,
+    /// `None` once the source's pathway is closed and drained.
+    receiver: Option<mpsc::Receiver<Msg>>,
+    weight: u32,
+}
+
+/// Receives from several pathways at once, sharing the consumer's attention between
+/// them by weight, as built with `Router::fan_in`.
+///
+/// Sources are served in weighted round-robin, in the order they were added: each one
+/// in turn gets up to its weight in messages before the next one is polled. A source
+/// with nothing buffered forfeits the rest of its turn instead of holding up the
+/// others, so nothing is left waiting while any source has messages.
+///
+/// # Fairness
+/// While every source has messages waiting, each full round hands out exactly as many
+/// messages per source as its weight: weights 3 and 1 interleave as `A A A B A A A B`.
+/// Between two of its turns, a source with messages waits for at most the sum of the
+/// other sources' weights in messages, so no source can be starved, however small its
+/// weight. When some sources are idle, the busy ones split the consumer's attention
+/// among themselves, still by weight.
+pub struct WeightedReceiver<Msg> {
+    consumer: &'static str,
+    sources: Vec<WeightedSource<Msg>>,
+    /// The source whose turn it is.
+    cursor: usize,
+    /// Messages left in the current source's turn.
+    credit: u32,
+}
+
+impl<Msg> WeightedReceiver<Msg> {
+    /// Receives the next message, picked by weight among the sources with messages.
+    /// Returns `None` once every source is closed and drained.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: the underlying channels' `poll_recv` only takes a
+    /// message in the poll that returns it, and a dropped call at most moves the turn
+    /// on to another source, never losing a message.
+    pub async fn recv(&mut self) -> Option<Msg> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Polls for the next message, same as [`recv`](Self::recv).
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Msg>> {
+        let count = self.sources.len();
+        // Every source gets polled at most once, so all of them are registered with
+        // `cx` before this returns `Pending`.
+        for _ in 0..count {
+            if self.credit == 0 {
+                self.next_turn();
+            }
+
+            let source = &mut self.sources[self.cursor];
+            let Some(receiver) = source.receiver.as_mut() else {
+                self.credit = 0;
+                continue;
+            };
+            match receiver.poll_recv(cx) {
+                Poll::Ready(Some(msg)) => {
+                    self.credit -= 1;
+                    return Poll::Ready(Some(msg));
+                }
+                Poll::Ready(None) => source.receiver = None,
+                Poll::Pending => {}
+            }
+            self.credit = 0;
+        }
+
+        if self.sources.iter().all(|source| source.receiver.is_none()) {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Number of messages buffered across all open sources.
+    pub fn len(&self) -> usize {
+        self.sources
+            .iter()
+            .filter_map(|source| source.receiver.as_ref())
+            .map(mpsc::Receiver::len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn next_turn(&mut self) {
+        self.cursor = (self.cursor + 1) % self.sources.len();
+        self.credit = self.sources[self.cursor].weight;
+    }
+}
+
+impl<Msg> Debug for WeightedReceiver<Msg> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeightedReceiver")
+            .field("consumer", &self.consumer)
+            .field(
+                "sources",
+                &self
+                    .sources
+                    .iter()
+                    .map(|source| (source.name, source.weight, source.receiver.is_some()))
+                    .collect::<Vec<_>>(),
+            )
+            .field("cursor", &self.cursor)
+            .field("credit", &self.credit)
+            .finish()
+    }
+}
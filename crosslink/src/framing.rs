@@ -0,0 +1,117 @@
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+/// A serialization format for framed messages, e.g. a thin wrapper around `serde_json`
+/// or `bincode`.
+pub trait Format {
+    type Error;
+
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, Self::Error>;
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Self::Error>;
+}
+
+/// Why a frame couldn't be encoded or decoded.
+#[derive(Debug, Error)]
+pub enum FramingError<E> {
+    #[error("Frame of {0} bytes is too short to hold a tag.")]
+    Truncated(usize),
+    #[error("No message type of this link is tagged {0:#06x}.")]
+    UnknownTag(u16),
+    #[error("Payload failed to (de)serialize: {0}")]
+    Format(E),
+}
+
+/// Frames `msg` as its big-endian `tag` followed by the payload `F` serializes it into.
+pub fn encode<F: Format, T: Serialize>(
+    tag: u16,
+    msg: &T,
+) -> Result<Vec<u8>, FramingError<F::Error>> {
+    let payload = F::serialize(msg).map_err(FramingError::Format)?;
+    let mut frame = Vec::with_capacity(2 + payload.len());
+    frame.extend_from_slice(&tag.to_be_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Splits a frame into its tag and payload.
+pub fn split<E>(frame: &[u8]) -> Result<(u16, &[u8]), FramingError<E>> {
+    match frame {
+        [high, low, payload @ ..] => Ok((u16::from_be_bytes([*high, *low]), payload)),
+        _ => Err(FramingError::Truncated(frame.len())),
+    }
+}
+
+/// Whether no two of `tags` are equal, for checking at compile time that links
+/// sharing a transport can't mistake each other's messages:
+/// `const _: () = assert!(tags_distinct(&[a::Sender::TAG, b::Sender::TAG]));`.
+pub const fn tags_distinct(tags: &[u16]) -> bool {
+    let mut i = 0;
+    while i < tags.len() {
+        let mut j = i + 1;
+        while j < tags.len() {
+            if tags[i] == tags[j] {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Generates the framing of a link defined with `framing: true`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __framing {
+    ($($handle:ident: $msg:ty = $tag:expr),* $(,)?) => {
+        $(
+            impl $handle {
+                /// Wire tag of the message type this endpoint sends.
+                pub const TAG: u16 = $tag;
+            }
+        )*
+
+        /// A message of this link, named after the endpoint that sends it,
+        /// as encoded to and decoded from tagged frames.
+        #[derive(Debug)]
+        #[allow(non_camel_case_types, dead_code)]
+        pub enum Frame {
+            $($handle($msg)),*
+        }
+
+        #[allow(dead_code)]
+        impl Frame {
+            /// The tag this frame is prefixed with on the wire.
+            pub fn tag(&self) -> u16 {
+                match self {
+                    $(Self::$handle(_) => $handle::TAG),*
+                }
+            }
+
+            /// Serializes the message with `F`, prefixed with its tag.
+            pub fn encode_framed<F: $crate::framing::Format>(
+                &self,
+            ) -> Result<::std::vec::Vec<u8>, $crate::framing::FramingError<F::Error>> {
+                match self {
+                    $(Self::$handle(msg) => $crate::framing::encode::<F, $msg>($handle::TAG, msg)),*
+                }
+            }
+
+            /// Reads the tag of `frame` and deserializes the rest with `F`
+            /// as the message type it stands for.
+            pub fn decode_framed<F: $crate::framing::Format>(
+                frame: &[u8],
+            ) -> Result<Self, $crate::framing::FramingError<F::Error>> {
+                let (tag, payload) = $crate::framing::split(frame)?;
+                $(
+                    if tag == $handle::TAG {
+                        return F::deserialize::<$msg>(payload)
+                            .map(Self::$handle)
+                            .map_err($crate::framing::FramingError::Format);
+                    }
+                )*
+                Err($crate::framing::FramingError::UnknownTag(tag))
+            }
+        }
+    };
+}
@@ -0,0 +1,182 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    hash::{Hash, Hasher},
+};
+
+#[cfg(feature = "tokio")]
+use tokio::sync::mpsc;
+
+#[cfg(feature = "tokio")]
+use crate::backend::TokioBackend;
+use crate::{
+    backend::ChannelBackend,
+    dispatch::{self, SendHooks},
+    error::CommsError,
+    hash::TypeIdHasher,
+    receiver::ReceiverSlot,
+    sender::{ConcreteSenderTrait, SenderSlot},
+};
+
+/// An immutable [`Router`](crate::Router), obtained with
+/// [`Router::freeze`](crate::Router::freeze) once every pathway is registered.
+///
+/// Pathways live in collision-free tables computed once at freeze time, so every
+/// lookup is a single hash and a single probe, with no collision handling. Only sending
+/// and taking plain receivers are exposed: specialized receivers (instrumented, pooled,
+/// shared, ...) must be taken before freezing. A tap attached before freezing keeps receiving copies.
+#[derive(Debug)]
+pub struct FrozenRouter {
+    senders: PerfectTable<SenderSlot>,
+    receivers: PerfectTable<ReceiverSlot>,
+    hooks: SendHooks,
+}
+
+// Shared across tasks behind an `Arc`, same as `Router`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<FrozenRouter>();
+};
+
+/// A read-only map from marker `TypeId`s to slots, using a perfect hash:
+/// the seed and table size are picked so that no two keys share a bucket.
+#[derive(Debug)]
+struct PerfectTable<V> {
+    entries: Box<[(TypeId, V)]>,
+    /// Index into `entries` per bucket, `u32::MAX` for empty buckets.
+    buckets: Box<[u32]>,
+    seed: u64,
+    shift: u32,
+}
+
+const EMPTY_BUCKET: u32 = u32::MAX;
+
+/// Seeds tried at a given table size before doubling it.
+const SEEDS_PER_SIZE: u64 = 64;
+
+impl<V> PerfectTable<V> {
+    fn new(entries: impl IntoIterator<Item = (TypeId, V)>) -> Self {
+        let entries: Box<[_]> = entries.into_iter().collect();
+        // Start at a load factor of at most 1/2, which makes a collision-free seed
+        // quick to find for router-sized tables.
+        let mut bits = (entries.len().max(1) * 2)
+            .next_power_of_two()
+            .trailing_zeros();
+
+        loop {
+            let shift = u64::BITS - bits;
+            for seed in 0..SEEDS_PER_SIZE {
+                let mut buckets = vec![EMPTY_BUCKET; 1 << bits].into_boxed_slice();
+                let collision_free = entries.iter().enumerate().all(|(i, (id, _))| {
+                    let bucket = &mut buckets[Self::bucket(*id, seed, shift)];
+                    let vacant = *bucket == EMPTY_BUCKET;
+                    *bucket = i as u32;
+                    vacant
+                });
+                if collision_free {
+                    return Self {
+                        entries,
+                        buckets,
+                        seed,
+                        shift,
+                    };
+                }
+            }
+            bits += 1;
+        }
+    }
+
+    fn bucket(id: TypeId, seed: u64, shift: u32) -> usize {
+        let mut hasher = TypeIdHasher::default();
+        hasher.write_u64(seed);
+        id.hash(&mut hasher);
+        // Top bits, which the multiplicative hash mixes best.
+        (hasher.finish() >> shift) as usize
+    }
+
+    fn get(&self, id: TypeId) -> Option<&V> {
+        let index = self.buckets[Self::bucket(id, self.seed, self.shift)];
+        self.entries
+            .get(index as usize)
+            .filter(|(entry_id, _)| *entry_id == id)
+            .map(|(_, value)| value)
+    }
+}
+
+impl FrozenRouter {
+    pub(crate) fn new(
+        senders: impl IntoIterator<Item = (TypeId, SenderSlot)>,
+        receivers: impl IntoIterator<Item = (TypeId, ReceiverSlot)>,
+        hooks: SendHooks,
+    ) -> Self {
+        Self {
+            senders: PerfectTable::new(senders),
+            receivers: PerfectTable::new(receivers),
+            hooks,
+        }
+    }
+
+    fn typed_sender<SenderMarker, Msg>(&self) -> Result<&SenderSlot, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        dispatch::checked_sender::<SenderMarker, Msg>(
+            self.senders.get(TypeId::of::<SenderMarker>()),
+        )
+    }
+
+    /// Sends a message on a specified link, same as `Router::send`.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send<SenderMarker, Msg>(&self, message: Msg) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        dispatch::send::<SenderMarker, Msg>(
+            self.typed_sender::<SenderMarker, Msg>(),
+            &self.hooks,
+            message,
+        )
+        .await
+    }
+
+    /// Sends a message only if the pathway has room for it right now,
+    /// same as `Router::try_send`.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub fn try_send<SenderMarker, Msg>(&self, message: Msg) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        dispatch::try_send::<SenderMarker, Msg>(
+            self.typed_sender::<SenderMarker, Msg>(),
+            &self.hooks,
+            message,
+        )
+    }
+
+    /// Takes the receiving half of a pathway built on a custom [`ChannelBackend`].
+    #[track_caller]
+    pub fn take_receiver_with<B, ReceiverMarker, Msg>(&self) -> Result<B::Receiver<Msg>, CommsError>
+    where
+        B: ChannelBackend,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        dispatch::take_receiver::<B, ReceiverMarker, Msg>(
+            self.receivers.get(TypeId::of::<ReceiverMarker>()),
+        )
+    }
+
+    /// Takes the receiver for a pathway, same as `Router::take_receiver`.
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        self.take_receiver_with::<TokioBackend, ReceiverMarker, Msg>()
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    future::poll_fn,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Poll, Waker},
+};
+
+/// Holds sends on a paused pathway until it's resumed.
+///
+/// Waiting sends are woken in the order they started waiting.
+/// Built on std only, so pausing works the same on every backend.
+#[derive(Debug, Default)]
+pub(crate) struct Gate {
+    /// Checked first, so an open gate costs a single atomic load per send.
+    paused: AtomicBool,
+    waiters: Mutex<Vec<Waker>>,
+}
+
+impl Gate {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Release);
+    }
+
+    pub fn resume(&self) {
+        let waiters = {
+            let mut waiters = self.waiters.lock().unwrap_or_else(|e| e.into_inner());
+            self.paused.store(false, Ordering::Release);
+            std::mem::take(&mut *waiters)
+        };
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Resolves once the gate is open.
+    pub async fn opened(&self) {
+        poll_fn(|cx| {
+            if !self.is_paused() {
+                return Poll::Ready(());
+            }
+
+            let mut waiters = self.waiters.lock().unwrap_or_else(|e| e.into_inner());
+            // Resumed while we were taking the lock.
+            if !self.is_paused() {
+                return Poll::Ready(());
+            }
+            // A spurious re-poll pushes a second waker, but the first one keeps
+            // this send's place in the queue.
+            waiters.push(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+}
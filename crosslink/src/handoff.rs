@@ -0,0 +1,43 @@
+use tokio::sync::{mpsc, oneshot};
+
+/// The old consumer's end of a handoff prepared with
+/// [`Router::prepare_handoff`](crate::Router::prepare_handoff).
+///
+/// Dropping it without calling [`complete`](Self::complete) calls the handoff off: the
+/// new consumer's receiver then closes without yielding anything.
+#[derive(Debug)]
+pub struct ConsumerHandoff<Msg> {
+    old: oneshot::Sender<mpsc::Receiver<Msg>>,
+}
+
+impl<Msg> ConsumerHandoff<Msg> {
+    pub(crate) fn new(old: oneshot::Sender<mpsc::Receiver<Msg>>) -> Self {
+        Self { old }
+    }
+
+    /// Hands the old consumer's receiver over to the relay feeding the new consumer,
+    /// which from then on gets everything still buffered in it and sent to it, in order.
+    ///
+    /// Call it once the old consumer is done with the last message it received, and
+    /// only while the new consumer's receiver is alive: a relay that can't deliver
+    /// drops the old receiver along with whatever it still buffers.
+    pub fn complete(self, receiver: mpsc::Receiver<Msg>) {
+        // The relay only ever stops waiting when the runtime shuts down.
+        let _ = self.old.send(receiver);
+    }
+}
+
+/// Waits for the old receiver, then forwards it into `new` until either side is gone.
+pub(crate) async fn relay<Msg>(
+    old: oneshot::Receiver<mpsc::Receiver<Msg>>,
+    new: mpsc::Sender<Msg>,
+) {
+    let Ok(mut old) = old.await else {
+        return;
+    };
+    while let Some(msg) = old.recv().await {
+        if new.send(msg).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+/// A `HashMap` keyed by `TypeId`, using [`TypeIdHasher`].
+pub(crate) type TypeIdMap<V> = HashMap<TypeId, V, BuildHasherDefault<TypeIdHasher>>;
+
+/// A cheap hasher for `TypeId` keys.
+///
+/// `TypeId`s are already high-quality hashes of the type, so running them through
+/// SipHash on every lookup in the `send` hot path is wasted work. This just folds
+/// whatever the key writes into a single word, FxHash-style.
+/// It offers no HashDoS protection, which is fine since keys are never user-controlled.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct TypeIdHasher(u64);
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl Hasher for TypeIdHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = (self.0.rotate_left(5) ^ n).wrapping_mul(SEED);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
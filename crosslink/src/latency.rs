@@ -0,0 +1,112 @@
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use tokio::sync::mpsc;
+
+#[cfg(feature = "tokio")]
+use crate::envelope::Envelope;
+use crate::telemetry;
+
+/// Running latency figures for an instrumented pathway, shared between the
+/// router and the pathway's [`InstrumentedReceiver`].
+#[derive(Debug)]
+pub(crate) struct LatencyStats {
+    pathway: &'static str,
+    count: AtomicU64,
+    total_nanos: AtomicU64,
+    min_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+}
+
+impl LatencyStats {
+    pub fn new(pathway: &'static str) -> Self {
+        Self {
+            pathway,
+            count: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+            min_nanos: AtomicU64::new(u64::MAX),
+            max_nanos: AtomicU64::new(0),
+        }
+    }
+
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    fn record(&self, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.min_nanos.fetch_min(nanos, Ordering::Relaxed);
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+
+        telemetry::received(self.pathway, latency);
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let count = self.count.load(Ordering::Relaxed);
+        if count == 0 {
+            return LatencySnapshot {
+                pathway: self.pathway,
+                ..Default::default()
+            };
+        }
+
+        LatencySnapshot {
+            pathway: self.pathway,
+            count,
+            min: Duration::from_nanos(self.min_nanos.load(Ordering::Relaxed)),
+            max: Duration::from_nanos(self.max_nanos.load(Ordering::Relaxed)),
+            mean: Duration::from_nanos(self.total_nanos.load(Ordering::Relaxed) / count),
+        }
+    }
+}
+
+/// Point-in-time latency figures for an instrumented pathway,
+/// measured from `Router::send` to the consumer's `recv`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencySnapshot {
+    pub pathway: &'static str,
+    pub count: u64,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+}
+
+/// Receiving half of an instrumented pathway.
+///
+/// Unwraps each message and records how long it sat in the channel.
+/// With the `metrics` feature, every sample is also recorded into the
+/// `crosslink_pathway_latency_seconds` histogram, labelled by pathway.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct InstrumentedReceiver<T> {
+    receiver: mpsc::Receiver<Envelope<T>>,
+    stats: Arc<LatencyStats>,
+}
+
+#[cfg(feature = "tokio")]
+impl<T> InstrumentedReceiver<T> {
+    pub(crate) fn new(receiver: mpsc::Receiver<Envelope<T>>, stats: Arc<LatencyStats>) -> Self {
+        Self { receiver, stats }
+    }
+
+    /// Receives the next message, same as `mpsc::Receiver::recv`.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: a message's latency is recorded in the same poll
+    /// that returns it, so a dropped call neither loses a message nor records one.
+    pub async fn recv(&mut self) -> Option<T> {
+        let envelope = self.receiver.recv().await?;
+        self.stats.record(envelope.sent_at().elapsed());
+        Some(envelope.into_inner())
+    }
+
+    /// Returns the latency figures recorded on this pathway so far.
+    pub fn latency(&self) -> LatencySnapshot {
+        self.stats.snapshot()
+    }
+}
@@ -9,31 +9,27 @@
 //! ## Quick Start
 //!
 //! ```rust
-//! use crosslink::{Router, define_crosslink, CommsError};
+//! use crosslink::{Router, define_crosslink};
 //! use std::sync::Arc;
 //! use tokio::time::{sleep, Duration};
 //!
-//! // 1. Define your message types
+//! // 1. Define your message types.
+//! // No trait impls are needed: any type that is `Send + Sync + 'static + Debug`
+//! // can be sent and received (see `sender::ConcreteSenderTrait` and
+//! // `receiver::ConcreteReceiverTrait`).
 //! #[derive(Debug, Clone)]
 //! pub struct Ping(u32);
-//! // Ensure types satisfy Crosslink's internal trait bounds:
-//! // For sending: Send + Sync + 'static + Debug + Clone
-//! // For receiving: Send + 'static + Debug
-//! impl crosslink::sender::ConcreteSenderTrait for Ping {}
-//! impl crosslink::receiver::ConcreteReceiverMessageTrait for Ping {}
 //!
 //! #[derive(Debug, Clone)]
 //! pub struct Pong(u32);
-//! impl crosslink::sender::ConcreteSenderTrait for Pong {}
-//! impl crosslink::receiver::ConcreteReceiverMessageTrait for Pong {}
 //!
 //! // 2. Define the link using the macro (typically at module level)
 //! // This generates:
-//! // - `pub mod ping_pong { ... }` containing marker types and setup function.
-//! // - Marker types like `ping_pong::marker::PingerSenderMarker`.
-//! // - Setup function `ping_pong::setup_ping_pong_link(...)`.
+//! // - `pub mod ping_pong { ... }` containing handle types and the setup function.
+//! // - Marker types like `ping_pong::marker::PingerSend`.
+//! // - Setup function `ping_pong::setup_ping_pong(...)`.
 //! define_crosslink! {
-//!     link_id_prefix: PingPong, // Forms module `ping_pong` & func `setup_ping_pong`
+//!     link_id: "PingPong", // Forms module `ping_pong` & func `setup_ping_pong`
 //!     Pinger { sends: Ping, receives: Pong }, // Defines `ping_pong::Pinger` (nominal)
 //!     Ponger { sends: Pong, receives: Ping }, // Defines `ping_pong::Ponger` (nominal)
 //!     buffer_size: 8,
@@ -46,21 +42,21 @@
 //!     // Call the generated setup function for the link
 //!     ping_pong::setup_ping_pong(&mut router, Some(8)); // buffer_override
 //!
-//!     let shared_router = Arc::new(router);
+//!     let shared_router = router.into_shared();
 //!
 //!     // 4. Pinger Task
 //!     let pinger_router = Arc::clone(&shared_router);
 //!     tokio::spawn(async move {
 //!         // Obtain receiver using the generated marker and expected message type
 //!         let mut pinger_rx = pinger_router
-//!             .take_receiver::<ping_pong::marker::PingPongPingerReceiverMarker, Pong>()
+//!             .take_receiver::<ping_pong::marker::PingerRecv, Pong>()
 //!             .expect("Pinger: Failed to take Pong receiver");
 //!
 //!         for i in 0..2 {
 //!             let msg = Ping(i);
 //!             println!("[Pinger] Sending: {:?}", msg);
 //!             // Send using the corresponding sender marker and message type
-//!             if let Err(e) = pinger_router.send::<ping_pong::marker::PingPongPingerSenderMarker, _>(msg).await {
+//!             if let Err(e) = pinger_router.send::<ping_pong::marker::PingerSend, _>(msg).await {
 //!                 eprintln!("[Pinger] Send error: {}", e); return;
 //!             }
 //!
@@ -76,14 +72,14 @@
 //!     let ponger_router = Arc::clone(&shared_router);
 //!     tokio::spawn(async move {
 //!         let mut ponger_rx = ponger_router
-//!             .take_receiver::<ping_pong::marker::PingPongPongerReceiverMarker, Ping>()
+//!             .take_receiver::<ping_pong::marker::PongerRecv, Ping>()
 //!             .expect("Ponger: Failed to take Ping receiver");
 //!
 //!         while let Some(ping_msg) = ponger_rx.recv().await {
 //!             println!("[Ponger] Received: {:?}", ping_msg);
 //!             let reply = Pong(ping_msg.0); // Respond with Pong
 //!             println!("[Ponger] Sending: {:?}", reply);
-//!             if let Err(e) = ponger_router.send::<ping_pong::marker::PingPongPongerSenderMarker, _>(reply).await {
+//!             if let Err(e) = ponger_router.send::<ping_pong::marker::PongerSend, _>(reply).await {
 //!                 eprintln!("[Ponger] Send error: {}", e); return;
 //!             }
 //!         }
@@ -99,13 +95,1788 @@
 //! a setup function for each link, which configures your central `Router`.
 //! You then use the `Router` with generated marker types for type-safe message
 //! sending and receiver acquisition.
+//!
+//! ## Generated Names
+//!
+//! Marker names are derived from the endpoint handle names: `{HandleName}Send` for the
+//! sending side and `{HandleName}Recv` for the receiving side, inside the link's
+//! `marker` module, plus `{HandleName}Errors` and `ErrorsRecv` for endpoints declared
+//! with `errors:` (see [Error Channels](#error-channels)). With `reexport_markers: true`, they're also re-exported from the
+//! link module itself.
+//!
+//! Alongside `LINK_ID`, each link module exposes `LINK_SCHEMA_HASH`, a hash of its
+//! endpoints and message type names, for components that share serialized messages to
+//! check they were built from the same definition with the same compiler version.
+//!
+//! ```rust
+//! use crosslink::define_crosslink;
+//!
+//! #[derive(Debug, Clone)]
+//! pub struct Ping;
+//! #[derive(Debug, Clone)]
+//! pub struct Pong;
+//!
+//! define_crosslink! {
+//!     link_id: "PingPongLink",
+//!     PingerHandle { sends: Ping, receives: Pong },
+//!     PongerHandle { sends: Pong, receives: Ping },
+//!     buffer_size: 8,
+//!     reexport_markers: true,
+//! }
+//!
+//! use ping_pong_link::{PingerHandleRecv, PingerHandleSend, PongerHandleRecv, PongerHandleSend};
+//! use std::any::TypeId;
+//!
+//! fn main() {
+//!     assert_eq!(
+//!         std::any::type_name::<PingerHandleSend>(),
+//!         std::any::type_name::<ping_pong_link::marker::PingerHandleSend>(),
+//!     );
+//!     let _ = [
+//!         TypeId::of::<PingerHandleSend>(),
+//!         TypeId::of::<PingerHandleRecv>(),
+//!         TypeId::of::<PongerHandleSend>(),
+//!         TypeId::of::<PongerHandleRecv>(),
+//!     ];
+//!     let _: fn(&mut crosslink::Router, Option<usize>) -> (ping_pong_link::PingerHandle, ping_pong_link::PongerHandle) =
+//!         ping_pong_link::setup_ping_pong_link;
+//!     let _: u64 = ping_pong_link::LINK_SCHEMA_HASH;
+//! }
+//! ```
+//!
+//! Markers are only ever used as type parameters, and can't be constructed:
+//!
+//! ```compile_fail
+//! use crosslink::define_crosslink;
+//!
+//! #[derive(Debug)]
+//! pub struct Ping;
+//!
+//! define_crosslink! {
+//!     link_id: "PingPongLink",
+//!     PingerHandle { sends: Ping, receives: Ping },
+//!     PongerHandle { sends: Ping, receives: Ping },
+//!     buffer_size: 8,
+//! }
+//!
+//! fn main() {
+//!     let _ = ping_pong_link::marker::PingerHandleSend;
+//! }
+//! ```
+//!
+//! Each endpoint also gets aliases for its channel halves, named after the handle
+//! without a trailing `Handle`: `PingerReceiver` for what `PingerHandle` receives on,
+//! and `PingerSender` for the sender `Router::with_sender` lends out. A struct keeping
+//! a taken receiver can name its field's type through the link, and follows along
+//! when the message types change:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, Clone)]
+//! pub struct Ping;
+//! #[derive(Debug, Clone)]
+//! pub struct Pong;
+//!
+//! define_crosslink! {
+//!     link_id: "PingPongLink",
+//!     PingerHandle { sends: Ping, receives: Pong },
+//!     PongerHandle { sends: Pong, receives: Ping },
+//!     buffer_size: 8,
+//! }
+//!
+//! use ping_pong_link::marker::PingerHandleRecv;
+//!
+//! struct Pinger {
+//!     inbox: ping_pong_link::PingerReceiver,
+//! }
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     ping_pong_link::setup_ping_pong_link(&mut router, None);
+//!     let pinger = Pinger {
+//!         inbox: router.take_receiver::<PingerHandleRecv, Pong>().unwrap(),
+//!     };
+//!     assert!(pinger.inbox.is_empty());
+//! }
+//! ```
+//!
+//! The receiver alias names whatever the link's flavor hands out, e.g. an
+//! `InstrumentedReceiver` on instrumented links. Only plain links get a sender alias,
+//! and neither exists without the `tokio` feature.
+//!
+//! ## Symmetric Links
+//!
+//! Both endpoints may exchange the same message type, e.g. peers gossiping `Rumor`s.
+//! Pathways are keyed by marker rather than by message type, so the two directions
+//! never get mixed up:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! pub struct Rumor(&'static str);
+//!
+//! define_crosslink! {
+//!     link_id: "GossipLink",
+//!     Alice { sends: Rumor, receives: Rumor },
+//!     Bob { sends: Rumor, receives: Rumor },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! use gossip_link::{AliceRecv, AliceSend, BobRecv, BobSend};
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     gossip_link::setup_gossip_link(&mut router, None);
+//!     let mut alice_rx = router.take_receiver::<AliceRecv, Rumor>().unwrap();
+//!     let mut bob_rx = router.take_receiver::<BobRecv, Rumor>().unwrap();
+//!
+//!     router.try_send::<AliceSend, _>(Rumor("from alice")).unwrap();
+//!     router.try_send::<BobSend, _>(Rumor("from bob")).unwrap();
+//!
+//!     assert_eq!(bob_rx.try_recv().unwrap(), Rumor("from alice"));
+//!     assert_eq!(alice_rx.try_recv().unwrap(), Rumor("from bob"));
+//!     assert!(alice_rx.try_recv().is_err() && bob_rx.try_recv().is_err());
+//! }
+//! ```
+//!
+//! ## Message Types From Other Modules
+//!
+//! Message types can be named by any path that resolves where `define_crosslink!` is
+//! invoked: absolute, `crate::`, `self::`, `super::`, or through a `use` in scope.
+//!
+//! ```rust
+//! mod proto {
+//!     pub mod commands {
+//!         #[derive(Debug)]
+//!         pub struct StartCmd;
+//!
+//!         #[derive(Debug)]
+//!         pub struct Started;
+//!     }
+//! }
+//!
+//! mod app {
+//!     use super::proto::commands;
+//!     use crosslink::define_crosslink;
+//!
+//!     define_crosslink! {
+//!         link_id: "CommandLink",
+//!         Controller {
+//!             sends: crate::proto::commands::StartCmd,
+//!             receives: super::proto::commands::Started,
+//!         },
+//!         Engine {
+//!             sends: commands::Started,
+//!             receives: self::commands::StartCmd,
+//!         },
+//!         buffer_size: 4,
+//!     }
+//! }
+//!
+//! use app::command_link::{marker::ControllerSend, marker::EngineRecv, setup_command_link};
+//! use proto::commands::StartCmd;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = crosslink::Router::new();
+//!     setup_command_link(&mut router, None);
+//!     let mut engine = router.take_receiver::<EngineRecv, StartCmd>().unwrap();
+//!
+//!     router.send::<ControllerSend, _>(StartCmd).await.unwrap();
+//!     assert!(engine.recv().await.is_some());
+//! }
+//! ```
+//!
+//! ## Sharing the Router
+//!
+//! A router has two phases. Setup registers pathways through `&mut self`, so it can
+//! only happen while a single owner holds the router. At runtime, `send`,
+//! `take_receiver` and friends all work through `&self`, so tasks share one router
+//! behind an `Arc`:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//! use std::sync::Arc;
+//!
+//! define_crosslink! {
+//!     link_id: "WorkLink",
+//!     Boss { sends: u32, receives: () },
+//!     Worker { sends: (), receives: u32 },
+//!     buffer_size: 8,
+//!     reexport_markers: true,
+//! }
+//!
+//! use work_link::{BossSend, WorkerRecv};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     work_link::setup_work_link(&mut router, None);
+//!     let router: Arc<Router> = router.into_shared();
+//!
+//!     let boss = Arc::clone(&router);
+//!     tokio::spawn(async move { boss.send::<BossSend, u32>(1).await });
+//!     let mut worker_rx = router.take_receiver::<WorkerRecv, u32>().unwrap();
+//!     assert_eq!(worker_rx.recv().await, Some(1));
+//! }
+//! ```
+//!
+//! Moving the router into the `Arc` is what ends setup: nothing can be registered
+//! through a shared router, so every task sees the same pathways.
+//! The router deliberately isn't a cheaply cloneable handle itself. Clones would
+//! either need their maps behind a lock on every send, or could drift apart when one
+//! of them registers something.
+//! [`Router::freeze`] goes one step further, trading inspection and reconfiguration
+//! for a faster lookup, and is shared the same way.
+//!
+//! Components that only send and take receivers can depend on `impl bus::MessageBus`
+//! instead, implemented by `Router`, `FrozenRouter` and an `Arc` of either, and get a
+//! stub in tests:
+//!
+//! ```rust
+//! use std::{
+//!     any::{Any, TypeId},
+//!     sync::Mutex,
+//! };
+//!
+//! use crosslink::{CommsError, bus::MessageBus, sender::ConcreteSenderTrait};
+//! use tokio::sync::mpsc;
+//!
+//! struct AuditSend;
+//!
+//! async fn audit(bus: &impl MessageBus, event: String) -> Result<(), CommsError> {
+//!     bus.send::<AuditSend, String>(event).await
+//! }
+//!
+//! #[derive(Default)]
+//! struct RecordingBus(Mutex<Vec<String>>);
+//!
+//! impl MessageBus for RecordingBus {
+//!     async fn send<S, Msg>(&self, message: Msg) -> Result<(), CommsError>
+//!     where
+//!         S: Any + Send + Sync + 'static,
+//!         Msg: ConcreteSenderTrait,
+//!     {
+//!         self.0.lock().unwrap().push(format!("{message:?}"));
+//!         Ok(())
+//!     }
+//!
+//!     fn take_receiver<R, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+//!     where
+//!         R: Any + Send + Sync + 'static,
+//!         Msg: ConcreteSenderTrait,
+//!     {
+//!         Err(CommsError::PathwayNotFound("stub".into(), Some(TypeId::of::<R>())))
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let bus = RecordingBus::default();
+//!     audit(&bus, "login".to_string()).await.unwrap();
+//!     assert_eq!(*bus.0.lock().unwrap(), ["\"login\""]);
+//! }
+//! ```
+//!
+//! Its methods are generic, so there's no `dyn MessageBus`. `bus::DynMessageBus` is the
+//! object-safe variant, passing markers as `TypeId`s and messages boxed: mistakes the
+//! compiler catches with `MessageBus` only show up at runtime, and every send
+//! allocates.
+//!
+//! ## Taking Several Receivers
+//!
+//! A task consuming more than one pathway takes its receivers with
+//! `Router::take_receivers`, which gets all of them or none. If one take fails, the
+//! receivers taken so far go back into the router, so a retry, or another task, can
+//! still take them:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! define_crosslink! {
+//!     link_id: "SampleLink",
+//!     Sensor { sends: u32, receives: () },
+//!     Hub { sends: (), receives: u32 },
+//!     buffer_size: 4,
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "TickLink",
+//!     Clock { sends: u64, receives: () },
+//!     Hub { sends: (), receives: u64 },
+//!     buffer_size: 4,
+//! }
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     sample_link::setup_sample_link(&mut router, None);
+//!     tick_link::setup_tick_link(&mut router, None);
+//!
+//!     // Someone else already holds the ticks.
+//!     let ticks = router.take_receiver::<tick_link::marker::HubRecv, u64>().unwrap();
+//!
+//!     let err = router
+//!         .take_receivers::<sample_link::marker::HubRecv, u32, tick_link::marker::HubRecv, u64>()
+//!         .unwrap_err();
+//!     assert!(matches!(err, crosslink::CommsError::InternalInconsistency(_)));
+//!
+//!     // The samples receiver was put back.
+//!     assert!(router.take_receiver::<sample_link::marker::HubRecv, u32>().is_ok());
+//!     drop(ticks);
+//! }
+//! ```
+//!
+//! ## Type Aliases
+//!
+//! Message types are matched by the compiler, not by spelling, so each side may name
+//! the same type however it likes, e.g. through an alias:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct ControlMessage(u8);
+//! #[derive(Debug)]
+//! pub struct Status;
+//!
+//! type Cmd = ControlMessage;
+//!
+//! define_crosslink! {
+//!     link_id: "ControlLink",
+//!     Controller { sends: Cmd, receives: Status },
+//!     Device { sends: Status, receives: ControlMessage },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! use control_link::{ControllerSend, DeviceRecv};
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     control_link::setup_control_link(&mut router, None);
+//!     let mut device_rx = router.take_receiver::<DeviceRecv, ControlMessage>().unwrap();
+//!
+//!     router.try_send::<ControllerSend, Cmd>(ControlMessage(1)).unwrap();
+//!     assert_eq!(device_rx.try_recv().unwrap(), ControlMessage(1));
+//! }
+//! ```
+//!
+//! Every message type sent must be received by the other endpoint, and every type
+//! received sent by it. A direction left without a counterpart, typically after
+//! copy-pasting an endpoint, fails to compile, pointing at the receiving side with
+//! both types named. An orphaned `sends`:
+//!
+//! ```compile_fail
+//! use crosslink::define_crosslink;
+//!
+//! #[derive(Debug)]
+//! pub struct Reading;
+//! #[derive(Debug)]
+//! pub struct Ack;
+//!
+//! define_crosslink! {
+//!     link_id: "SensorLink",
+//!     Sensor { sends: Reading, receives: Ack },
+//!     Collector { sends: Ack, receives: Ack }, // nothing receives `Reading`
+//!     buffer_size: 4,
+//! }
+//!
+//! fn main() {}
+//! ```
+//!
+//! and an orphaned `receives`:
+//!
+//! ```compile_fail
+//! use crosslink::define_crosslink;
+//!
+//! #[derive(Debug)]
+//! pub struct Reading;
+//! #[derive(Debug)]
+//! pub struct Ack;
+//! #[derive(Debug)]
+//! pub struct Config;
+//!
+//! define_crosslink! {
+//!     link_id: "SensorLink",
+//!     Sensor { sends: Reading, receives: Config }, // nothing sends `Config`
+//!     Collector { sends: Ack, receives: Reading },
+//!     buffer_size: 4,
+//! }
+//!
+//! fn main() {}
+//! ```
+//!
+//! A link connects exactly two endpoints. A third one is rejected, with the count and
+//! the limit, rather than wiring channels between every pair:
+//!
+//! ```compile_fail
+//! use crosslink::define_crosslink;
+//!
+//! define_crosslink! {
+//!     link_id: "GossipLink",
+//!     Alice { sends: u32, receives: u32 },
+//!     Bob { sends: u32, receives: u32 },
+//!     Carol { sends: u32, receives: u32 },
+//!     buffer_size: 4,
+//! }
+//!
+//! fn main() {}
+//! ```
+//!
+//! Messages travel to another task and may outlive the code that sent them, so they
+//! have to be owned. A reference is rejected:
+//!
+//! ```compile_fail
+//! use crosslink::define_crosslink;
+//!
+//! #[derive(Debug)]
+//! pub struct Ping;
+//!
+//! define_crosslink! {
+//!     link_id: "PingLink",
+//!     Pinger { sends: &Ping, receives: () },
+//!     Ponger { sends: (), receives: &Ping },
+//!     buffer_size: 4,
+//! }
+//!
+//! fn main() {}
+//! ```
+//!
+//! and so is a type borrowing through a lifetime other than `'static`:
+//!
+//! ```compile_fail
+//! use crosslink::define_crosslink;
+//!
+//! #[derive(Debug)]
+//! pub struct Line<'a>(&'a str);
+//!
+//! define_crosslink! {
+//!     link_id: "LogLink",
+//!     Writer { sends: Line<'a>, receives: () },
+//!     Sink { sends: (), receives: Line<'a> },
+//!     buffer_size: 4,
+//! }
+//!
+//! fn main() {}
+//! ```
+//!
+//! ## Feature-Gated Links
+//!
+//! `cfg:` takes a `cfg` predicate and compiles the whole link only when it holds. Code
+//! naming the link from outside carries the same `#[cfg]`:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug)]
+//! pub struct Sample(pub i16);
+//!
+//! define_crosslink! {
+//!     link_id: "AudioLink",
+//!     Mic { sends: Sample, receives: () },
+//!     Speaker { sends: (), receives: Sample },
+//!     buffer_size: 64,
+//!     reexport_markers: true,
+//!     cfg: feature = "audio",
+//! }
+//!
+//! #[cfg(feature = "audio")]
+//! pub use audio_link::*;
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     #[cfg(feature = "audio")]
+//!     audio_link::setup_audio_link(&mut router, None);
+//!     assert!(router.topology().links.is_empty());
+//! }
+//! ```
+//!
+//! Without the feature, nothing of the link exists:
+//!
+//! ```compile_fail
+//! use crosslink::define_crosslink;
+//!
+//! #[derive(Debug)]
+//! pub struct Sample(pub i16);
+//!
+//! define_crosslink! {
+//!     link_id: "AudioLink",
+//!     Mic { sends: Sample, receives: () },
+//!     Speaker { sends: (), receives: Sample },
+//!     buffer_size: 64,
+//!     cfg: feature = "audio",
+//! }
+//!
+//! fn main() {
+//!     let _ = audio_link::LINK_ID;
+//! }
+//! ```
+//!
+//! ## Plugin Discovery
+//!
+//! With the `inventory` feature, every `define_crosslink!` invocation registers its link
+//! at compile time, and `Router::collect_all()` builds a router with all of them set up,
+//! however many crates they are spread over. Links are set up in no particular order,
+//! and two links with the same `link_id` make it panic. `crosslink::registry::links()`
+//! lists what was collected. See `examples/plugins.rs`.
+//!
+//! ## Topology
+//!
+//! `Router::topology` exports the router's wiring as plain data, e.g. to draw
+//! architecture diagrams: every link with its endpoints and metadata, and every pathway
+//! with its markers, message type and buffer size. With the `serde` feature it
+//! implements `Serialize`, ready to be dumped as JSON:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug)]
+//! pub struct Ping;
+//! #[derive(Debug)]
+//! pub struct Pong;
+//!
+//! define_crosslink! {
+//!     link_id: "PingPongLink",
+//!     PingerHandle { sends: Ping, receives: Pong },
+//!     PongerHandle { sends: Pong, receives: Ping },
+//!     buffer_size: 8,
+//!     meta: { owner: "team-net" },
+//! }
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     ping_pong_link::setup_ping_pong_link(&mut router, None);
+//!
+//!     let topology = router.topology();
+//!     let link = &topology.links[0];
+//!     assert_eq!(link.link_id, "PingPongLink");
+//!     assert_eq!(link.endpoints, ["PingerHandle", "PongerHandle"]);
+//!     assert_eq!(link.meta, [("owner", "team-net")]);
+//!
+//!     let ping = link
+//!         .pathways
+//!         .iter()
+//!         .find(|pathway| pathway.from == Some("PingerHandle"))
+//!         .unwrap();
+//!     assert_eq!(ping.to, Some("PongerHandle"));
+//!     assert!(ping.message_type.ends_with("Ping"));
+//!     assert_eq!(ping.buffer_size, Some(8));
+//!     assert!(topology.pathways.is_empty());
+//! }
+//! ```
+//!
+//! ## Wire Framing
+//!
+//! With the `serde` feature, `framing: true` gives each endpoint handle a `TAG: u16`
+//! for the message type it sends, derived from the type as written, and adds a `Frame`
+//! enum with one variant per endpoint. `Frame::encode_framed` prefixes the serialized
+//! message with its tag, and `Frame::decode_framed` reads the tag back to pick the type
+//! to deserialize, with any serde format plugged in through `framing::Format`. The two
+//! directions of a framed link must carry different types. See `examples/framing.rs`.
+//!
+//! A type gets the same tag in every link, but distinct types across links may collide,
+//! since tags are only 16 bits. Links multiplexed over one transport should each decode
+//! with their own `Frame`, e.g. after routing by connection or stream, or check their
+//! tags apart at compile time with `framing::tags_distinct`.
+//!
+//! ## Dynamic Routing
+//!
+//! When the topology only exists in configuration, `dynamic::DynamicRouter` registers
+//! pathways by name at runtime. Messages are `DynamicMessage`s, boxed payloads tagged
+//! with a type name that must match the pathway's:
+//!
+//! ```rust
+//! use crosslink::dynamic::{DynamicMessage, DynamicRouter, LinkSpec};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let specs = vec![LinkSpec {
+//!         name: "audit".to_string(),
+//!         message_type: "bytes".to_string(),
+//!         buffer_size: 8,
+//!     }];
+//!     let router = DynamicRouter::from_specs(specs).unwrap();
+//!     let mut audit = router.take_receiver("audit").unwrap();
+//!
+//!     let payload = b"user logged in".to_vec();
+//!     router
+//!         .send("audit", DynamicMessage::new("bytes", payload))
+//!         .await
+//!         .unwrap();
+//!     let wrong_tag = router.try_send("audit", DynamicMessage::new("text", "hi"));
+//!     assert!(wrong_tag.is_err());
+//!
+//!     let received = audit.recv().await.unwrap();
+//!     assert_eq!(received.downcast::<Vec<u8>>().unwrap(), b"user logged in");
+//! }
+//! ```
+//!
+//! ## Resizing Buffers
+//!
+//! `Router::reconfigure_buffer` swaps a pathway's channel for one of another size,
+//! moving the messages it already holds over in order, as long as the receiver is still
+//! held by the router:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Reading(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "ReadingLink",
+//!     Probe { sends: Reading, receives: () },
+//!     Recorder { sends: (), receives: Reading },
+//!     buffer_size: 2,
+//!     reexport_markers: true,
+//! }
+//!
+//! use reading_link::{ProbeSend, RecorderRecv};
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     reading_link::setup_reading_link(&mut router, None);
+//!
+//!     router.try_send::<ProbeSend, _>(Reading(1)).unwrap();
+//!     router.try_send::<ProbeSend, _>(Reading(2)).unwrap();
+//!     assert!(router.try_send::<ProbeSend, _>(Reading(3)).unwrap_err().is_full());
+//!
+//!     // Too small for what's buffered: nothing changes.
+//!     assert!(router
+//!         .reconfigure_buffer::<ProbeSend, RecorderRecv, Reading>(1)
+//!         .is_err());
+//!
+//!     router
+//!         .reconfigure_buffer::<ProbeSend, RecorderRecv, Reading>(4)
+//!         .unwrap();
+//!     router.try_send::<ProbeSend, _>(Reading(3)).unwrap();
+//!
+//!     let buffered: Vec<Reading> = router
+//!         .drain_iter::<RecorderRecv, Reading>()
+//!         .unwrap()
+//!         .collect();
+//!     assert_eq!(buffered, [Reading(1), Reading(2), Reading(3)]);
+//! }
+//! ```
+//!
+//! A resize that fails leaves the pathway as it was, with every message still in it.
+//! Spillover pathways, for one, can't be resized while their overflow queue is in use:
+//!
+//! ```rust
+//! use crosslink::Router;
+//!
+//! struct EventsSend;
+//! struct EventsRecv;
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     router
+//!         .register_spillover_pathway::<EventsSend, EventsRecv, u32>(2)
+//!         .unwrap();
+//!     for n in 0..4u32 {
+//!         router.try_send::<EventsSend, _>(n).unwrap();
+//!     }
+//!
+//!     let err = router
+//!         .reconfigure_buffer::<EventsSend, EventsRecv, u32>(8)
+//!         .unwrap_err();
+//!     assert!(err.is_full());
+//!     assert_eq!(router.receiver_len::<EventsRecv>().unwrap(), 2);
+//!     assert_eq!(router.overflow_depth::<EventsSend>(), Some(2));
+//!
+//!     let mut events = router.take_receiver::<EventsRecv, u32>().unwrap();
+//!     for n in 0..4u32 {
+//!         assert_eq!(events.recv().await, Some(n));
+//!     }
+//! }
+//! ```
+//!
+//! ## Inspecting Pending Messages
+//!
+//! While debugging, `Router::snapshot_pending` copies out what a pathway holds,
+//! without taking it away from its consumer: the messages are pushed back in order.
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! pub struct Event(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "EventLink",
+//!     Source { sends: Event, receives: () },
+//!     Sink { sends: (), receives: Event },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! use event_link::{SinkRecv, SourceSend};
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     event_link::setup_event_link(&mut router, None);
+//!     for id in 0..3u32 {
+//!         router.try_send::<SourceSend, _>(Event(id)).unwrap();
+//!     }
+//!
+//!     let snapshot = router.snapshot_pending::<SourceSend, SinkRecv, Event>().unwrap();
+//!     assert_eq!(snapshot.messages, [Event(0), Event(1), Event(2)]);
+//!     assert!(snapshot.unsent.is_empty());
+//!
+//!     let mut sink = router.take_receiver::<SinkRecv, Event>().unwrap();
+//!     for id in 0..3u32 {
+//!         assert_eq!(sink.try_recv(), Ok(Event(id)));
+//!     }
+//!     assert!(sink.try_recv().is_err());
+//! }
+//! ```
+//!
+//! Producers sending meanwhile may fill the buffer before everything is pushed back.
+//! What didn't fit is listed in `unsent`, and is no longer in the pathway.
+//!
+//! ## Pausing Pathways
+//!
+//! `Router::pause` holds a pathway's sends back, e.g. while its consumer restarts.
+//! `send` waits for `Router::resume`, and `try_send` fails as if the pathway were full.
+//! Waiting sends are released in the order they started waiting:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Job(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "JobLink",
+//!     Dispatcher { sends: Job, receives: () },
+//!     Worker { sends: (), receives: Job },
+//!     buffer_size: 16,
+//!     reexport_markers: true,
+//! }
+//!
+//! use job_link::{DispatcherSend, WorkerRecv};
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     job_link::setup_job_link(&mut router, None);
+//!     let router = router.into_shared();
+//!     let mut jobs = router.take_receiver::<WorkerRecv, Job>().unwrap();
+//!
+//!     router.pause::<DispatcherSend>().unwrap();
+//!     let mut senders = Vec::new();
+//!     for id in 0..10u32 {
+//!         let router = router.clone();
+//!         senders.push(tokio::spawn(async move {
+//!             router.send::<DispatcherSend, _>(Job(id)).await
+//!         }));
+//!         // Lets the task start waiting before the next one is spawned.
+//!         tokio::task::yield_now().await;
+//!     }
+//!
+//!     let err = router.try_send::<DispatcherSend, _>(Job(99)).unwrap_err();
+//!     assert!(matches!(err, crosslink::CommsError::ChannelFull(..)));
+//!     assert!(jobs.try_recv().is_err());
+//!
+//!     router.resume::<DispatcherSend>().unwrap();
+//!     for sender in senders {
+//!         sender.await.unwrap().unwrap();
+//!     }
+//!     for id in 0..10u32 {
+//!         assert_eq!(jobs.recv().await, Some(Job(id)));
+//!     }
+//! }
+//! ```
+//!
+//! ## Channel Backends
+//!
+//! Pathways are built on `tokio::sync::mpsc` by default, through the `tokio` feature.
+//! To run on another executor, disable default features and implement
+//! [`ChannelBackend`] for your channel of choice (e.g. `async-channel`), then wire
+//! pathways with `Router::register_sender_with`/`Router::register_receiver_with`.
+//!
+//! A backend panicking while the router holds a receiver's lock, e.g. in `len`,
+//! doesn't leave the pathway unusable: later calls recover the lock and carry on.
+//!
+//! ```rust
+//! use std::panic::{AssertUnwindSafe, catch_unwind};
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//!
+//! use crosslink::{ChannelBackend, Router, backend::TrySendError};
+//! use tokio::sync::mpsc;
+//!
+//! static PANICKED: AtomicBool = AtomicBool::new(false);
+//!
+//! /// `mpsc`, except that `len` panics the first time it's called.
+//! #[derive(Debug)]
+//! struct FlakyBackend;
+//!
+//! impl ChannelBackend for FlakyBackend {
+//!     type Sender<T: Send + 'static> = mpsc::Sender<T>;
+//!     type Receiver<T: Send + 'static> = mpsc::Receiver<T>;
+//!
+//!     fn channel<T: Send + 'static>(buffer: usize) -> (mpsc::Sender<T>, mpsc::Receiver<T>) {
+//!         mpsc::channel(buffer)
+//!     }
+//!
+//!     async fn send<T: Send + 'static>(sender: &mpsc::Sender<T>, msg: T) -> Result<(), T> {
+//!         sender.send(msg).await.map_err(|e| e.0)
+//!     }
+//!
+//!     fn try_send<T: Send + 'static>(
+//!         sender: &mpsc::Sender<T>,
+//!         msg: T,
+//!     ) -> Result<(), TrySendError<T>> {
+//!         sender.try_send(msg).map_err(|e| match e {
+//!             mpsc::error::TrySendError::Full(msg) => TrySendError::Full(msg),
+//!             mpsc::error::TrySendError::Closed(msg) => TrySendError::Closed(msg),
+//!         })
+//!     }
+//!
+//!     fn len<T: Send + 'static>(receiver: &mpsc::Receiver<T>) -> usize {
+//!         if !PANICKED.swap(true, Ordering::SeqCst) {
+//!             panic!("flaky backend");
+//!         }
+//!         receiver.len()
+//!     }
+//! }
+//!
+//! struct SensorSend;
+//! struct LoggerRecv;
+//!
+//! fn main() {
+//!     let mut router = Router::new();
+//!     router
+//!         .register_pathway_with::<FlakyBackend, SensorSend, LoggerRecv, u32>(4)
+//!         .unwrap();
+//!     router.try_send::<SensorSend, _>(7u32).unwrap();
+//!
+//!     // Poisons the receiver's lock.
+//!     let result = catch_unwind(AssertUnwindSafe(|| router.receiver_len::<LoggerRecv>()));
+//!     assert!(result.is_err());
+//!
+//!     assert_eq!(router.receiver_len::<LoggerRecv>().unwrap(), 1);
+//!     let mut logger = router
+//!         .take_receiver_with::<FlakyBackend, LoggerRecv, u32>()
+//!         .unwrap();
+//!     assert_eq!(logger.try_recv(), Ok(7));
+//! }
+//! ```
+//!
+//! ## Task Names
+//!
+//! Helpers spawning a task (`pipe`, `spawn_relay`, `spawn_filter_relay`,
+//! `connect_external_source`, `connect_external_sink` and `spawn_consumer`) take an
+//! optional name for it. Without one, the task is named after its pathway, e.g.
+//! `crosslink-relay:{from marker}->{to marker}` or `crosslink-consumer:{marker}`,
+//! using the markers' full type names.
+//!
+//! Tokio only names tasks when built with `RUSTFLAGS="--cfg tokio_unstable"` and its
+//! `tracing` feature, which crosslink's `tracing` feature turns on. Names then show up
+//! in `tokio-console`. Otherwise they're ignored.
+//!
+//! ## Relay Ordering
+//!
+//! Relays forward one message at a time, in the order they receive them, waiting on
+//! the downstream pathway whenever it's full. Chaining links through relays therefore
+//! keeps every message, in order and exactly once, however fast or slow the consumer
+//! at the end of the chain is:
+//!
+//! ```rust
+//! use std::{sync::Arc, time::Duration};
+//!
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Seq(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "IngestLink",
+//!     Producer { sends: Seq, receives: () },
+//!     Relay { sends: (), receives: Seq },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "DeliverLink",
+//!     Relay { sends: Seq, receives: () },
+//!     Consumer { sends: (), receives: Seq },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! const COUNT: u32 = 1000;
+//!
+//! async fn run(slow_consumer: bool) {
+//!     let mut router = Router::new();
+//!     ingest_link::setup_ingest_link(&mut router, None);
+//!     deliver_link::setup_deliver_link(&mut router, None);
+//!     let router = Arc::new(router);
+//!
+//!     router
+//!         .pipe::<ingest_link::RelayRecv, deliver_link::RelaySend, Seq>(None)
+//!         .unwrap();
+//!     let mut consumer = router
+//!         .take_receiver::<deliver_link::ConsumerRecv, Seq>()
+//!         .unwrap();
+//!
+//!     let producer = {
+//!         let router = Arc::clone(&router);
+//!         tokio::spawn(async move {
+//!             for i in 0..COUNT {
+//!                 router.send::<ingest_link::ProducerSend, _>(Seq(i)).await.unwrap();
+//!             }
+//!         })
+//!     };
+//!
+//!     for expected in 0..COUNT {
+//!         if slow_consumer && expected % 100 == 0 {
+//!             // Lets both buffers fill up, so the relay and producer wait on backpressure.
+//!             tokio::time::sleep(Duration::from_millis(1)).await;
+//!         }
+//!         assert_eq!(consumer.recv().await, Some(Seq(expected)));
+//!     }
+//!     producer.await.unwrap();
+//!     assert!(consumer.try_recv().is_err(), "no message arrives twice");
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     run(false).await;
+//!     run(true).await;
+//! }
+//! ```
+//!
+//! Since a relay only takes the next message once the previous one is forwarded, a
+//! consumer that stops draining eventually stalls the producer too. With one slot per
+//! buffer, the chain below holds three messages, one in each buffer and one in the
+//! relay's hands, before the fourth has to wait:
+//!
+//! ```rust
+//! use std::{sync::Arc, time::Duration};
+//!
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Seq(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "IngestLink",
+//!     Producer { sends: Seq, receives: () },
+//!     Relay { sends: (), receives: Seq },
+//!     buffer_size: 1,
+//!     reexport_markers: true,
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "DeliverLink",
+//!     Relay { sends: Seq, receives: () },
+//!     Consumer { sends: (), receives: Seq },
+//!     buffer_size: 1,
+//!     reexport_markers: true,
+//! }
+//!
+//! use ingest_link::ProducerSend;
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     ingest_link::setup_ingest_link(&mut router, None);
+//!     deliver_link::setup_deliver_link(&mut router, None);
+//!     let router = Arc::new(router);
+//!
+//!     router
+//!         .pipe::<ingest_link::RelayRecv, deliver_link::RelaySend, Seq>(None)
+//!         .unwrap();
+//!     // Taken, but never drained.
+//!     let _consumer = router
+//!         .take_receiver::<deliver_link::ConsumerRecv, Seq>()
+//!         .unwrap();
+//!
+//!     for i in 0..3u32 {
+//!         router.send::<ProducerSend, _>(Seq(i)).await.unwrap();
+//!     }
+//!     let stalled = tokio::time::timeout(
+//!         Duration::from_millis(50),
+//!         router.send::<ProducerSend, _>(Seq(3)),
+//!     );
+//!     assert!(stalled.await.is_err());
+//!     assert!(router.try_send::<ProducerSend, _>(Seq(3)).unwrap_err().is_full());
+//! }
+//! ```
+//!
+//! `Router::spawn_filter_relay` keeps the order of what it lets through. Like every
+//! relay, it finishes forwarding what's buffered once the source pathway closes, here
+//! by dropping the router holding its sender, and then exits:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! define_crosslink! {
+//!     link_id: "RawLink",
+//!     Counter { sends: u32, receives: () },
+//!     Filter { sends: (), receives: u32 },
+//!     buffer_size: 16,
+//!     reexport_markers: true,
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "EvenLink",
+//!     Filter { sends: u32, receives: () },
+//!     Printer { sends: (), receives: u32 },
+//!     buffer_size: 16,
+//!     reexport_markers: true,
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     raw_link::setup_raw_link(&mut router, None);
+//!     even_link::setup_even_link(&mut router, None);
+//!
+//!     let relay = router
+//!         .spawn_filter_relay::<raw_link::FilterRecv, even_link::FilterSend, u32>(
+//!             |n| n % 2 == 0,
+//!             None,
+//!         )
+//!         .unwrap();
+//!     let mut printer = router
+//!         .take_receiver::<even_link::PrinterRecv, u32>()
+//!         .unwrap();
+//!
+//!     for n in 0..10u32 {
+//!         router.send::<raw_link::CounterSend, _>(n).await.unwrap();
+//!     }
+//!     drop(router);
+//!     relay.await.unwrap();
+//!
+//!     let mut forwarded = Vec::new();
+//!     while let Some(n) = printer.recv().await {
+//!         forwarded.push(n);
+//!     }
+//!     assert_eq!(forwarded, [0, 2, 4, 6, 8]);
+//! }
+//! ```
+//!
+//! ## Dead Letters
+//!
+//! `Router::set_dead_letter` catches messages of a given type that couldn't be
+//! delivered because their receiver is gone, instead of losing them:
+//!
+//! ```rust
+//! use crosslink::{CommsError, Router, define_crosslink};
+//! use tokio::sync::mpsc;
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Job(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "JobLink",
+//!     Producer { sends: Job, receives: () },
+//!     Worker { sends: (), receives: Job },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! use job_link::{ProducerSend, WorkerRecv};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     job_link::setup_job_link(&mut router, None);
+//!     let (dead_tx, mut dead_rx) = mpsc::channel(16);
+//!     router.set_dead_letter::<Job>(dead_tx);
+//!
+//!     // The worker dies, dropping its receiver.
+//!     drop(router.take_receiver::<WorkerRecv, Job>().unwrap());
+//!
+//!     let result = router.send::<ProducerSend, _>(Job(7)).await;
+//!     assert!(matches!(result, Err(CommsError::SendFailed(..))));
+//!     assert_eq!(dead_rx.try_recv().unwrap(), Job(7));
+//! }
+//! ```
+//!
+//! ## Error Channels
+//!
+//! An endpoint declared with `errors: SomeError` reports failures to a supervisor on a
+//! channel of its own, next to its `sends` and `receives`. Domain message types stay
+//! free of error variants, and reports never queue up behind domain messages. Every
+//! reporting endpoint of a link feeds the same receiver, with each report tagged
+//! with the endpoint it came from:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub enum StageError {
+//!     Corrupt(u32),
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "StageLink",
+//!     Reader { sends: u32, receives: (), errors: StageError },
+//!     Writer { sends: (), receives: u32, errors: StageError },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! use stage_link::{ErrorsRecv, ReaderErrors, WriterErrors};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     stage_link::setup_stage_link(&mut router, None);
+//!     let mut supervisor = router.take_error_receiver::<ErrorsRecv, StageError>().unwrap();
+//!
+//!     router.report_error::<WriterErrors, _>(StageError::Corrupt(3)).await.unwrap();
+//!     router.report_error::<ReaderErrors, _>(StageError::Corrupt(5)).await.unwrap();
+//!
+//!     let report = supervisor.recv().await.unwrap();
+//!     assert_eq!((report.source, report.error), ("Writer", StageError::Corrupt(3)));
+//!     assert_eq!(supervisor.recv().await.unwrap().source, "Reader");
+//! }
+//! ```
+//!
+//! ## Fault Injection
+//!
+//! With the `testing` feature, `Router::chaos` wraps a pathway's receiver in a task that
+//! delays, drops and duplicates messages as set in a `chaos::ChaosConfig`, so recovery
+//! logic can be tested against the consumer's real code, which takes its receiver as
+//! usual:
+//!
+//! ```rust
+//! # #[cfg(feature = "testing")]
+//! # mod doc {
+//! use std::time::Duration;
+//!
+//! use crosslink::{CommsError, Router, chaos::ChaosConfig, define_crosslink};
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! pub struct Job(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "JobLink",
+//!     Dispatcher { sends: Job, receives: () },
+//!     Worker { sends: (), receives: Job },
+//!     buffer_size: 16,
+//!     reexport_markers: true,
+//! }
+//!
+//! use job_link::{DispatcherSend, WorkerRecv};
+//!
+//! #[tokio::main]
+//! pub async fn main() -> Result<(), CommsError> {
+//!     let mut router = Router::new();
+//!     job_link::setup_job_link(&mut router, None);
+//!     router.chaos::<WorkerRecv, Job>(ChaosConfig {
+//!         delay: Duration::from_millis(50),
+//!         drop_prob: 0.05,
+//!         dup_prob: 0.01,
+//!         seed: Some(42),
+//!     })?;
+//!
+//!     // The worker's code doesn't change.
+//!     let mut jobs = router.take_receiver::<WorkerRecv, Job>()?;
+//!     for id in 0..10u32 {
+//!         router.send::<DispatcherSend, _>(Job(id)).await?;
+//!     }
+//!     drop(router);
+//!
+//!     let mut received = Vec::new();
+//!     while let Some(Job(id)) = jobs.recv().await {
+//!         received.push(id);
+//!     }
+//!     // Some may be missing or repeated, but order is kept.
+//!     assert!(received.is_sorted());
+//!     Ok(())
+//! }
+//! # }
+//! # #[cfg(feature = "testing")]
+//! # fn main() -> Result<(), crosslink::CommsError> { doc::main() }
+//! # #[cfg(not(feature = "testing"))]
+//! # fn main() {}
+//! ```
+//!
+//! With a fixed `seed`, the same messages are dropped and duplicated on every run.
+//!
+//! ## Watch Links
+//!
+//! Some links only ever need their latest value, e.g. a configuration or a cursor
+//! position. `watch: true` builds both directions on `tokio::sync::watch` instead of
+//! mpsc, each starting from its message type's `Default`:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, Clone, Default, PartialEq)]
+//! pub struct Config {
+//!     pub verbose: bool,
+//! }
+//! #[derive(Debug, Clone, Default, PartialEq)]
+//! pub struct Cursor(pub u64);
+//!
+//! define_crosslink! {
+//!     link_id: "SettingsLink",
+//!     Admin { sends: Config, receives: Cursor },
+//!     Worker { sends: Cursor, receives: Config },
+//!     buffer_size: 1,
+//!     watch: true,
+//!     reexport_markers: true,
+//! }
+//!
+//! use settings_link::{AdminSend, WorkerRecv};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     settings_link::setup_settings_link(&mut router, None);
+//!     let mut config = router.watch_receiver::<WorkerRecv, Config>().unwrap();
+//!     assert_eq!(*config.borrow(), Config::default());
+//!
+//!     router.update_watch::<AdminSend, _>(Config { verbose: false }).unwrap();
+//!     router.update_watch::<AdminSend, _>(Config { verbose: true }).unwrap();
+//!
+//!     // Both updates landed before the worker looked: it only sees the last one.
+//!     config.changed().await.unwrap();
+//!     assert!(config.borrow_and_update().verbose);
+//!     assert!(!config.has_changed().unwrap());
+//! }
+//! ```
+//!
+//! The three kinds of channel differ in what a slow consumer gets:
+//! - mpsc, the default, queues every message for one consumer, and holds producers
+//!   back once `buffer_size` of them are waiting.
+//! - watch coalesces: each update replaces the value, so a consumer that falls behind
+//!   skips straight to the latest, and producers never wait. Any number of consumers
+//!   can watch, each through its own `watch_receiver`, and the router keeps one of its
+//!   own, so the pathway never closes.
+//! - `tokio::sync::broadcast`, which links don't offer, would hand every message to
+//!   every consumer, dropping the oldest for consumers lagging more than its capacity.
+//!
+//! `send` and `try_send` work on watch pathways too, and never wait either.
+//!
+//! ## Sharded Pathways
+//!
+//! `Router::register_sharded_pathway` spreads one pathway over a fixed number of
+//! channels, one per consumer shard. `send_sharded` picks the shard from a key, so
+//! messages sharing a key stay in order on the same shard:
+//!
+//! ```rust
+//! use crosslink::Router;
+//!
+//! struct Orders;
+//! struct OrderShards;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Order {
+//!     customer: u64,
+//!     item: &'static str,
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     router
+//!         .register_sharded_pathway::<Orders, OrderShards, Order>(4, 16)
+//!         .unwrap();
+//!     assert_eq!(router.shard_count::<Orders>(), Some(4));
+//!
+//!     for item in ["tea", "cake"] {
+//!         let order = Order { customer: 7, item };
+//!         router.send_sharded::<Orders, _>(order, 7).await.unwrap();
+//!     }
+//!
+//!     // Customer 7 is always handled by shard 7 % 4.
+//!     let mut shard = router.take_shard_receiver::<OrderShards, Order>(3).unwrap();
+//!     assert_eq!(shard.recv().await.unwrap().item, "tea");
+//!     assert_eq!(shard.recv().await.unwrap().item, "cake");
+//! }
+//! ```
+//!
+//! ## Weighted Fan-In
+//!
+//! `Router::fan_in` merges the receivers of several pathways carrying the same type,
+//! e.g. one link per producer, into a single `WeightedReceiver`. Sources take turns in
+//! weighted round-robin, so a busy bulk producer can't crowd out an urgent one:
+//!
+//! ```rust
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub enum Job {
+//!     Urgent(u32),
+//!     Bulk(u32),
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "UrgentLink",
+//!     Pager { sends: Job, receives: () },
+//!     Worker { sends: (), receives: Job },
+//!     buffer_size: 8,
+//!     reexport_markers: true,
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "BulkLink",
+//!     Batcher { sends: Job, receives: () },
+//!     Worker { sends: (), receives: Job },
+//!     buffer_size: 8,
+//!     reexport_markers: true,
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), crosslink::CommsError> {
+//!     let mut router = Router::new();
+//!     urgent_link::setup_urgent_link(&mut router, None);
+//!     bulk_link::setup_bulk_link(&mut router, None);
+//!
+//!     for i in 0..4 {
+//!         router.try_send::<urgent_link::PagerSend, _>(Job::Urgent(i))?;
+//!         router.try_send::<bulk_link::BatcherSend, _>(Job::Bulk(i))?;
+//!     }
+//!
+//!     let mut jobs = router
+//!         .fan_in::<urgent_link::Worker, Job>()
+//!         .source::<urgent_link::WorkerRecv>(3)?
+//!         .source::<bulk_link::WorkerRecv>(1)?
+//!         .build();
+//!
+//!     let mut order = Vec::new();
+//!     while let Some(job) = jobs.recv().await {
+//!         order.push(job);
+//!         if jobs.is_empty() {
+//!             break;
+//!         }
+//!     }
+//!     assert_eq!(
+//!         order,
+//!         [
+//!             Job::Urgent(0),
+//!             Job::Urgent(1),
+//!             Job::Urgent(2),
+//!             Job::Bulk(0),
+//!             Job::Urgent(3),
+//!             Job::Bulk(1),
+//!             Job::Bulk(2),
+//!             Job::Bulk(3),
+//!         ]
+//!     );
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ## Retrying Full Pathways
+//!
+//! `Router::send_with_retry` keeps retrying a `try_send` with exponential backoff while
+//! the pathway is full, for consumers that fall behind now and then:
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use crosslink::{Router, define_crosslink, retry::RetryPolicy};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Sample(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "SampleLink",
+//!     Sensor { sends: Sample, receives: () },
+//!     Logger { sends: (), receives: Sample },
+//!     buffer_size: 1,
+//!     reexport_markers: true,
+//! }
+//!
+//! use sample_link::{LoggerRecv, SensorSend};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     sample_link::setup_sample_link(&mut router, None);
+//!     let mut logger = router.take_receiver::<LoggerRecv, Sample>().unwrap();
+//!     let policy = RetryPolicy {
+//!         max_attempts: 3,
+//!         initial_delay: Duration::from_millis(1),
+//!         multiplier: 2.0,
+//!     };
+//!
+//!     router.try_send::<SensorSend, _>(Sample(1)).unwrap();
+//!
+//!     // Nobody reads: every attempt finds the buffer full.
+//!     let error = router
+//!         .send_with_retry::<SensorSend, _>(Sample(2), policy)
+//!         .await
+//!         .unwrap_err();
+//!     assert!(error.is_full());
+//!     assert!(error.to_string().ends_with("gave up after 3 attempts"));
+//!
+//!     // The logger catches up while the sensor backs off.
+//!     let retry = router.send_with_retry::<SensorSend, _>(Sample(3), policy);
+//!     let catch_up = async {
+//!         tokio::time::sleep(Duration::from_millis(2)).await;
+//!         logger.recv().await
+//!     };
+//!     let (sent, first) = tokio::join!(retry, catch_up);
+//!     assert!(sent.is_ok());
+//!     assert_eq!(first, Some(Sample(1)));
+//!     assert_eq!(logger.recv().await, Some(Sample(3)));
+//! }
+//! ```
+//!
+//! ## Spillover Pathways
+//!
+//! `Router::register_spillover_pathway` gives a pathway a bounded buffer that spills
+//! into an unbounded overflow queue when full, so bursts never hold producers up. A
+//! background task feeds the overflow back in order as the consumer catches up:
+//!
+//! ```rust
+//! use crosslink::Router;
+//!
+//! struct Events;
+//! struct EventLog;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     router
+//!         .register_spillover_pathway::<Events, EventLog, u32>(2)
+//!         .unwrap();
+//!
+//!     // A burst of 5 into a buffer of 2: nothing waits, nothing is turned away.
+//!     for event in 0..5u32 {
+//!         router.try_send::<Events, _>(event).unwrap();
+//!     }
+//!     assert_eq!(router.overflow_depth::<Events>(), Some(3));
+//!
+//!     let mut log = router.take_receiver::<EventLog, u32>().unwrap();
+//!     for expected in 0..5 {
+//!         assert_eq!(log.recv().await, Some(expected));
+//!     }
+//!     assert_eq!(router.overflow_peak::<Events>(), Some(3));
+//! }
+//! ```
+//!
+//! The overflow queue has no limit: a consumer that stops keeping up makes memory grow
+//! instead of slowing producers down, so keep an eye on `overflow_depth`.
+//!
+//! ## Request/Response Services
+//!
+//! `Router::as_service` turns a pathway carrying `service::Request<Req, Resp>` into a
+//! service whose `call` sends a request and resolves with its response. `poll_ready`
+//! and `call` follow the shape of `tower::Service`, and every call gets its own oneshot
+//! reply channel, so concurrent calls can't receive each other's responses:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! use crosslink::{Router, define_crosslink, service::Request};
+//!
+//! #[derive(Debug)]
+//! pub struct Lookup(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "LookupLink",
+//!     Client { sends: Request<Lookup, String>, receives: () },
+//!     Server { sends: (), receives: Request<Lookup, String> },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! use lookup_link::{ClientSend, ServerRecv};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     lookup_link::setup_lookup_link(&mut router, None);
+//!     let router = Arc::new(router);
+//!
+//!     let mut requests = router
+//!         .take_receiver::<ServerRecv, Request<Lookup, String>>()
+//!         .unwrap();
+//!     tokio::spawn(async move {
+//!         while let Some(request) = requests.recv().await {
+//!             let (Lookup(id), reply) = request.into_parts();
+//!             let _ = reply.send(format!("user-{id}"));
+//!         }
+//!     });
+//!
+//!     let mut service = router.as_service::<ClientSend, Lookup, String>();
+//!     let (first, second) = tokio::join!(service.call(Lookup(1)), service.call(Lookup(2)));
+//!     assert_eq!(first.unwrap(), "user-1");
+//!     assert_eq!(second.unwrap(), "user-2");
+//! }
+//! ```
+//!
+//! With the `tower` feature, the service implements `tower::Service<Req>` itself, to be
+//! wrapped in tower middleware or passed to code written against tower. See
+//! `examples/tower_service.rs`.
+//!
+//! ## Cancel Safety
+//!
+//! Every receiver this crate hands out has a cancel-safe `recv`, like
+//! `mpsc::Receiver::recv`: it can lose a `tokio::select!` race without losing a message.
+//!
+//! ```rust
+//! use std::{future::Future, time::Duration};
+//!
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Job(u32);
+//!
+//! define_crosslink! {
+//!     link_id: "PlainLink",
+//!     Left { sends: Job, receives: Job },
+//!     Right { sends: Job, receives: Job },
+//!     buffer_size: 4,
+//!     reexport_markers: true,
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "TimedLink",
+//!     Clock { sends: Job, receives: () },
+//!     Timer { sends: (), receives: Job },
+//!     buffer_size: 4,
+//!     instrumented: true,
+//!     reexport_markers: true,
+//! }
+//!
+//! define_crosslink! {
+//!     link_id: "HandoffLink",
+//!     Giver { sends: Job, receives: () },
+//!     Taker { sends: (), receives: Job },
+//!     buffer_size: 1,
+//!     rendezvous: true,
+//!     reexport_markers: true,
+//! }
+//!
+//! use handoff_link::{GiverSend, TakerRecv};
+//! use plain_link::{LeftRecv, LeftSend, RightRecv, RightSend};
+//! use timed_link::{ClockSend, TimerRecv};
+//!
+//! struct Encoded;
+//! struct Decoded;
+//!
+//! /// Polls a `recv` once, while nothing has been sent, and drops it halfway.
+//! async fn abandon<T: std::fmt::Debug>(recv: impl Future<Output = Option<T>>) {
+//!     let polled = tokio::time::timeout(Duration::ZERO, recv).await;
+//!     assert!(polled.is_err(), "nothing was sent yet");
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     plain_link::setup_plain_link(&mut router, None);
+//!     timed_link::setup_timed_link(&mut router, None);
+//!     handoff_link::setup_handoff_link(&mut router, None);
+//!     router
+//!         .register_codec_pathway::<Encoded, Decoded, Job, u32>(4, |Job(n)| n, Job)
+//!         .unwrap();
+//!     let router = router.into_shared();
+//!
+//!     let mut pooled = router.take_pooled_receiver::<RightRecv, Job>(4).unwrap();
+//!     abandon(pooled.recv()).await;
+//!     router.send::<LeftSend, _>(Job(1)).await.unwrap();
+//!     assert_eq!(pooled.recv().await, Some(Job(1)));
+//!
+//!     let shared = router.shared_receiver::<LeftRecv, Job>().unwrap();
+//!     abandon(shared.recv()).await;
+//!     router.send::<RightSend, _>(Job(2)).await.unwrap();
+//!     assert_eq!(shared.recv().await, Some(Job(2)));
+//!
+//!     let mut timed = router.take_instrumented_receiver::<TimerRecv, Job>().unwrap();
+//!     abandon(timed.recv()).await;
+//!     router.send::<ClockSend, _>(Job(3)).await.unwrap();
+//!     assert_eq!(timed.recv().await, Some(Job(3)));
+//!     assert_eq!(timed.latency().count, 1);
+//!
+//!     let mut decoding = router.take_decoding_receiver::<Decoded, Job, u32>().unwrap();
+//!     abandon(decoding.recv()).await;
+//!     router.send::<Encoded, _>(Job(4)).await.unwrap();
+//!     assert_eq!(decoding.recv().await, Some(Job(4)));
+//!
+//!     let mut handoff = router.take_rendezvous_receiver::<TakerRecv, Job>().unwrap();
+//!     abandon(handoff.recv()).await;
+//!     let giver = tokio::spawn({
+//!         let router = router.clone();
+//!         async move { router.send::<GiverSend, _>(Job(5)).await }
+//!     });
+//!     assert_eq!(handoff.recv().await, Some(Job(5)));
+//!     giver.await.unwrap().unwrap();
+//! }
+//! ```
+//!
+//! The one exception is `SharedReceiver::recv_filtered`, which loses a rejected message
+//! if dropped while sending it back.
+//!
+//! ## Latency Instrumentation
+//!
+//! Adding `instrumented: true` to a `define_crosslink!` invocation stamps every message
+//! on `send`. Consumers take an `InstrumentedReceiver` with
+//! `Router::take_instrumented_receiver`, and `Router::pathway_latency` reports how long
+//! messages sat in the channel. With the `metrics` feature, each sample is also recorded
+//! into the `crosslink_pathway_latency_seconds` histogram.
+//!
+//! ## Timestamped Links
+//!
+//! `timestamped: true` stamps every message with the `SystemTime` it was sent, e.g. for
+//! event sourcing, without the message types carrying it. Producers send as usual, and
+//! consumers take a `TimestampedReceiver` with `Router::take_timestamped_receiver`,
+//! yielding `(SystemTime, Msg)` pairs:
+//!
+//! ```rust
+//! use std::time::SystemTime;
+//!
+//! use crosslink::{Router, define_crosslink};
+//!
+//! #[derive(Debug)]
+//! pub struct Deposit(pub u64);
+//!
+//! define_crosslink! {
+//!     link_id: "LedgerLink",
+//!     Teller { sends: Deposit, receives: () },
+//!     Ledger { sends: (), receives: Deposit },
+//!     buffer_size: 16,
+//!     reexport_markers: true,
+//!     timestamped: true,
+//! }
+//!
+//! use ledger_link::{LedgerRecv, TellerSend};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut router = Router::new();
+//!     ledger_link::setup_ledger_link(&mut router, None);
+//!     let mut ledger = router.take_timestamped_receiver::<LedgerRecv, Deposit>().unwrap();
+//!
+//!     let before = SystemTime::now();
+//!     router.send::<TellerSend, Deposit>(Deposit(100)).await.unwrap();
+//!
+//!     let (at, deposit) = ledger.recv().await.unwrap();
+//!     assert_eq!(deposit.0, 100);
+//!     assert!(at >= before);
+//! }
+//! ```
+//!
+//! Both features share `envelope::Envelope`, which records the send time twice: as an
+//! `Instant` for latencies, and as a `SystemTime` for timestamps. Wall-clock time means
+//! something outside the process and can be stored, but jumps with the system clock,
+//! so it's no good for measuring durations. Receivers of instrumented pathways can be
+//! taken with `take_timestamped_receiver` as well, without recording latencies then.
+//!
+//! ## Observability
+//!
+//! The `observability` feature turns on `tracing` and `metrics` together.
+//! Every `send` and `try_send` then runs in a `crosslink.send` span, emits an event,
+//! and bumps `crosslink_messages_sent_total` or `crosslink_send_errors_total`.
+//! Latencies are only measured on links with `instrumented: true`, whose senders stamp
+//! each message; plain pathways carry no stamp and add nothing to the histogram.
+//! Instrumented pathways report each latency sample once, taken from that stamp, to both
+//! the histogram and a `message received` event.
+//! Pathways tracked with `Router::track_high_water_mark` also bump
+//! `crosslink_receiver_saturated_total` and emit a `receiver buffer full` event whenever
+//! a consumer finds their buffer full.
+//! All of them label the pathway as `pathway`, the sender marker's type name.
+//! In debug builds, a router dropped while still holding receivers, as listed by
+//! `Router::untaken_receivers`, emits a `router dropped with receivers never taken`
+//! warning naming their markers, typically pointing at a consumer never spawned.
+//! See `examples/observability.rs`.
 
+#[cfg(feature = "tokio")]
+pub mod ack;
+pub mod backend;
+#[cfg(feature = "tokio")]
+pub mod bus;
+#[cfg(all(feature = "tokio", feature = "testing"))]
+pub mod chaos;
+#[cfg(feature = "tokio")]
+pub mod codec;
+#[cfg(feature = "tokio")]
+pub(crate) mod dead_letter;
+#[cfg(feature = "tokio")]
+pub mod dedup;
+pub mod diagnosis;
+pub(crate) mod dispatch;
+#[cfg(feature = "tokio")]
+pub mod dynamic;
+pub mod envelope;
 pub mod error;
+#[cfg(feature = "tokio")]
+pub mod fan_in;
+#[cfg(feature = "serde")]
+pub mod framing;
+pub mod frozen;
+pub(crate) mod gate;
+#[cfg(feature = "tokio")]
+pub mod handoff;
+pub(crate) mod hash;
+pub mod latency;
+#[cfg(feature = "tokio")]
+pub(crate) mod overflow;
+#[cfg(feature = "tokio")]
+pub mod permit;
+#[cfg(feature = "tokio")]
+pub mod pool;
 pub mod receiver;
+#[cfg(feature = "inventory")]
+pub mod registry;
+#[cfg(feature = "tokio")]
+pub mod rendezvous;
+#[cfg(feature = "tokio")]
+pub mod report;
+#[cfg(feature = "tokio")]
+pub mod retry;
 pub mod router;
 pub mod sender;
+#[cfg(feature = "tokio")]
+pub mod service;
+#[cfg(feature = "tokio")]
+pub(crate) mod shard;
+#[cfg(feature = "tokio")]
+pub mod shared;
+pub mod stats;
+#[cfg(feature = "tokio")]
+pub mod tap;
+pub(crate) mod telemetry;
+pub mod topology;
+#[cfg(feature = "tokio")]
+pub(crate) mod watch;
 
+pub use backend::ChannelBackend;
+#[cfg(feature = "tokio")]
+pub use backend::TokioBackend;
 pub use error::CommsError;
+pub use frozen::FrozenRouter;
 pub use router::Router;
 
 pub use crosslink_macros::define_crosslink;
+
+// Stands in for `registry::__register_link` when links don't register themselves.
+#[cfg(not(feature = "inventory"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_link {
+    ($($tokens:tt)*) => {};
+}
+
+// Stands in for `backend::__channel_aliases`: without Tokio, there are no channel
+// types to name.
+#[cfg(not(feature = "tokio"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __channel_aliases {
+    ($($tokens:tt)*) => {};
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __framing {
+    ($($tokens:tt)*) => {
+        compile_error!("`framing: true` needs crosslink's `serde` feature");
+    };
+}
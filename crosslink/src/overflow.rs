@@ -0,0 +1,166 @@
+use std::{
+    any::{Any, TypeId},
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::{
+    error::CommsError,
+    sender::{ConcreteSenderTrait, DynSender, Undelivered, downcast_error},
+    telemetry,
+};
+
+/// How many messages of a spillover pathway are waiting in its overflow queue,
+/// and the most there ever were.
+#[derive(Debug)]
+pub(crate) struct OverflowDepth {
+    pathway: &'static str,
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl OverflowDepth {
+    pub fn new(pathway: &'static str) -> Self {
+        Self {
+            pathway,
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn get(&self) -> usize {
+        self.current.load(Ordering::Acquire)
+    }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    fn spilled(&self) {
+        let depth = self.current.fetch_add(1, Ordering::AcqRel) + 1;
+        self.peak.fetch_max(depth, Ordering::Relaxed);
+        telemetry::overflow_depth(self.pathway, depth);
+    }
+
+    fn drained(&self) {
+        let depth = self.current.fetch_sub(1, Ordering::AcqRel) - 1;
+        telemetry::overflow_depth(self.pathway, depth);
+    }
+}
+
+/// A sender for spillover pathways: messages go to the bounded channel while it has
+/// room, and into an unbounded overflow queue otherwise, so sends never wait.
+///
+/// While anything is in the overflow queue, new messages queue up behind it rather
+/// than skipping ahead into the bounded channel, so a producer's messages stay in order.
+#[derive(Debug)]
+pub(crate) struct OverflowSender<T> {
+    pub sender: mpsc::Sender<T>,
+    pub overflow: mpsc::UnboundedSender<T>,
+    pub depth: Arc<OverflowDepth>,
+}
+
+impl<T: ConcreteSenderTrait> OverflowSender<T> {
+    /// Hands the message back if the pathway is closed.
+    fn push(&self, msg: T) -> Result<(), Undelivered> {
+        let msg = if self.depth.get() == 0 {
+            match self.sender.try_send(msg) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(msg)) => return Err(Undelivered::closed(msg)),
+                Err(TrySendError::Full(msg)) => msg,
+            }
+        } else {
+            msg
+        };
+
+        // Counted before it's queued, so a send racing this one sees the overflow in use.
+        self.depth.spilled();
+        self.overflow.send(msg).map_err(|e| {
+            self.depth.drained();
+            Undelivered::closed(e.0)
+        })
+    }
+}
+
+impl<T: ConcreteSenderTrait> DynSender for OverflowSender<T> {
+    fn send_erased(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        let result = self.try_send_erased(msg_any);
+        Box::pin(async { result })
+    }
+
+    fn try_send_erased(&self, msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        let concrete_msg = msg_any.downcast::<T>().map_err(|_| downcast_error::<T>())?;
+        self.push(*concrete_msg)
+            .map_err(|undelivered| undelivered.error)
+    }
+
+    fn send_returning(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Undelivered>> + Send>> {
+        let result = self.try_send_returning(msg_any);
+        Box::pin(async { result })
+    }
+
+    fn try_send_returning(&self, msg_any: Box<dyn Any + Send>) -> Result<(), Undelivered> {
+        let concrete_msg = msg_any
+            .downcast::<T>()
+            .map_err(|_| Undelivered::lost(downcast_error::<T>()))?;
+        self.push(*concrete_msg)
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    /// Only counts the bounded channel, see `Router::overflow_depth` for the rest.
+    fn buffered(&self) -> Option<usize> {
+        Some(self.sender.max_capacity() - self.sender.capacity())
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        Some(self.sender.is_closed())
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(OverflowSender::<T> {
+            sender: self.sender.clone(),
+            overflow: self.overflow.clone(),
+            depth: Arc::clone(&self.depth),
+        })
+    }
+}
+
+/// Moves overflowed messages into the bounded channel as it frees up, in order.
+///
+/// Ends once every `OverflowSender` is gone and the queue is empty, or when the
+/// receiver is dropped, taking whatever is still queued down with it.
+pub(crate) async fn drain_overflow<T>(
+    mut overflow: mpsc::UnboundedReceiver<T>,
+    sender: mpsc::Sender<T>,
+    depth: Arc<OverflowDepth>,
+) {
+    while let Some(msg) = overflow.recv().await {
+        let delivered = sender.send(msg).await.is_ok();
+        depth.drained();
+        if !delivered {
+            overflow.close();
+            while overflow.try_recv().is_ok() {
+                depth.drained();
+            }
+            break;
+        }
+    }
+}
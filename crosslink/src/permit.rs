@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::{stats::PathwayCounters, telemetry};
+
+/// Room for one message on a pathway, reserved by
+/// [`Router::reserve_owned`](crate::Router::reserve_owned) ahead of the message itself.
+///
+/// The permit holds its own handle to the channel rather than borrowing the router, so
+/// it can be stored or moved into another task, and committed there once the message
+/// is ready. Until then, the slot counts against the pathway's buffer; dropping the
+/// permit unsent gives it back.
+#[derive(Debug)]
+pub struct OwnedPermit<Msg> {
+    permit: mpsc::OwnedPermit<Msg>,
+    counters: Arc<PathwayCounters>,
+    pathway: &'static str,
+}
+
+impl<Msg> OwnedPermit<Msg> {
+    pub(crate) fn new(
+        permit: mpsc::OwnedPermit<Msg>,
+        counters: Arc<PathwayCounters>,
+        pathway: &'static str,
+    ) -> Self {
+        Self {
+            permit,
+            counters,
+            pathway,
+        }
+    }
+
+    /// Sends `msg` into the reserved slot, counting it as sent on the pathway.
+    ///
+    /// Never waits, and can't fail: if the receiver was dropped since the reservation,
+    /// the message is dropped along with the channel.
+    pub fn send(self, msg: Msg) {
+        self.permit.send(msg);
+        let result = Ok(());
+        self.counters.record(&result);
+        telemetry::sent(self.pathway, &result);
+    }
+}
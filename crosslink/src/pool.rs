@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+
+/// A bounded pool of recycled messages, shared between a consumer and its producers.
+///
+/// The ownership dance goes like this:
+/// 1. A producer calls [`BufferPool::acquire`] (or [`BufferPool::acquire_or_else`])
+///    to get a buffer, falling back to a fresh allocation when the pool is empty.
+/// 2. The producer fills the buffer and moves it into the link with `Router::send`.
+/// 3. The consumer receives it through a [`PooledReceiver`], processes it, and moves
+///    it back with [`PooledReceiver::return_buffer`].
+/// 4. The buffer is now back in the pool, ready for step 1.
+///
+/// Buffers are returned as-is: the pool never clears them, so producers should reset
+/// them (e.g. `Vec::clear`) before reuse.
+/// When the pool is full, returned buffers are simply dropped.
+#[derive(Debug)]
+pub struct BufferPool<T> {
+    recycled_tx: mpsc::Sender<T>,
+    recycled_rx: Arc<Mutex<mpsc::Receiver<T>>>,
+}
+
+impl<T> Clone for BufferPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            recycled_tx: self.recycled_tx.clone(),
+            recycled_rx: Arc::clone(&self.recycled_rx),
+        }
+    }
+}
+
+impl<T> BufferPool<T> {
+    /// Creates a pool holding at most `capacity` recycled buffers.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, same as `tokio::sync::mpsc::channel`.
+    pub fn new(capacity: usize) -> Self {
+        let (recycled_tx, recycled_rx) = mpsc::channel(capacity);
+        Self {
+            recycled_tx,
+            recycled_rx: Arc::new(Mutex::new(recycled_rx)),
+        }
+    }
+
+    /// Takes a recycled buffer out of the pool, if one is available.
+    pub fn acquire(&self) -> Option<T> {
+        self.recycled_rx.lock().ok()?.try_recv().ok()
+    }
+
+    /// Takes a recycled buffer out of the pool, or creates a new one with `f`.
+    pub fn acquire_or_else(&self, f: impl FnOnce() -> T) -> T {
+        self.acquire().unwrap_or_else(f)
+    }
+
+    /// Hands a buffer back to the pool.
+    /// Returns `false` if the pool was full and the buffer was dropped instead.
+    pub fn release(&self, buffer: T) -> bool {
+        self.recycled_tx.try_send(buffer).is_ok()
+    }
+}
+
+/// A receiver paired with a [`BufferPool`] that consumed messages can be returned to.
+#[derive(Debug)]
+pub struct PooledReceiver<T> {
+    receiver: mpsc::Receiver<T>,
+    pool: BufferPool<T>,
+}
+
+impl<T> PooledReceiver<T> {
+    pub(crate) fn new(receiver: mpsc::Receiver<T>, pool_size: usize) -> Self {
+        Self {
+            receiver,
+            pool: BufferPool::new(pool_size),
+        }
+    }
+
+    /// Receives the next message, same as `mpsc::Receiver::recv`.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe, as it only awaits `mpsc::Receiver::recv`.
+    pub async fn recv(&mut self) -> Option<T> {
+        self.receiver.recv().await
+    }
+
+    /// Hands a consumed message back to the pool so producers can reuse it.
+    /// Returns `false` if the pool was full and the message was dropped instead.
+    pub fn return_buffer(&self, msg: T) -> bool {
+        self.pool.release(msg)
+    }
+
+    /// Returns a handle to the pool, to be cloned into producer tasks.
+    pub fn pool(&self) -> BufferPool<T> {
+        self.pool.clone()
+    }
+
+    /// Unwraps the underlying receiver, dropping the pool handle.
+    pub fn into_inner(self) -> mpsc::Receiver<T> {
+        self.receiver
+    }
+}
@@ -1,24 +1,180 @@
-use std::{any::Any, fmt::Debug};
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    panic::Location,
+    sync::{Mutex, MutexGuard},
+};
+
+#[cfg(feature = "tokio")]
+use std::marker::PhantomData;
+
+#[cfg(feature = "tokio")]
 use tokio::sync::mpsc;
 
+use crate::backend::ChannelBackend;
+#[cfg(feature = "tokio")]
+use crate::backend::TokioBackend;
+
+/// The bounds a message type must satisfy to be received.
+///
+/// Like [`ConcreteSenderTrait`](crate::sender::ConcreteSenderTrait), it is blanket
+/// implemented and can't be implemented by hand. It leaves out `Sync`, since the
+/// receiving side only ever moves messages out of the channel.
 pub trait ConcreteReceiverTrait: Send + 'static + Debug {}
 impl<T: Send + 'static + Debug> ConcreteReceiverTrait for T {}
 
-pub trait DynReceiver: Send + Debug {
+/// The type-erased receiving half of a pathway, as stored in the router until taken.
+///
+/// Internal, like `DynSender`.
+pub(crate) trait DynReceiver: Send + Debug {
     /// Consumes the Box<dyn DynReceiver> and converts it into a Box<dyn Any + Send>.
     /// This is essential for downcasting to a concrete type if needed.
     fn into_any(self: Box<Self>) -> Box<dyn Any + Send>;
+
+    /// Borrows the receiver as `Any`, to downcast it without taking it out of its slot.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Number of messages currently buffered, without consuming any.
+    fn len(&self) -> usize;
+
+    /// Whether the receiver is handed out as clones, staying in its slot for good.
+    fn is_lent(&self) -> bool {
+        false
+    }
+
+    /// What taking the receiver hands out, as `Any`: the backend's own receiver, or
+    /// by default the wrapper itself.
+    #[cfg(feature = "tokio")]
+    fn into_taken(self: Box<Self>) -> Box<dyn Any + Send> {
+        self.into_any()
+    }
 }
 
+/// A registered receiver and its metadata.
+/// The receiver itself is `None` once taken out of the router.
 #[derive(Debug)]
-pub(crate) struct ConcreteReceiver<T: ConcreteReceiverTrait> {
-    pub receiver: mpsc::Receiver<T>,
+pub(crate) struct ReceiverSlot {
+    pub msg_type_id: TypeId,
+    pub msg_type_name: &'static str,
+    pub marker_name: &'static str,
+    receiver: Mutex<Option<Box<dyn DynReceiver>>>,
+    /// Where the receiver was taken, to point later takers at the first one.
+    /// Only tracked with debug assertions on.
+    #[cfg(debug_assertions)]
+    taken_at: Mutex<Option<&'static Location<'static>>>,
 }
 
-impl<T: ConcreteReceiverTrait> DynReceiver for ConcreteReceiver<T> {
+impl ReceiverSlot {
+    /// `Msg` is what the channel carries, e.g. `Envelope<Msg>` rather than `Msg`.
+    pub fn new<Msg: 'static>(marker_name: &'static str, receiver: Box<dyn DynReceiver>) -> Self {
+        Self {
+            msg_type_id: TypeId::of::<Msg>(),
+            msg_type_name: std::any::type_name::<Msg>(),
+            marker_name,
+            receiver: Mutex::new(Some(receiver)),
+            #[cfg(debug_assertions)]
+            taken_at: Mutex::new(None),
+        }
+    }
+
+    /// Locks the receiver, recovering from poisoning.
+    ///
+    /// The lock only guards an `Option` that is taken or put back whole, so a panic
+    /// while holding it can't leave it half-updated. Recovering keeps the pathway
+    /// usable instead of bricking it for good.
+    pub fn lock(&self) -> MutexGuard<'_, Option<Box<dyn DynReceiver>>> {
+        self.receiver.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Records the caller as the one holding the receiver now.
+    #[track_caller]
+    pub fn mark_taken(&self) {
+        #[cfg(debug_assertions)]
+        self.set_taken_at(Some(Location::caller()));
+    }
+
+    /// Forgets the previous taker, once the receiver is back in the router.
+    pub fn mark_returned(&self) {
+        #[cfg(debug_assertions)]
+        self.set_taken_at(None);
+    }
+
+    /// Where the receiver was taken, if known.
+    #[cfg(debug_assertions)]
+    pub fn taken_at(&self) -> Option<&'static Location<'static>> {
+        *self.taken_at.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[cfg(not(debug_assertions))]
+    pub fn taken_at(&self) -> Option<&'static Location<'static>> {
+        None
+    }
+
+    #[cfg(debug_assertions)]
+    fn set_taken_at(&self, location: Option<&'static Location<'static>>) {
+        *self.taken_at.lock().unwrap_or_else(|e| e.into_inner()) = location;
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ConcreteReceiver<B: ChannelBackend, T: ConcreteReceiverTrait> {
+    pub receiver: B::Receiver<T>,
+}
+
+impl<B: ChannelBackend, T: ConcreteReceiverTrait> DynReceiver for ConcreteReceiver<B, T> {
     fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
-        // Since ConcreteReceiver<T> is 'static,
-        // Box<ConcreteReceiver<T>> can be cast to Box<dyn Any + Send>.
+        // Since ConcreteReceiver<B, T> is 'static,
+        // Box<ConcreteReceiver<B, T>> can be cast to Box<dyn Any + Send>.
         self
     }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        B::len(&self.receiver)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn into_taken(self: Box<Self>) -> Box<dyn Any + Send> {
+        Box::new(self.receiver)
+    }
+}
+
+/// The iterator behind `Router::drain_iter`: yields what's buffered in a receiver
+/// left in its slot, holding the slot's lock all along.
+#[cfg(feature = "tokio")]
+pub(crate) struct Drain<'a, T> {
+    guard: MutexGuard<'a, Option<Box<dyn DynReceiver>>>,
+    _msg: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "tokio")]
+impl<'a, T: ConcreteReceiverTrait> Drain<'a, T> {
+    /// Returns `None` unless `guard` holds a tokio receiver of `T`s.
+    pub fn new(mut guard: MutexGuard<'a, Option<Box<dyn DynReceiver>>>) -> Option<Self> {
+        Self::receiver(&mut guard)?;
+        Some(Self {
+            guard,
+            _msg: PhantomData,
+        })
+    }
+
+    fn receiver(slot: &mut Option<Box<dyn DynReceiver>>) -> Option<&mut mpsc::Receiver<T>> {
+        slot.as_mut()?
+            .as_any_mut()
+            .downcast_mut::<ConcreteReceiver<TokioBackend, T>>()
+            .map(|concrete| &mut concrete.receiver)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: ConcreteReceiverTrait> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        Self::receiver(&mut self.guard)?.try_recv().ok()
+    }
 }
@@ -0,0 +1,51 @@
+use crate::router::Router;
+
+/// A link submitted by `define_crosslink!` for [`Router::collect_all`].
+///
+/// Built by the macro only, through the `inventory` feature.
+#[derive(Debug)]
+pub struct LinkRegistration {
+    link_id: &'static str,
+    setup: fn(&mut Router),
+}
+
+impl LinkRegistration {
+    #[doc(hidden)]
+    pub const fn new(link_id: &'static str, setup: fn(&mut Router)) -> Self {
+        Self { link_id, setup }
+    }
+
+    /// The `link_id` of the registered link.
+    pub fn link_id(&self) -> &'static str {
+        self.link_id
+    }
+
+    /// Runs the link's setup function on `router`, with its default buffer size.
+    pub(crate) fn setup(&self, router: &mut Router) {
+        (self.setup)(router)
+    }
+}
+
+inventory::collect!(LinkRegistration);
+
+/// Every link registered in the binary, in no particular order.
+pub fn links() -> impl Iterator<Item = &'static LinkRegistration> {
+    inventory::iter::<LinkRegistration>.into_iter()
+}
+
+#[doc(hidden)]
+pub use inventory;
+
+/// Submits a link for `Router::collect_all`. Expands to nothing without the
+/// `inventory` feature, so `define_crosslink!` can call it unconditionally.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_link {
+    ($link_id:expr, $setup:path) => {
+        $crate::registry::inventory::submit! {
+            $crate::registry::LinkRegistration::new($link_id, |router| {
+                $setup(router, ::core::option::Option::None);
+            })
+        }
+    };
+}
@@ -0,0 +1,128 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    pin::Pin,
+};
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    backend::ChannelBackend,
+    error::CommsError,
+    sender::{ConcreteSenderTrait, DynSender, downcast_error},
+};
+
+/// A message in flight on a rendezvous pathway, along with the signal
+/// that releases its sender once the receiver takes it.
+#[derive(Debug)]
+pub struct Handoff<T> {
+    msg: T,
+    taken_tx: oneshot::Sender<()>,
+}
+
+/// A sender for rendezvous pathways: `send` only resolves once the receiver
+/// has taken the message out of the channel.
+#[derive(Debug)]
+pub(crate) struct RendezvousSender<B: ChannelBackend, T: ConcreteSenderTrait> {
+    pub sender: B::Sender<Handoff<T>>,
+}
+
+impl<B: ChannelBackend, T: ConcreteSenderTrait> DynSender for RendezvousSender<B, T> {
+    fn send_erased(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        match msg_any.downcast::<T>() {
+            Ok(concrete_msg) => {
+                let sender_clone = self.sender.clone();
+                Box::pin(async move {
+                    let (taken_tx, taken_rx) = oneshot::channel();
+                    let handoff = Handoff {
+                        msg: *concrete_msg,
+                        taken_tx,
+                    };
+
+                    B::send(&sender_clone, handoff).await.map_err(|_| {
+                        CommsError::SendFailed(
+                            format!(
+                                "Failed to send message of type {}: channel closed",
+                                std::any::type_name::<T>(),
+                            ),
+                            None,
+                        )
+                    })?;
+
+                    taken_rx.await.map_err(|_| {
+                        CommsError::SendFailed(
+                            format!(
+                                "Receiver for message of type {} was dropped before taking it",
+                                std::any::type_name::<T>(),
+                            ),
+                            None,
+                        )
+                    })
+                })
+            }
+            Err(_) => Box::pin(async { Err(downcast_error::<T>()) }),
+        }
+    }
+
+    fn try_send_erased(&self, _msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        // There's no buffer to drop the message into, and no way to tell whether
+        // the receiver is waiting right now.
+        Err(CommsError::ChannelFull(format!(
+            "Rendezvous pathway for {} can't hand off a message without waiting",
+            std::any::type_name::<T>(),
+        )))
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn buffered(&self) -> Option<usize> {
+        B::buffered(&self.sender)
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        B::is_closed(&self.sender)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(RendezvousSender::<B, T> {
+            sender: self.sender.clone(),
+        })
+    }
+}
+
+/// Receiving half of a rendezvous pathway.
+///
+/// Taking a message out with [`RendezvousReceiver::recv`] is what releases the
+/// sender waiting on it.
+#[derive(Debug)]
+pub struct RendezvousReceiver<T> {
+    receiver: mpsc::Receiver<Handoff<T>>,
+}
+
+impl<T> RendezvousReceiver<T> {
+    pub(crate) fn new(receiver: mpsc::Receiver<Handoff<T>>) -> Self {
+        Self { receiver }
+    }
+
+    /// Receives the next message and releases its sender.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: dropping a pending call loses no message, and the
+    /// sender keeps waiting until a later `recv` takes it.
+    pub async fn recv(&mut self) -> Option<T> {
+        let handoff = self.receiver.recv().await?;
+        // The sender may have stopped waiting, that's fine.
+        let _ = handoff.taken_tx.send(());
+        Some(handoff.msg)
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    pin::Pin,
+};
+
+use crate::{
+    backend::{ChannelBackend, TrySendError},
+    error::CommsError,
+    sender::{
+        ConcreteSenderTrait, DynSender, Undelivered, closed_error, downcast_error, try_send_error,
+    },
+};
+
+/// An error reported by an endpoint on its link's error channel, as received by the
+/// link's supervisor.
+#[derive(Debug, Clone)]
+pub struct ErrorReport<E> {
+    /// The handle name of the endpoint that reported it, e.g. `PingerHandle`.
+    pub source: &'static str,
+    pub error: E,
+}
+
+/// A sender that accepts bare errors and tags them with the endpoint reporting them,
+/// so every endpoint of a link can feed one supervisor channel.
+#[derive(Debug)]
+pub(crate) struct ReportingSender<B: ChannelBackend, E: ConcreteSenderTrait> {
+    pub sender: B::Sender<ErrorReport<E>>,
+    pub source: &'static str,
+}
+
+impl<B: ChannelBackend, E: ConcreteSenderTrait> ReportingSender<B, E> {
+    fn report(&self, error: E) -> ErrorReport<E> {
+        ErrorReport {
+            source: self.source,
+            error,
+        }
+    }
+}
+
+impl<B: ChannelBackend, E: ConcreteSenderTrait> DynSender for ReportingSender<B, E> {
+    fn send_erased(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        match msg_any.downcast::<E>() {
+            Ok(error) => {
+                let sender_clone = self.sender.clone();
+                let report = self.report(*error);
+                Box::pin(async move {
+                    B::send(&sender_clone, report)
+                        .await
+                        .map_err(|_| closed_error::<E>())
+                })
+            }
+            Err(_) => Box::pin(async { Err(downcast_error::<E>()) }),
+        }
+    }
+
+    fn try_send_erased(&self, msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        let error = msg_any.downcast::<E>().map_err(|_| downcast_error::<E>())?;
+        B::try_send(&self.sender, self.report(*error)).map_err(try_send_error::<E, _>)
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<E>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<E>()
+    }
+
+    fn buffered(&self) -> Option<usize> {
+        B::buffered(&self.sender)
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        B::is_closed(&self.sender)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(ReportingSender::<B, E> {
+            sender: self.sender.clone(),
+            source: self.source,
+        })
+    }
+
+    fn send_returning(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Undelivered>> + Send>> {
+        match msg_any.downcast::<E>() {
+            Ok(error) => {
+                let sender_clone = self.sender.clone();
+                let report = self.report(*error);
+                Box::pin(async move {
+                    B::send(&sender_clone, report)
+                        .await
+                        .map_err(|report| Undelivered::closed(report.error))
+                })
+            }
+            Err(_) => Box::pin(async { Err(Undelivered::lost(downcast_error::<E>())) }),
+        }
+    }
+
+    fn try_send_returning(&self, msg_any: Box<dyn Any + Send>) -> Result<(), Undelivered> {
+        let error = msg_any
+            .downcast::<E>()
+            .map_err(|_| Undelivered::lost(downcast_error::<E>()))?;
+        B::try_send(&self.sender, self.report(*error)).map_err(|e| match e {
+            TrySendError::Closed(report) => Undelivered::closed(report.error),
+            TrySendError::Full(report) => Undelivered::full(report.error),
+        })
+    }
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+/// How [`Router::send_with_retry`](crate::Router::send_with_retry) retries a send
+/// while the pathway is full.
+///
+/// The first retry waits `initial_delay`, and every further one `multiplier` times
+/// longer than the last.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts made in total, the first one included. `0` behaves like `1`.
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    /// Five attempts, backing off from 10ms: 10, 20, 40 and 80ms.
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(10),
+            multiplier: 2.0,
+        }
+    }
+}
@@ -2,157 +2,3662 @@ use std::{
     any::{Any, TypeId},
     collections::HashMap,
     fmt::Debug,
-    sync::Mutex,
+    sync::Arc,
 };
 
-use tokio::sync::mpsc;
+#[cfg(feature = "tokio")]
+use std::{hash::Hash, marker::PhantomData, time::Duration};
+#[cfg(feature = "tokio")]
+use tokio::{
+    sync::{Notify, mpsc, watch},
+    task::JoinHandle,
+};
 
+#[cfg(all(feature = "tokio", feature = "testing"))]
+use crate::chaos::{self, ChaosConfig};
+#[cfg(feature = "tokio")]
+use crate::{
+    ack::{self, AckSender, Acked, Reply},
+    backend::TokioBackend,
+    codec::{Codec, CodecSender, DecodingReceiver},
+    dedup::DedupReceiver,
+    diagnosis::PendingSnapshot,
+    envelope::TimestampedReceiver,
+    fan_in::FanInBuilder,
+    handoff::{self, ConsumerHandoff},
+    latency::InstrumentedReceiver,
+    overflow::{self, OverflowDepth, OverflowSender},
+    permit::OwnedPermit,
+    pool::PooledReceiver,
+    receiver::Drain,
+    rendezvous::{Handoff, RendezvousReceiver, RendezvousSender},
+    report::{ErrorReport, ReportingSender},
+    retry::RetryPolicy,
+    sender::downcast_error,
+    service::PathwayService,
+    shard::{KeyedSender, Shards},
+    shared::SharedReceiver,
+    stats::HighWaterMark,
+    tap::{Tap, TappedMessage},
+    watch::{WatchReceiver, WatchSender},
+};
 use crate::{
+    backend::ChannelBackend,
+    diagnosis::{Diagnosis, SenderDiagnosis},
+    dispatch::{self, Pathway, SendHooks},
+    envelope::{Envelope, EnvelopeSender},
     error::CommsError,
-    receiver::{ConcreteReceiver, ConcreteReceiverTrait, DynReceiver},
-    sender::{ConcreteSender, ConcreteSenderTrait, DynSender},
+    frozen::FrozenRouter,
+    gate::Gate,
+    hash::TypeIdMap,
+    latency::{LatencySnapshot, LatencyStats},
+    receiver::{ConcreteReceiver, ConcreteReceiverTrait, DynReceiver, ReceiverSlot},
+    sender::{ConcreteSender, ConcreteSenderTrait, DebugRenderer, SenderSlot},
+    stats::PathwayStats,
+    topology::{LinkTopology, PathwayTopology, Topology},
 };
 
+/// What `reconfigure_buffer` takes out of the router before rebuilding a pathway, to put
+/// it back if the rebuild fails.
+#[cfg(feature = "tokio")]
+struct RemovedPathway {
+    sender: Option<SenderSlot>,
+    receiver: Option<ReceiverSlot>,
+    link: PathwayLink,
+    overflow_depth: Option<Arc<OverflowDepth>>,
+}
+
+/// Ties the two halves of a pathway registered in one go,
+/// and knows how to rebuild it from scratch.
+#[derive(Debug, Clone, Copy)]
+struct PathwayLink {
+    receiver_marker: TypeId,
+    rebuild: fn(&mut Router, usize) -> Result<(), CommsError>,
+    /// What `send` takes, and what the channel carries, e.g. `Envelope<Msg>`,
+    /// for [`Router::validate`].
+    msg_type: (TypeId, &'static str),
+    carried_type: (TypeId, &'static str),
+    /// Capacity of the channel, 0 for rendezvous pathways, for [`Router::topology`].
+    buffer: usize,
+    /// Set when the pathway is one direction of a `define_crosslink!` link.
+    link: Option<LinkDirection>,
+}
+
+/// The link a pathway belongs to, and which of its endpoints sends and receives on it.
+#[derive(Debug, Clone, Copy)]
+struct LinkDirection {
+    link_id: &'static str,
+    from: &'static str,
+    to: &'static str,
+}
+
 #[derive(Debug, Default)]
 #[allow(clippy::type_complexity)]
 pub struct Router {
-    typed_senders: HashMap<TypeId, Box<dyn DynSender>>,
-    typed_receivers: HashMap<TypeId, (TypeId, Mutex<Option<Box<dyn DynReceiver>>>)>,
+    typed_senders: TypeIdMap<SenderSlot>,
+    typed_receivers: TypeIdMap<ReceiverSlot>,
+    /// Keyed by both the sender and the receiver marker of an instrumented pathway.
+    latency_stats: TypeIdMap<Arc<LatencyStats>>,
+    link_meta: HashMap<&'static str, &'static [(&'static str, &'static str)]>,
+    /// Keyed by sender marker.
+    pathway_links: TypeIdMap<PathwayLink>,
+    hooks: SendHooks,
+    /// `Arc<Codec<Domain, Wire>>`s, keyed by both markers of a codec pathway.
+    #[cfg(feature = "tokio")]
+    codecs: TypeIdMap<Arc<dyn Any + Send + Sync>>,
+    /// Keyed by both markers of a sharded pathway.
+    #[cfg(feature = "tokio")]
+    shards: TypeIdMap<Arc<Shards>>,
+    /// Wakes `await_receiver` calls whenever a receiver is returned.
+    #[cfg(feature = "tokio")]
+    receiver_available: Notify,
+    /// Keyed by receiver marker, for pathways whose high-water mark is tracked.
+    #[cfg(feature = "tokio")]
+    high_water_marks: TypeIdMap<Arc<HighWaterMark>>,
+    /// Keyed by sender marker, for spillover pathways.
+    #[cfg(feature = "tokio")]
+    overflow_depths: TypeIdMap<Arc<OverflowDepth>>,
+}
+
+/// With debug assertions on, a router dropped with receivers nobody took warns about
+/// them through `tracing`, see [`Router::untaken_receivers`].
+#[cfg(debug_assertions)]
+impl Drop for Router {
+    fn drop(&mut self) {
+        let untaken = self.untaken_receivers();
+        if !untaken.is_empty() {
+            crate::telemetry::untaken_receivers(&untaken);
+        }
+    }
 }
 
+// Routers are shared across tasks behind an `Arc`,
+// so a non-thread-safe field must fail the build right here.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<Router>();
+};
+
 impl Router {
     pub fn new() -> Self {
         Default::default()
     }
 
-    pub fn __internal_register_sender<SenderMarker, Msg>(
+    /// Creates a router with room for `senders` and `receivers` pathways,
+    /// avoiding rehashing while registering large topologies.
+    pub fn with_capacity(senders: usize, receivers: usize) -> Self {
+        let mut router = Self::default();
+        router.typed_senders = TypeIdMap::with_capacity_and_hasher(senders, Default::default());
+        router.typed_receivers = TypeIdMap::with_capacity_and_hasher(receivers, Default::default());
+        router
+    }
+
+    fn ensure_sender_vacant<SenderMarker: Any>(&self) -> Result<(), CommsError> {
+        if self
+            .typed_senders
+            .contains_key(&TypeId::of::<SenderMarker>())
+        {
+            return Err(CommsError::PathwayAlreadyRegistered(format!(
+                "Sender for marker type '{}' already registered.",
+                std::any::type_name::<SenderMarker>()
+            )));
+        }
+        Ok(())
+    }
+
+    fn ensure_receiver_vacant<ReceiverMarker: Any>(&self) -> Result<(), CommsError> {
+        if self
+            .typed_receivers
+            .contains_key(&TypeId::of::<ReceiverMarker>())
+        {
+            return Err(CommsError::PathwayAlreadyRegistered(format!(
+                "Receiver for marker type '{}' already registered.",
+                std::any::type_name::<ReceiverMarker>()
+            )));
+        }
+        Ok(())
+    }
+
+    fn link_pathway<SenderMarker: Any, ReceiverMarker: Any, Msg: Any, Carried: Any>(
         &mut self,
-        sender: mpsc::Sender<Msg>,
+        buffer: usize,
+        rebuild: fn(&mut Router, usize) -> Result<(), CommsError>,
+    ) {
+        // Rebuilding a pathway, e.g. in `reset_pathway`, keeps it in its link.
+        let link = self
+            .pathway_links
+            .get(&TypeId::of::<SenderMarker>())
+            .and_then(|existing| existing.link);
+        self.pathway_links.insert(
+            TypeId::of::<SenderMarker>(),
+            PathwayLink {
+                receiver_marker: TypeId::of::<ReceiverMarker>(),
+                rebuild,
+                msg_type: (TypeId::of::<Msg>(), std::any::type_name::<Msg>()),
+                carried_type: (TypeId::of::<Carried>(), std::any::type_name::<Carried>()),
+                buffer,
+                link,
+            },
+        );
+    }
+
+    pub fn __internal_register_link_direction<SenderMarker: Any>(
+        &mut self,
+        link_id: &'static str,
+        from: &'static str,
+        to: &'static str,
+    ) {
+        if let Some(pathway) = self.pathway_links.get_mut(&TypeId::of::<SenderMarker>()) {
+            pathway.link = Some(LinkDirection { link_id, from, to });
+        }
+    }
+
+    pub fn __internal_register_link_meta(
+        &mut self,
+        link_id: &'static str,
+        meta: &'static [(&'static str, &'static str)],
+    ) -> Result<(), CommsError> {
+        if self.link_meta.contains_key(link_id) {
+            return Err(CommsError::PathwayAlreadyRegistered(format!(
+                "Link '{}' already registered.",
+                link_id
+            )));
+        }
+
+        self.link_meta.insert(link_id, meta);
+        Ok(())
+    }
+
+    /// Returns the metadata attached to a link through its `meta` block,
+    /// or `None` if no link with this id was set up on this router.
+    pub fn link_meta(&self, link_id: &str) -> Option<&'static [(&'static str, &'static str)]> {
+        self.link_meta.get(link_id).copied()
+    }
+
+    /// Registers the sending half of a pathway built on a custom [`ChannelBackend`].
+    pub fn register_sender_with<B, SenderMarker, Msg>(
+        &mut self,
+        sender: B::Sender<Msg>,
     ) -> Result<(), CommsError>
     where
+        B: ChannelBackend,
         SenderMarker: Any + Send + Sync + 'static,
         Msg: ConcreteSenderTrait,
     {
-        let marker_type_id = TypeId::of::<SenderMarker>();
+        self.ensure_sender_vacant::<SenderMarker>()?;
 
-        if self.typed_senders.contains_key(&marker_type_id) {
-            return Err(CommsError::PathwayAlreadyRegistered(format!(
-                "Sender for marker type '{}' already registered.",
-                std::any::type_name::<SenderMarker>()
-            )));
-        }
+        self.typed_senders.insert(
+            TypeId::of::<SenderMarker>(),
+            SenderSlot::new::<Msg>(
+                std::any::type_name::<SenderMarker>(),
+                Box::new(ConcreteSender::<B, Msg> { sender }),
+            ),
+        );
 
-        self.typed_senders
-            .insert(marker_type_id, Box::new(ConcreteSender { sender }));
+        Ok(())
+    }
+
+    /// Registers the receiving half of a pathway built on a custom [`ChannelBackend`].
+    pub fn register_receiver_with<B, ReceiverMarker, Msg>(
+        &mut self,
+        receiver: B::Receiver<Msg>, // Receiver for the owning end of the pathway
+    ) -> Result<(), CommsError>
+    where
+        B: ChannelBackend,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteReceiverTrait,
+    {
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        let dyn_receiver_box: Box<dyn DynReceiver> =
+            Box::new(ConcreteReceiver::<B, Msg> { receiver });
+        self.typed_receivers.insert(
+            TypeId::of::<ReceiverMarker>(),
+            ReceiverSlot::new::<Msg>(std::any::type_name::<ReceiverMarker>(), dyn_receiver_box),
+        );
 
         Ok(())
     }
 
-    pub fn __internal_register_receiver<ReceiverMarker, Msg>(
+    /// Registers the sending half of a pathway like
+    /// [`register_sender_with`](Self::register_sender_with), unless `SenderMarker` is
+    /// already registered, in which case `sender` is dropped and the existing one kept.
+    ///
+    /// Meant for setup code that may run more than once, e.g. lazily from several
+    /// plugins. The existing registration only counts if it takes the same `Msg`:
+    /// one for another message type fails with `CommsError::TypeMismatch`, since
+    /// keeping it would leave the caller sending on a pathway it doesn't expect.
+    /// The backend isn't compared.
+    pub fn ensure_sender_with<B, SenderMarker, Msg>(
         &mut self,
-        receiver: mpsc::Receiver<Msg>, // Receiver for the owning end of the pathway
+        sender: B::Sender<Msg>,
+    ) -> Result<(), CommsError>
+    where
+        B: ChannelBackend,
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        match self.typed_senders.get(&TypeId::of::<SenderMarker>()) {
+            None => self.register_sender_with::<B, SenderMarker, Msg>(sender),
+            Some(slot) if slot.sender.accepts_message_type_id() == TypeId::of::<Msg>() => Ok(()),
+            Some(slot) => Err(CommsError::TypeMismatch(
+                format!(
+                    "Sender for marker type '{}' is already registered for '{}', not '{}'.",
+                    std::any::type_name::<SenderMarker>(),
+                    slot.sender.message_type_name(),
+                    std::any::type_name::<Msg>()
+                ),
+                Some(TypeId::of::<SenderMarker>()),
+            )),
+        }
+    }
+
+    /// Registers the receiving half of a pathway like
+    /// [`register_receiver_with`](Self::register_receiver_with), unless `ReceiverMarker`
+    /// is already registered, in which case `receiver` is dropped and the existing one
+    /// kept, whether or not it was taken since.
+    ///
+    /// Fails with `CommsError::TypeMismatch` if the existing registration is for another
+    /// message type, same as [`ensure_sender_with`](Self::ensure_sender_with). Since
+    /// the half passed in is dropped when one is already there, ensuring both halves of
+    /// one channel only keeps them connected if they were always registered together.
+    pub fn ensure_receiver_with<B, ReceiverMarker, Msg>(
+        &mut self,
+        receiver: B::Receiver<Msg>,
     ) -> Result<(), CommsError>
     where
+        B: ChannelBackend,
         ReceiverMarker: Any + Send + Sync + 'static,
         Msg: ConcreteReceiverTrait,
     {
-        let marker_type_id = TypeId::of::<ReceiverMarker>();
-        if self.typed_receivers.contains_key(&marker_type_id) {
-            return Err(CommsError::PathwayAlreadyRegistered(format!(
-                "Receiver for marker type '{}' already registered.",
-                std::any::type_name::<ReceiverMarker>()
-            )));
+        match self.typed_receivers.get(&TypeId::of::<ReceiverMarker>()) {
+            None => self.register_receiver_with::<B, ReceiverMarker, Msg>(receiver),
+            Some(slot) if slot.msg_type_id == TypeId::of::<Msg>() => Ok(()),
+            Some(_) => Err(CommsError::TypeMismatch(
+                format!(
+                    "Receiver for marker type '{}' is already registered for another type than '{}'.",
+                    std::any::type_name::<ReceiverMarker>(),
+                    std::any::type_name::<Msg>()
+                ),
+                Some(TypeId::of::<ReceiverMarker>()),
+            )),
         }
+    }
 
-        let dyn_receiver_box: Box<dyn DynReceiver> = Box::new(ConcreteReceiver { receiver });
-        self.typed_receivers.insert(
-            marker_type_id,
-            (TypeId::of::<Msg>(), Mutex::new(Some(dyn_receiver_box))),
+    /// Registers both halves of a pathway built on a custom [`ChannelBackend`],
+    /// creating the channel between them.
+    pub fn register_pathway_with<B, SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        B: ChannelBackend,
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        let (sender, receiver) = B::channel::<Msg>(buffer);
+        self.register_sender_with::<B, SenderMarker, Msg>(sender)?;
+        self.register_receiver_with::<B, ReceiverMarker, Msg>(receiver)?;
+        self.link_pathway::<SenderMarker, ReceiverMarker, Msg, Msg>(
+            buffer,
+            Self::register_pathway_with::<B, SenderMarker, ReceiverMarker, Msg>,
         );
 
         Ok(())
     }
 
+    /// Tears down a pathway and recreates it with a fresh channel of `buffer` messages,
+    /// under the same markers.
+    ///
+    /// This is how a pathway recovers after its consumer task died and dropped the
+    /// receiver: once reset, the restarted consumer can `take_receiver` again and
+    /// producers resume sending.
+    ///
+    /// Anything still buffered in the old channel is discarded.
+    /// Sender clones held outside the router (e.g. by `pipe` tasks) keep
+    /// pointing at the old channel.
+    ///
+    /// Only pathways registered as a whole, as `define_crosslink!` does,
+    /// can be reset, since both markers must be known to belong together.
+    pub fn reset_pathway<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let link = self.pathway_link::<SenderMarker, ReceiverMarker>()?;
+        self.typed_sender::<SenderMarker, Msg>()?;
+
+        self.typed_senders.remove(&TypeId::of::<SenderMarker>());
+        self.typed_receivers.remove(&TypeId::of::<ReceiverMarker>());
+
+        (link.rebuild)(self, buffer)
+    }
+
+    /// Looks up the pathway joining `SenderMarker` to `ReceiverMarker`.
+    fn pathway_link<SenderMarker, ReceiverMarker>(&self) -> Result<PathwayLink, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+    {
+        self.pathway_links
+            .get(&TypeId::of::<SenderMarker>())
+            .copied()
+            .filter(|link| link.receiver_marker == TypeId::of::<ReceiverMarker>())
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!(
+                        "Markers '{}' and '{}' are not two halves of the same pathway.",
+                        std::any::type_name::<SenderMarker>(),
+                        std::any::type_name::<ReceiverMarker>()
+                    ),
+                    Some(TypeId::of::<SenderMarker>()),
+                )
+            })
+    }
+
+    /// Looks up the sender registered for `SenderMarker`, checking it accepts `Msg`.
+    fn typed_sender<SenderMarker, Msg>(&self) -> Result<&SenderSlot, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        dispatch::checked_sender::<SenderMarker, Msg>(
+            self.typed_senders.get(&TypeId::of::<SenderMarker>()),
+        )
+    }
+
     /// Sends a message on a specified link.
+    #[must_use = "a failed send drops the message, check the result"]
     pub async fn send<SenderMarker, Msg>(&self, message: Msg) -> Result<(), CommsError>
     where
         SenderMarker: Any + Send + Sync + 'static,
         Msg: ConcreteSenderTrait,
     {
-        let marker_type_id = TypeId::of::<SenderMarker>();
-        let msg_type_id_to_send = TypeId::of::<Msg>();
+        dispatch::send::<SenderMarker, Msg>(
+            self.typed_sender::<SenderMarker, Msg>(),
+            &self.hooks,
+            message,
+        )
+        .await
+    }
 
-        match self.typed_senders.get(&marker_type_id) {
-            Some(dyn_sender) => {
-                if dyn_sender.accepts_message_type_id() != msg_type_id_to_send {
-                    return Err(CommsError::InternalInconsistency(format!(
-                        "Metadata mismatch for link '{}', pathway '{}'.
-                        Expected type '{}' for sending, but sender is configured for '{}'.",
-                        std::any::type_name::<SenderMarker>(),
-                        dyn_sender.message_type_name(),
-                        std::any::type_name::<Msg>(),
-                        dyn_sender.message_type_name()
-                    )));
-                }
-                dyn_sender.send_erased(Box::new(message)).await
+    /// Sends a message on the one pathway accepting `Msg`, for callers that only know
+    /// the message type, such as plugins.
+    ///
+    /// Finding the pathway scans every registered sender, so this is O(n) in the number
+    /// of pathways; prefer `send` with a marker anywhere the marker is known.
+    /// Fails with `CommsError::PathwayNotFound` if no pathway accepts `Msg`, and with
+    /// `CommsError::AmbiguousPathway` if several do, e.g. both directions of a
+    /// symmetric link, rather than picking one arbitrarily.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send_by_type<Msg>(&self, message: Msg) -> Result<(), CommsError>
+    where
+        Msg: ConcreteSenderTrait,
+    {
+        let (marker, slot) = self.sender_for_type::<Msg>()?;
+        let pathway = Pathway {
+            marker,
+            name: slot.marker_name,
+        };
+        dispatch::send_on(Ok(slot), &self.hooks, pathway, message).await
+    }
+
+    /// Looks up the only sender accepting `Msg`, along with its marker.
+    fn sender_for_type<Msg>(&self) -> Result<(TypeId, &SenderSlot), CommsError>
+    where
+        Msg: ConcreteSenderTrait,
+    {
+        let mut matches = self
+            .typed_senders
+            .iter()
+            .filter(|(_, slot)| slot.sender.accepts_message_type_id() == TypeId::of::<Msg>());
+
+        match (matches.next(), matches.next()) {
+            (Some((marker, slot)), None) => Ok((*marker, slot)),
+            (None, _) => Err(CommsError::PathwayNotFound(
+                format!(
+                    "No pathway accepts message type '{}'.",
+                    std::any::type_name::<Msg>()
+                ),
+                None,
+            )),
+            (Some(first), Some(second)) => {
+                let mut markers: Vec<&str> = [first, second]
+                    .into_iter()
+                    .chain(matches)
+                    .map(|(_, slot)| slot.marker_name)
+                    .collect();
+                markers.sort_unstable();
+                Err(CommsError::AmbiguousPathway(format!(
+                    "Message type '{}' is accepted by several pathways: {}. Send with a marker instead.",
+                    std::any::type_name::<Msg>(),
+                    markers.join(", ")
+                )))
             }
-            None => Err(CommsError::PathwayNotFound(format!(
-                "No pathway configured for marker type '{}' that accepts message type '{}'.
-                Ensure this message type is defined for sending on this link",
-                std::any::type_name::<SenderMarker>(),
-                std::any::type_name::<Msg>()
-            ))),
         }
     }
 
-    pub fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    /// Sends a type-erased message on the pathway whose sender marker has the `TypeId`
+    /// `sender_marker`, e.g. one built by deserializing in plugin code.
+    ///
+    /// This is the dynamic counterpart of [`send`](Self::send): nothing is checked at
+    /// compile time, and a message of the wrong type only shows up at runtime, as a
+    /// `CommsError::TypeMismatch`. The message must be the type `send` takes on that
+    /// pathway, e.g. `Msg` rather than `Envelope<Msg>` on an instrumented one.
+    /// Dead-letter channels don't apply. Prefer `send` wherever the types are known.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send_erased(
+        &self,
+        sender_marker: TypeId,
+        message: Box<dyn Any + Send>,
+    ) -> Result<(), CommsError> {
+        let slot = self.typed_senders.get(&sender_marker);
+        let pathway = Pathway {
+            marker: sender_marker,
+            name: slot.map_or("<unregistered>", |slot| slot.marker_name),
+        };
+        let slot = slot.ok_or_else(|| {
+            CommsError::PathwayNotFound(
+                format!("No pathway configured for marker {:?}.", sender_marker),
+                Some(sender_marker),
+            )
+        });
+        dispatch::send_boxed(slot, &self.hooks, pathway, message).await
+    }
+
+    /// Sends a message only if the pathway has room for it right now.
+    ///
+    /// This is the fast path for producers that don't want to wait on backpressure:
+    /// it never allocates a future, and fails with `CommsError::ChannelFull`
+    /// instead of waiting when the buffer is full.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub fn try_send<SenderMarker, Msg>(&self, message: Msg) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        dispatch::try_send::<SenderMarker, Msg>(
+            self.typed_sender::<SenderMarker, Msg>(),
+            &self.hooks,
+            message,
+        )
+    }
+
+    /// Tries to send each of `messages` without waiting, and returns the ones that didn't
+    /// fit because the pathway was full, in their original order.
+    ///
+    /// Meant for lossy bursts, e.g. telemetry, where the caller decides whether the
+    /// rejects are re-queued or discarded. Every message is tried, so one that finds
+    /// room after an earlier reject is still sent. Only a failure other than a full
+    /// pathway, such as a closed one, is an error: the batch stops there and the
+    /// messages not yet tried are dropped with it.
+    #[must_use = "messages handed back in the result weren't sent"]
+    pub fn try_send_all<SenderMarker, Msg>(
+        &self,
+        messages: Vec<Msg>,
+    ) -> Result<Vec<Msg>, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        dispatch::try_send_all::<SenderMarker, Msg>(
+            self.typed_sender::<SenderMarker, Msg>(),
+            &self.hooks,
+            messages,
+        )
+    }
+
+    /// Whether a sender is registered for `SenderMarker` and takes `Msg`s, i.e. whether
+    /// `send::<SenderMarker, Msg>` can find its pathway.
+    ///
+    /// A cheap pre-flight check that allocates nothing, unlike a failed send's error.
+    /// It doesn't say whether a send would succeed: the pathway may still be full,
+    /// paused or closed.
+    pub fn accepts<SenderMarker, Msg>(&self) -> bool
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: 'static,
+    {
+        self.typed_senders
+            .get(&TypeId::of::<SenderMarker>())
+            .is_some_and(|slot| slot.sender.accepts_message_type_id() == TypeId::of::<Msg>())
+    }
+
+    /// Takes the receiving half of a pathway built on a custom [`ChannelBackend`].
+    #[track_caller]
+    pub fn take_receiver_with<B, ReceiverMarker, Msg>(&self) -> Result<B::Receiver<Msg>, CommsError>
     where
+        B: ChannelBackend,
         ReceiverMarker: Any + Send + Sync + 'static,
         Msg: Send + 'static + Debug + Sync,
     {
-        let marker_type_id = TypeId::of::<ReceiverMarker>();
-        let expected_msg_type_id = TypeId::of::<Msg>();
+        dispatch::take_receiver::<B, ReceiverMarker, Msg>(
+            self.typed_receivers.get(&TypeId::of::<ReceiverMarker>()),
+        )
+    }
 
-        match self.typed_receivers.get(&marker_type_id) {
-            Some((reg_type_id, receiver_lock)) => {
-                if *reg_type_id != expected_msg_type_id {
-                    return Err(CommsError::TypeMismatch(format!(
-                        "Expected type '{}' for receiving.",
-                        std::any::type_name::<Msg>(),
-                    )));
-                }
+    /// Registers both halves of an instrumented pathway built on a custom [`ChannelBackend`].
+    /// The channel carries stamped messages, so the receiving side has to unwrap them.
+    pub fn register_instrumented_with<B, SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        B: ChannelBackend,
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.register_envelope_pathway::<B, SenderMarker, ReceiverMarker, Msg>(
+            buffer,
+            Self::register_instrumented_with::<B, SenderMarker, ReceiverMarker, Msg>,
+        )?;
 
-                let mut recv_guard = receiver_lock.lock().map_err(|e| {
-                    CommsError::InternalInconsistency(format!(
-                        "Failed to lock receiver for link '{}' and handle '{}'. Error: {}",
-                        std::any::type_name::<ReceiverMarker>(),
-                        std::any::type_name::<Msg>(),
-                        e
-                    ))
-                })?;
-
-                if let Some(dyn_receiver) = recv_guard.take() {
-                    match dyn_receiver.into_any().downcast::<ConcreteReceiver<Msg>>() {
-                        Ok(concrete_box_recv) => Ok(concrete_box_recv.receiver),
-                        Err(_) => Err(CommsError::InternalInconsistency(format!(
-                            "Critical: Downcast to ConcreteReceiver<{}> failed for key '{}' after TypeId match.",
-                            std::any::type_name::<ReceiverMarker>(),
-                            std::any::type_name::<Msg>()
-                        ))),
-                    }
-                } else {
-                    Err(CommsError::InternalInconsistency(format!(
-                        "Failed to take receiver for link '{}' and handle '{}'.",
+        let stats = Arc::new(LatencyStats::new(std::any::type_name::<SenderMarker>()));
+        self.latency_stats
+            .insert(TypeId::of::<SenderMarker>(), Arc::clone(&stats));
+        self.latency_stats
+            .insert(TypeId::of::<ReceiverMarker>(), stats);
+        Ok(())
+    }
+
+    /// Registers both halves of a timestamped pathway built on a custom
+    /// [`ChannelBackend`], whose messages carry the wall-clock time they were sent.
+    pub fn register_timestamped_with<B, SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        B: ChannelBackend,
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.register_envelope_pathway::<B, SenderMarker, ReceiverMarker, Msg>(
+            buffer,
+            Self::register_timestamped_with::<B, SenderMarker, ReceiverMarker, Msg>,
+        )
+    }
+
+    /// Registers a pathway whose channel carries `Envelope<Msg>`s, stamped as they're
+    /// sent, the common ground of instrumented and timestamped pathways.
+    fn register_envelope_pathway<B, SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+        rebuild: fn(&mut Router, usize) -> Result<(), CommsError>,
+    ) -> Result<(), CommsError>
+    where
+        B: ChannelBackend,
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        let (sender, receiver) = B::channel::<Envelope<Msg>>(buffer);
+        self.typed_senders.insert(
+            TypeId::of::<SenderMarker>(),
+            SenderSlot::new::<Msg>(
+                std::any::type_name::<SenderMarker>(),
+                Box::new(EnvelopeSender::<B, Msg> { sender }),
+            ),
+        );
+        self.register_receiver_with::<B, ReceiverMarker, Envelope<Msg>>(receiver)?;
+        self.link_pathway::<SenderMarker, ReceiverMarker, Msg, Envelope<Msg>>(buffer, rebuild);
+        Ok(())
+    }
+
+    /// Puts a previously taken receiver back into its pathway's slot,
+    /// so it can be taken again.
+    ///
+    /// Fails if the slot isn't empty or the message type doesn't match the pathway.
+    pub fn return_receiver_with<B, ReceiverMarker, Msg>(
+        &self,
+        receiver: B::Receiver<Msg>,
+    ) -> Result<(), CommsError>
+    where
+        B: ChannelBackend,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteReceiverTrait,
+    {
+        let slot = self
+            .typed_receivers
+            .get(&TypeId::of::<ReceiverMarker>())
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!(
+                        "No receiver for link '{}' and handle '{}' found.",
                         std::any::type_name::<ReceiverMarker>(),
                         std::any::type_name::<Msg>()
-                    )))
+                    ),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                )
+            })?;
+
+        if slot.msg_type_id != TypeId::of::<Msg>() {
+            return Err(CommsError::TypeMismatch(
+                format!(
+                    "Expected type '{}' for receiving.",
+                    std::any::type_name::<Msg>(),
+                ),
+                Some(TypeId::of::<ReceiverMarker>()),
+            ));
+        }
+
+        let mut recv_guard = slot.lock();
+
+        if recv_guard.is_some() {
+            return Err(CommsError::PathwayAlreadyRegistered(format!(
+                "Receiver for marker type '{}' was never taken.",
+                std::any::type_name::<ReceiverMarker>()
+            )));
+        }
+
+        *recv_guard = Some(Box::new(ConcreteReceiver::<B, Msg> { receiver }));
+        slot.mark_returned();
+        #[cfg(feature = "tokio")]
+        self.receiver_available.notify_waiters();
+        Ok(())
+    }
+
+    /// Checks that no un-taken receiver still has buffered messages, returning
+    /// the receiver marker names of the pathways that do.
+    ///
+    /// Meant for test teardown, to catch messages that were sent but never processed.
+    /// Only receivers still owned by the router are checked, and nothing is consumed,
+    /// so the router remains fully usable afterwards.
+    #[cfg(feature = "testing")]
+    pub fn assert_empty(&self) -> Result<(), Vec<&'static str>> {
+        let mut non_empty: Vec<&'static str> = self
+            .typed_receivers
+            .values()
+            .filter(|slot| slot.lock().as_ref().is_some_and(|r| r.len() > 0))
+            .map(|slot| slot.marker_name)
+            .collect();
+
+        if non_empty.is_empty() {
+            Ok(())
+        } else {
+            non_empty.sort_unstable();
+            Err(non_empty)
+        }
+    }
+
+    /// Returns how many messages are buffered in a pathway whose receiver is still held
+    /// by the router.
+    ///
+    /// Fails if the marker isn't registered, or if its receiver was already taken,
+    /// since the router can't see into it anymore.
+    pub fn receiver_len<ReceiverMarker>(&self) -> Result<usize, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+    {
+        let slot = self
+            .typed_receivers
+            .get(&TypeId::of::<ReceiverMarker>())
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!(
+                        "No receiver for link '{}' found.",
+                        std::any::type_name::<ReceiverMarker>(),
+                    ),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                )
+            })?;
+
+        slot.lock()
+            .as_ref()
+            .map(|receiver| receiver.len())
+            .ok_or_else(|| {
+                CommsError::RecvFailed(format!(
+                    "Receiver for link '{}' was already taken out of the router.",
+                    std::any::type_name::<ReceiverMarker>(),
+                ))
+            })
+    }
+
+    /// Returns the send-to-receive latency recorded so far on an instrumented pathway,
+    /// or `None` if the pathway wasn't registered as instrumented.
+    pub fn pathway_latency<SenderMarker>(&self) -> Option<LatencySnapshot>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+    {
+        self.latency_stats
+            .get(&TypeId::of::<SenderMarker>())
+            .map(|stats| stats.snapshot())
+    }
+
+    /// Ends the configuration phase, turning the router into an immutable
+    /// [`FrozenRouter`] optimized for dispatch.
+    ///
+    /// Nothing can be registered, reset or inspected afterwards, so anything beyond
+    /// sending and taking plain receivers must be done before freezing.
+    pub fn freeze(mut self) -> FrozenRouter {
+        FrozenRouter::new(
+            std::mem::take(&mut self.typed_senders),
+            std::mem::take(&mut self.typed_receivers),
+            std::mem::take(&mut self.hooks),
+        )
+    }
+
+    /// Builds a router with a clone of every sender of this one, and none of its
+    /// receivers, e.g. to intercept a few pathways in a test.
+    ///
+    /// Both routers then push into the same channels: a message sent through the fork
+    /// reaches whoever consumes the pathway here. Pausing a pathway, and its `stats`,
+    /// cover the sends of both. The fork knows the pathways registered as a whole, so
+    /// `reset_pathway` on it swaps one for a fresh channel of its own, whose receiver it
+    /// hands out, while this router keeps the original. The fork starts without a tap,
+    /// dead-letter channels or sharded pathways, whose receivers live with their
+    /// senders, and `validate` reports the receivers it doesn't have.
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug, PartialEq)]
+    /// # pub struct Order(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "OrderLink",
+    /// #     Shop { sends: Order, receives: () },
+    /// #     Billing { sends: (), receives: Order },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use order_link::{BillingRecv, ShopSend};
+    /// # fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # order_link::setup_order_link(&mut router, None);
+    /// # let mut billing = router.take_receiver::<BillingRecv, Order>()?;
+    /// let mut fork = router.fork();
+    /// fork.reset_pathway::<ShopSend, BillingRecv, Order>(16)?;
+    /// let mut intercepted = fork.take_receiver::<BillingRecv, Order>()?;
+    /// # fork.try_send::<ShopSend, _>(Order(1))?;
+    /// # assert_eq!(intercepted.try_recv(), Ok(Order(1)));
+    /// # assert!(billing.try_recv().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fork(&self) -> Router {
+        let mut fork = Router::default();
+        fork.typed_senders = self
+            .typed_senders
+            .iter()
+            .map(|(marker, slot)| (*marker, slot.share()))
+            .collect();
+        fork.latency_stats = self.latency_stats.clone();
+        fork.link_meta = self.link_meta.clone();
+        fork.pathway_links = self.pathway_links.clone();
+        #[cfg(feature = "tokio")]
+        {
+            fork.codecs = self.codecs.clone();
+            fork.overflow_depths = self.overflow_depths.clone();
+        }
+        fork
+    }
+
+    /// Ends setup by moving the router behind an `Arc`, ready to be cloned into tasks.
+    ///
+    /// Shorthand for `Arc::new(router)`, see [Sharing the Router](crate#sharing-the-router).
+    pub fn into_shared(self) -> Arc<Router> {
+        Arc::new(self)
+    }
+
+    /// Cross-checks the router's wiring, returning every problem found rather than
+    /// stopping at the first, e.g. right after setting up a large topology.
+    ///
+    /// For each pathway registered in one go (by `define_crosslink!` setup functions
+    /// or `register_pathway_with`), both halves must still be registered, the sender
+    /// must accept the pathway's message type, and the receiver slot must hold the
+    /// type its channel carries. Halves registered separately with
+    /// `register_sender_with`/`register_receiver_with` have no counterpart to be
+    /// checked against. Whether a receiver was taken doesn't matter.
+    pub fn validate(&self) -> Result<(), Vec<CommsError>> {
+        let mut problems = Vec::new();
+
+        for (sender_marker, link) in &self.pathway_links {
+            let sender = self.typed_senders.get(sender_marker);
+            let receiver = self.typed_receivers.get(&link.receiver_marker);
+            let pathway = match (sender, receiver) {
+                (Some(sender), Some(receiver)) => {
+                    format!("'{}' -> '{}'", sender.marker_name, receiver.marker_name)
                 }
+                (Some(sender), None) => format!("'{}' -> ?", sender.marker_name),
+                (None, Some(receiver)) => format!("? -> '{}'", receiver.marker_name),
+                (None, None) => String::from("?"),
+            };
+
+            match sender {
+                None => problems.push(CommsError::PathwayNotFound(
+                    format!("Pathway {} lost its sender.", pathway),
+                    Some(*sender_marker),
+                )),
+                Some(slot) if slot.sender.accepts_message_type_id() != link.msg_type.0 => problems
+                    .push(CommsError::TypeMismatch(
+                        format!(
+                            "Pathway {} sends '{}', but its sender accepts '{}'.",
+                            pathway,
+                            link.msg_type.1,
+                            slot.sender.message_type_name()
+                        ),
+                        Some(*sender_marker),
+                    )),
+                Some(_) => {}
+            }
+
+            match receiver {
+                None => problems.push(CommsError::PathwayNotFound(
+                    format!("Pathway {} lost its receiver.", pathway),
+                    Some(link.receiver_marker),
+                )),
+                Some(slot) if slot.msg_type_id != link.carried_type.0 => {
+                    problems.push(CommsError::TypeMismatch(
+                        format!(
+                            "Pathway {} carries '{}', but its receiver slot holds another type.",
+                            pathway, link.carried_type.1,
+                        ),
+                        Some(link.receiver_marker),
+                    ))
+                }
+                Some(_) => {}
             }
-            None => Err(CommsError::PathwayNotFound(format!(
-                "No receiver for link '{}' and handle '{}' found.",
-                std::any::type_name::<ReceiverMarker>(),
-                std::any::type_name::<Msg>()
-            ))),
         }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            // Map order is arbitrary, keep reports stable across runs.
+            problems.sort_by_cached_key(|e| e.to_string());
+            Err(problems)
+        }
+    }
+
+    /// Snapshots the router's wiring: every link set up on it with its endpoints and
+    /// metadata, and every pathway with its markers, message type and buffer size.
+    ///
+    /// Links are known from `define_crosslink!` setup functions. Pathways registered any
+    /// other way are listed on their own, with whatever the router knows about them.
+    pub fn topology(&self) -> Topology {
+        let mut links: HashMap<&'static str, LinkTopology> = self
+            .link_meta
+            .iter()
+            .map(|(&link_id, &meta)| {
+                let link = LinkTopology {
+                    link_id,
+                    endpoints: Vec::new(),
+                    meta,
+                    pathways: Vec::new(),
+                };
+                (link_id, link)
+            })
+            .collect();
+        let mut unlinked = Vec::new();
+
+        for (sender_marker, slot) in &self.typed_senders {
+            let link = self.pathway_links.get(sender_marker);
+            let direction = link.and_then(|link| link.link);
+            let pathway = PathwayTopology {
+                from: direction.map(|direction| direction.from),
+                to: direction.map(|direction| direction.to),
+                sender_marker: Some(slot.marker_name),
+                receiver_marker: link
+                    .and_then(|link| self.typed_receivers.get(&link.receiver_marker))
+                    .map(|receiver| receiver.marker_name),
+                message_type: slot.sender.message_type_name(),
+                buffer_size: link.map(|link| link.buffer),
+            };
+
+            match direction {
+                Some(direction) => {
+                    let link = links
+                        .entry(direction.link_id)
+                        .or_insert_with(|| LinkTopology {
+                            link_id: direction.link_id,
+                            endpoints: Vec::new(),
+                            meta: &[],
+                            pathways: Vec::new(),
+                        });
+                    link.endpoints.extend([direction.from, direction.to]);
+                    link.pathways.push(pathway);
+                }
+                None => unlinked.push(pathway),
+            }
+        }
+
+        let linked_receivers: Vec<TypeId> = self
+            .pathway_links
+            .values()
+            .map(|link| link.receiver_marker)
+            .collect();
+        for (receiver_marker, slot) in &self.typed_receivers {
+            if !linked_receivers.contains(receiver_marker) {
+                unlinked.push(PathwayTopology {
+                    from: None,
+                    to: None,
+                    sender_marker: None,
+                    receiver_marker: Some(slot.marker_name),
+                    message_type: slot.msg_type_name,
+                    buffer_size: None,
+                });
+            }
+        }
+
+        // Map order is arbitrary, keep snapshots stable across runs.
+        let by_markers =
+            |pathway: &PathwayTopology| (pathway.sender_marker, pathway.receiver_marker);
+        let mut links: Vec<LinkTopology> = links.into_values().collect();
+        for link in &mut links {
+            link.endpoints.sort_unstable();
+            link.endpoints.dedup();
+            link.pathways.sort_by_key(by_markers);
+        }
+        links.sort_by_key(|link| link.link_id);
+        unlinked.sort_by_key(by_markers);
+
+        Topology {
+            links,
+            pathways: unlinked,
+        }
+    }
+
+    /// Returns the send counters of a pathway, or `None` if no sender is registered
+    /// for `SenderMarker`.
+    ///
+    /// Counting is always on and dependency-free, covering sends through the router
+    /// as well as relays feeding the pathway. See [`PathwayStats`] for accuracy.
+    pub fn stats<SenderMarker>(&self) -> Option<PathwayStats>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+    {
+        self.typed_senders
+            .get(&TypeId::of::<SenderMarker>())
+            .map(|slot| slot.counters.snapshot())
+    }
+
+    /// Whether the receiver of `ReceiverMarker` was taken out of the router, or `None`
+    /// if no receiver is registered for it.
+    pub fn receiver_is_taken<ReceiverMarker>(&self) -> Option<bool>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+    {
+        self.typed_receivers
+            .get(&TypeId::of::<ReceiverMarker>())
+            .map(|slot| slot.lock().is_none())
+    }
+
+    /// The marker names of the receivers still in the router, sorted, shards of a
+    /// sharded pathway included.
+    ///
+    /// Once setup is done and consumers are running, any receiver left is likely a
+    /// wiring bug: nothing will ever consume its pathway, whose channel fills up and
+    /// then holds producers back. Watch pathways keep their receiver for good, and are
+    /// left out.
+    pub fn untaken_receivers(&self) -> Vec<&'static str> {
+        let slots = self.typed_receivers.values();
+        // Both markers of a sharded pathway map to its shards, only count them under
+        // the receiver's.
+        #[cfg(feature = "tokio")]
+        let slots = slots.chain(
+            self.shards
+                .iter()
+                .filter(|(marker, _)| !self.typed_senders.contains_key(*marker))
+                .flat_map(|(_, shards)| &shards.receivers),
+        );
+
+        let mut untaken: Vec<&'static str> = slots
+            .filter(|slot| {
+                slot.lock()
+                    .as_ref()
+                    .is_some_and(|receiver| !receiver.is_lent())
+            })
+            .map(|slot| slot.marker_name)
+            .collect();
+        untaken.sort_unstable();
+        untaken
+    }
+
+    /// Reports what the router knows about the receiver of `ReceiverMarker` and the
+    /// senders that could feed it, to find out why a `recv` on it never returns.
+    ///
+    /// Senders are the receiver's own pathway counterpart, if it was registered as a
+    /// whole, plus every other sender taking the type its channel carries, matched by
+    /// `TypeId`. The diagnosis only covers the router's view: senders cloned out of it,
+    /// e.g. by `pipe` tasks, don't show up.
+    ///
+    /// ```
+    /// # use crosslink::{Router, define_crosslink};
+    /// # #[derive(Debug)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "JobLink",
+    /// #     Dispatcher { sends: Job, receives: () },
+    /// #     Worker { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use job_link::WorkerRecv;
+    /// # fn main() {
+    /// # let mut router = Router::new();
+    /// # job_link::setup_job_link(&mut router, None);
+    /// # let _worker = router.take_receiver::<WorkerRecv, Job>().unwrap();
+    /// eprintln!("{}", router.diagnose::<WorkerRecv>());
+    /// # let diagnosis = router.diagnose::<WorkerRecv>();
+    /// # assert!(diagnosis.taken && diagnosis.senders.iter().all(|s| s.paired));
+    /// # }
+    /// ```
+    pub fn diagnose<ReceiverMarker>(&self) -> Diagnosis
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+    {
+        let receiver_marker = TypeId::of::<ReceiverMarker>();
+        let slot = self.typed_receivers.get(&receiver_marker);
+
+        let mut senders: Vec<SenderDiagnosis> = self
+            .typed_senders
+            .iter()
+            .filter_map(|(sender_marker, sender)| {
+                let paired = self
+                    .pathway_links
+                    .get(sender_marker)
+                    .is_some_and(|link| link.receiver_marker == receiver_marker);
+                let same_type = slot.is_some_and(|slot| {
+                    sender.sender.accepts_message_type_id() == slot.msg_type_id
+                });
+                (paired || same_type).then(|| SenderDiagnosis {
+                    marker: sender.marker_name,
+                    paired,
+                    closed: sender.sender.is_closed(),
+                })
+            })
+            .collect();
+        senders.sort_by_key(|sender| sender.marker);
+
+        Diagnosis {
+            receiver_marker: std::any::type_name::<ReceiverMarker>(),
+            registered: slot.is_some(),
+            taken: slot.is_some_and(|slot| slot.lock().is_none()),
+            message_type: slot.map(|slot| slot.msg_type_name),
+            senders,
+        }
+    }
+
+    /// Returns the renderer for messages sent on the pathway whose sender marker has
+    /// the `TypeId` `sender_marker`, or `None` if no sender is registered for it.
+    ///
+    /// The renderer is captured when the pathway is registered, so debug tooling holding
+    /// messages as `dyn Any`, e.g. from a dynamic path, can print them with their `Debug`
+    /// impl without knowing their type.
+    ///
+    /// ```
+    /// # use std::any::{Any, TypeId};
+    /// # use crosslink::{Router, define_crosslink};
+    /// # #[derive(Debug)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "FooLink",
+    /// #     Foo { sends: Job, receives: () },
+    /// #     Bar { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use foo_link::FooSend;
+    /// # fn main() {
+    /// # let mut router = Router::new();
+    /// # foo_link::setup_foo_link(&mut router, None);
+    /// let render = router.debug_renderer(TypeId::of::<FooSend>()).unwrap();
+    /// let message: Box<dyn Any + Send> = Box::new(Job(1));
+    /// assert_eq!(render(&*message).as_deref(), Some("Job(1)"));
+    /// # }
+    /// ```
+    pub fn debug_renderer(&self, sender_marker: TypeId) -> Option<DebugRenderer> {
+        self.typed_senders
+            .get(&sender_marker)
+            .map(|slot| slot.render)
+    }
+
+    /// Pauses the pathway fed by `SenderMarker`, e.g. while its consumer is being
+    /// restarted.
+    ///
+    /// While paused, `send` waits for [`resume`](Self::resume) instead of pushing,
+    /// and `try_send` fails with `CommsError::ChannelFull`. Relays feeding the pathway
+    /// wait too. Sends already pushed stay in the buffer and can still be received.
+    ///
+    /// Waiting sends hold on to their messages outside the buffer, so they don't count
+    /// against `buffer_size` until resumed. They then compete for capacity like any
+    /// other send: a long pause with many producers can pile up far more messages than
+    /// the buffer holds, all released at once. Waiting sends are woken in the order
+    /// they started waiting, so sends awaited one after another from a single task,
+    /// or queued on a single-threaded runtime, are delivered in order.
+    pub fn pause<SenderMarker>(&self) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+    {
+        self.gate::<SenderMarker>()?.pause();
+        Ok(())
+    }
+
+    /// Resumes a pathway paused with [`pause`](Self::pause), releasing every waiting
+    /// send. Resuming a pathway that isn't paused does nothing.
+    pub fn resume<SenderMarker>(&self) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+    {
+        self.gate::<SenderMarker>()?.resume();
+        Ok(())
+    }
+
+    fn gate<SenderMarker>(&self) -> Result<&Gate, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+    {
+        self.typed_senders
+            .get(&TypeId::of::<SenderMarker>())
+            .map(|slot| slot.gate.as_ref())
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!(
+                        "No sender for link '{}' found.",
+                        std::any::type_name::<SenderMarker>(),
+                    ),
+                    Some(TypeId::of::<SenderMarker>()),
+                )
+            })
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Router {
+    /// Builds a router with every `define_crosslink!` link in the binary set up on it,
+    /// each with its default buffer size.
+    ///
+    /// Links register themselves when the `inventory` feature is on, wherever they are
+    /// defined, e.g. in separately compiled plugin crates. The order they are set up in
+    /// is unspecified, which is harmless since links never share a pathway: markers are
+    /// types of the module each invocation generates, so two plugins can't clash on a
+    /// marker, even when their handles have the same names.
+    ///
+    /// # Panics
+    /// Panics if two links share a `link_id`, naming the one set up second, since
+    /// that's the only thing plugins can clash on.
+    #[cfg(feature = "inventory")]
+    pub fn collect_all() -> Router {
+        let mut router = Router::new();
+        for link in crate::registry::links() {
+            link.setup(&mut router);
+        }
+        router
+    }
+
+    pub fn __internal_register_sender<SenderMarker, Msg>(
+        &mut self,
+        sender: mpsc::Sender<Msg>,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.register_sender_with::<TokioBackend, SenderMarker, Msg>(sender)
+    }
+
+    pub fn __internal_register_receiver<ReceiverMarker, Msg>(
+        &mut self,
+        receiver: mpsc::Receiver<Msg>, // Receiver for the owning end of the pathway
+    ) -> Result<(), CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteReceiverTrait,
+    {
+        self.register_receiver_with::<TokioBackend, ReceiverMarker, Msg>(receiver)
+    }
+
+    /// Registers a tokio sender unless `SenderMarker` already has one, see
+    /// [`ensure_sender_with`](Self::ensure_sender_with).
+    pub fn ensure_sender<SenderMarker, Msg>(
+        &mut self,
+        sender: mpsc::Sender<Msg>,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.ensure_sender_with::<TokioBackend, SenderMarker, Msg>(sender)
+    }
+
+    /// Registers a tokio receiver unless `ReceiverMarker` already has one, see
+    /// [`ensure_receiver_with`](Self::ensure_receiver_with).
+    pub fn ensure_receiver<ReceiverMarker, Msg>(
+        &mut self,
+        receiver: mpsc::Receiver<Msg>,
+    ) -> Result<(), CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteReceiverTrait,
+    {
+        self.ensure_receiver_with::<TokioBackend, ReceiverMarker, Msg>(receiver)
+    }
+
+    pub fn __internal_register_pathway<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.register_pathway_with::<TokioBackend, SenderMarker, ReceiverMarker, Msg>(buffer)
+    }
+
+    /// Takes the receiver for a pathway. Each receiver can be taken once.
+    ///
+    /// In debug builds, the router remembers where it was taken, so a second take
+    /// fails with e.g. "already taken at src/worker.rs:42". Helpers taking receivers
+    /// on the caller's behalf, like `pipe` or `spawn_consumer`, report their own caller.
+    #[track_caller]
+    pub fn take_receiver<ReceiverMarker, Msg>(&self) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        self.take_receiver_with::<TokioBackend, ReceiverMarker, Msg>()
+    }
+
+    /// Takes the receiver of the pathway whose receiver marker has the `TypeId`
+    /// `receiver_marker`, boxed, the dynamic counterpart of
+    /// [`take_receiver`](Self::take_receiver).
+    ///
+    /// The channel must carry the type whose `TypeId` is `msg_type`, or this fails with
+    /// `CommsError::TypeMismatch`, leaving the receiver in place. On a plain Tokio
+    /// pathway, the box holds the `mpsc::Receiver` of that type.
+    #[track_caller]
+    pub fn take_receiver_erased(
+        &self,
+        receiver_marker: TypeId,
+        msg_type: TypeId,
+    ) -> Result<Box<dyn Any + Send>, CommsError> {
+        let slot = self.typed_receivers.get(&receiver_marker).ok_or_else(|| {
+            CommsError::PathwayNotFound(
+                format!("No receiver configured for marker {:?}.", receiver_marker),
+                Some(receiver_marker),
+            )
+        })?;
+        if slot.msg_type_id != msg_type {
+            return Err(CommsError::TypeMismatch(
+                format!(
+                    "Receiver '{}' carries '{}', not the type asked for.",
+                    slot.marker_name, slot.msg_type_name
+                ),
+                Some(receiver_marker),
+            ));
+        }
+
+        let receiver = slot.lock().take().ok_or_else(|| {
+            CommsError::RecvFailed(format!(
+                "Receiver for link '{}' was already taken out of the router.",
+                slot.marker_name
+            ))
+        })?;
+        slot.mark_taken();
+        Ok(receiver.into_taken())
+    }
+
+    /// Takes the receiver of a pathway, waiting up to `timeout` for it to be handed back
+    /// if it's currently taken, e.g. by a consumer being restarted.
+    ///
+    /// Wakes up whenever a receiver is handed back with
+    /// [`return_receiver`](Self::return_receiver), and fails with `CommsError::Timeout`
+    /// once `timeout` elapses. Any other failure of `take_receiver` is returned right
+    /// away, such as asking for the wrong message type, or for a pathway that isn't
+    /// registered: registering takes `&mut Router`, so it can't happen while this
+    /// waits, and a consumer spawned before setup is done has to be handed the router
+    /// afterwards instead.
+    ///
+    /// Don't await this where the task that would return the receiver is itself
+    /// waiting on the caller, as both would then hang until the timeout.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug, PartialEq)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "JobLink",
+    /// #     Dispatcher { sends: Job, receives: () },
+    /// #     Worker { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use job_link::WorkerRecv;
+    /// # struct UnknownRecv;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # job_link::setup_job_link(&mut router, None);
+    /// let router = router.into_shared();
+    /// let old_worker = router.take_receiver::<WorkerRecv, Job>()?;
+    ///
+    /// let shutdown = router.clone();
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(Duration::from_millis(10)).await;
+    ///     shutdown.return_receiver::<WorkerRecv, Job>(old_worker)
+    /// });
+    /// let jobs = router
+    ///     .await_receiver::<WorkerRecv, Job>(Duration::from_secs(5))
+    ///     .await?;
+    ///
+    /// let unknown = router.await_receiver::<UnknownRecv, Job>(Duration::from_secs(5));
+    /// assert!(unknown.await.unwrap_err().is_not_found());
+    /// # drop(jobs);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn await_receiver<ReceiverMarker, Msg>(
+        &self,
+        timeout: Duration,
+    ) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let wait = async {
+            loop {
+                // Registered before checking, so a receiver coming back in between
+                // still wakes this up.
+                let available = self.receiver_available.notified();
+                tokio::pin!(available);
+                available.as_mut().enable();
+
+                let pending = self
+                    .typed_receivers
+                    .get(&TypeId::of::<ReceiverMarker>())
+                    .is_some_and(|slot| {
+                        slot.msg_type_id == TypeId::of::<Msg>() && slot.lock().is_none()
+                    });
+                if !pending {
+                    return self.take_receiver::<ReceiverMarker, Msg>();
+                }
+                available.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(CommsError::Timeout(format!(
+                    "Receiver for link '{}' didn't become available within {:?}.",
+                    std::any::type_name::<ReceiverMarker>(),
+                    timeout
+                )))
+            })
+    }
+
+    /// Iterates over the messages currently buffered in a pathway whose receiver is still
+    /// held by the router, receiving each with `try_recv`, e.g. for assertions at the
+    /// end of a test:
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug, PartialEq)]
+    /// # pub struct Event(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "AuditLink",
+    /// #     Logger { sends: Event, receives: () },
+    /// #     Audit { sends: (), receives: Event },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use audit_link::{AuditRecv, LoggerSend};
+    /// # fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # audit_link::setup_audit_link(&mut router, None);
+    /// # router.try_send::<LoggerSend, _>(Event(1))?;
+    /// # router.try_send::<LoggerSend, _>(Event(2))?;
+    /// let leftovers: Vec<Event> = router.drain_iter::<AuditRecv, Event>()?.collect();
+    /// # assert_eq!(leftovers, [Event(1), Event(2)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The iterator is lazy and stops at the first empty poll: it only yields what's
+    /// buffered by then, never waiting for more, and whatever it didn't get to stays
+    /// buffered. It holds the receiver slot's lock until dropped, so the receiver stays
+    /// in the router, but taking or returning it meanwhile blocks, on this thread for
+    /// good. Fails if the receiver was taken out of the router.
+    pub fn drain_iter<ReceiverMarker, Msg>(
+        &self,
+    ) -> Result<impl Iterator<Item = Msg> + '_, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let receiver_marker = TypeId::of::<ReceiverMarker>();
+        let slot = self.typed_receivers.get(&receiver_marker).ok_or_else(|| {
+            CommsError::PathwayNotFound(
+                format!(
+                    "No receiver registered for marker '{}'.",
+                    std::any::type_name::<ReceiverMarker>()
+                ),
+                Some(receiver_marker),
+            )
+        })?;
+        if slot.msg_type_id != TypeId::of::<Msg>() {
+            return Err(CommsError::TypeMismatch(
+                format!(
+                    "Expected type '{}' for receiving.",
+                    std::any::type_name::<Msg>()
+                ),
+                Some(receiver_marker),
+            ));
+        }
+
+        let guard = slot.lock();
+        if guard.is_none() {
+            return Err(CommsError::RecvFailed(format!(
+                "Receiver for link '{}' was taken out of the router, there's nothing to drain.",
+                std::any::type_name::<ReceiverMarker>()
+            )));
+        }
+        Drain::new(guard).ok_or_else(|| {
+            CommsError::InternalInconsistency(format!(
+                "Receiver for link '{}' isn't a tokio receiver of '{}'.",
+                std::any::type_name::<ReceiverMarker>(),
+                std::any::type_name::<Msg>()
+            ))
+        })
+    }
+
+    /// Takes the receiver for a pathway that may legitimately be missing, e.g. one
+    /// only registered behind an optional feature.
+    ///
+    /// Returns `Ok(None)` if the marker isn't registered or its receiver was already
+    /// taken. Every other failure of `take_receiver`, such as asking for the wrong
+    /// message type, is still an error; use `take_receiver` where absence is a bug.
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "WorkLink",
+    /// #     Boss { sends: Job, receives: () },
+    /// #     Worker { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use work_link::WorkerRecv;
+    /// # pub struct UnknownRecv;
+    /// # fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # work_link::setup_work_link(&mut router, None);
+    /// assert!(router.try_take_receiver::<UnknownRecv, Job>()?.is_none());
+    /// assert!(router.try_take_receiver::<WorkerRecv, u64>().is_err());
+    ///
+    /// assert!(router.try_take_receiver::<WorkerRecv, Job>()?.is_some());
+    /// assert!(router.try_take_receiver::<WorkerRecv, Job>()?.is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn try_take_receiver<ReceiverMarker, Msg>(
+        &self,
+    ) -> Result<Option<mpsc::Receiver<Msg>>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        match self.take_receiver::<ReceiverMarker, Msg>() {
+            Ok(receiver) => Ok(Some(receiver)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(CommsError::InternalInconsistency(_))
+                if self
+                    .typed_receivers
+                    .get(&TypeId::of::<ReceiverMarker>())
+                    .is_some_and(|slot| slot.lock().is_none()) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Takes the receiver for a pathway, paired with a pool of `pool_size` recycled buffers.
+    ///
+    /// Consumed messages can be handed back with [`PooledReceiver::return_buffer`],
+    /// and producers pick them up again through [`PooledReceiver::pool`].
+    /// See [`crate::pool::BufferPool`] for the full ownership cycle.
+    #[track_caller]
+    pub fn take_pooled_receiver<ReceiverMarker, Msg>(
+        &self,
+        pool_size: usize,
+    ) -> Result<PooledReceiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let receiver = self.take_receiver::<ReceiverMarker, Msg>()?;
+        Ok(PooledReceiver::new(receiver, pool_size))
+    }
+
+    /// Registers both halves of an instrumented pathway.
+    ///
+    /// Messages are stamped on `send` and the elapsed time is recorded when they come
+    /// out of the [`InstrumentedReceiver`] obtained with `take_instrumented_receiver`.
+    /// The sending side is used exactly like a regular pathway.
+    pub fn __internal_register_instrumented<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.register_instrumented_with::<TokioBackend, SenderMarker, ReceiverMarker, Msg>(buffer)
+    }
+
+    /// Takes the receiver of an instrumented pathway.
+    #[track_caller]
+    pub fn take_instrumented_receiver<ReceiverMarker, Msg>(
+        &self,
+    ) -> Result<InstrumentedReceiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let stats = self
+            .latency_stats
+            .get(&TypeId::of::<ReceiverMarker>())
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!(
+                        "No instrumented pathway for receiver marker '{}'.",
+                        std::any::type_name::<ReceiverMarker>()
+                    ),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                )
+            })?;
+
+        let receiver = self.take_receiver::<ReceiverMarker, Envelope<Msg>>()?;
+        Ok(InstrumentedReceiver::new(receiver, Arc::clone(stats)))
+    }
+
+    /// Registers both halves of a timestamped pathway.
+    ///
+    /// Producers send as usual, and every message is stamped on the way in. Consumers
+    /// get the stamp back along with the message from the [`TimestampedReceiver`]
+    /// obtained with `take_timestamped_receiver`. See [Timestamped Links](crate#timestamped-links).
+    pub fn __internal_register_timestamped<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.register_timestamped_with::<TokioBackend, SenderMarker, ReceiverMarker, Msg>(buffer)
+    }
+
+    /// Takes the receiver of a timestamped pathway, yielding every message along with
+    /// the wall-clock time it was sent.
+    ///
+    /// Instrumented pathways carry the same stamped messages, so their receivers can be
+    /// taken this way too, trading the latency figures of
+    /// [`take_instrumented_receiver`](Self::take_instrumented_receiver) for timestamps.
+    #[track_caller]
+    pub fn take_timestamped_receiver<ReceiverMarker, Msg>(
+        &self,
+    ) -> Result<TimestampedReceiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.take_receiver::<ReceiverMarker, Envelope<Msg>>()
+            .map(TimestampedReceiver::new)
+    }
+
+    /// Registers both halves of a codec pathway, where producers send `Domain`s but
+    /// the channel carries `Wire`s, e.g. a compact in-process representation.
+    ///
+    /// `send::<SenderMarker, Domain>` runs `encode` before pushing, and the
+    /// [`DecodingReceiver`] from `take_decoding_receiver` runs `decode` on every
+    /// message it yields. Both hooks run on every single message, synchronously, on the
+    /// sending and receiving tasks respectively, behind a boxed call: keep them cheap,
+    /// or the conversion cost lands right on the pathway's throughput. Taking the plain
+    /// receiver with `take_receiver::<ReceiverMarker, Wire>` skips decoding.
+    pub fn register_codec_pathway<SenderMarker, ReceiverMarker, Domain, Wire>(
+        &mut self,
+        buffer: usize,
+        encode: impl Fn(Domain) -> Wire + Send + Sync + 'static,
+        decode: impl Fn(Wire) -> Domain + Send + Sync + 'static,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Domain: ConcreteSenderTrait,
+        Wire: ConcreteSenderTrait,
+    {
+        let codec = Arc::new(Codec::new(encode, decode));
+        self.register_codec_pathway_from::<SenderMarker, ReceiverMarker, Domain, Wire>(
+            buffer, codec,
+        )
+    }
+
+    fn register_codec_pathway_from<SenderMarker, ReceiverMarker, Domain, Wire>(
+        &mut self,
+        buffer: usize,
+        codec: Arc<Codec<Domain, Wire>>,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Domain: ConcreteSenderTrait,
+        Wire: ConcreteSenderTrait,
+    {
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        let (sender, receiver) = mpsc::channel::<Wire>(buffer);
+        self.typed_senders.insert(
+            TypeId::of::<SenderMarker>(),
+            SenderSlot::new::<Domain>(
+                std::any::type_name::<SenderMarker>(),
+                Box::new(CodecSender {
+                    sender,
+                    codec: Arc::clone(&codec),
+                }),
+            ),
+        );
+        self.register_receiver_with::<TokioBackend, ReceiverMarker, Wire>(receiver)?;
+
+        self.codecs
+            .insert(TypeId::of::<SenderMarker>(), Arc::clone(&codec) as _);
+        self.codecs.insert(TypeId::of::<ReceiverMarker>(), codec);
+        self.link_pathway::<SenderMarker, ReceiverMarker, Domain, Wire>(
+            buffer,
+            Self::rebuild_codec_pathway::<SenderMarker, ReceiverMarker, Domain, Wire>,
+        );
+
+        Ok(())
+    }
+
+    /// Recreates a codec pathway with the hooks it was first registered with.
+    fn rebuild_codec_pathway<SenderMarker, ReceiverMarker, Domain, Wire>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Domain: ConcreteSenderTrait,
+        Wire: ConcreteSenderTrait,
+    {
+        let codec = self.codec::<SenderMarker, Domain, Wire>()?;
+        self.register_codec_pathway_from::<SenderMarker, ReceiverMarker, Domain, Wire>(
+            buffer, codec,
+        )
+    }
+
+    /// Looks up the codec registered under `Marker`, either half of a codec pathway.
+    fn codec<Marker, Domain, Wire>(&self) -> Result<Arc<Codec<Domain, Wire>>, CommsError>
+    where
+        Marker: Any + Send + Sync + 'static,
+        Domain: ConcreteSenderTrait,
+        Wire: ConcreteSenderTrait,
+    {
+        let codec = self.codecs.get(&TypeId::of::<Marker>()).ok_or_else(|| {
+            CommsError::PathwayNotFound(
+                format!(
+                    "No codec pathway for marker '{}'.",
+                    std::any::type_name::<Marker>()
+                ),
+                Some(TypeId::of::<Marker>()),
+            )
+        })?;
+
+        Arc::clone(codec).downcast().map_err(|_| {
+            CommsError::TypeMismatch(
+                format!(
+                    "Codec pathway '{}' doesn't convert between '{}' and '{}'.",
+                    std::any::type_name::<Marker>(),
+                    std::any::type_name::<Domain>(),
+                    std::any::type_name::<Wire>()
+                ),
+                Some(TypeId::of::<Marker>()),
+            )
+        })
+    }
+
+    /// Takes the receiver of a codec pathway, decoding messages back into `Domain`s.
+    #[track_caller]
+    pub fn take_decoding_receiver<ReceiverMarker, Domain, Wire>(
+        &self,
+    ) -> Result<DecodingReceiver<Domain, Wire>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Domain: ConcreteSenderTrait,
+        Wire: ConcreteSenderTrait,
+    {
+        let codec = self.codec::<ReceiverMarker, Domain, Wire>()?;
+        let receiver = self.take_receiver::<ReceiverMarker, Wire>()?;
+        Ok(DecodingReceiver::new(receiver, codec))
+    }
+
+    /// Takes the receiver of a pathway, wrapped to drop messages whose key, as computed
+    /// by `key_fn`, matches one of the last `window` messages it delivered.
+    ///
+    /// Meant for idempotent consumers behind an at-least-once source, e.g. a relay
+    /// retrying sends that may have gone through. Memory is bounded by `window` keys,
+    /// and duplicates further apart than that get through. See [`DedupReceiver`].
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug, PartialEq)]
+    /// # pub struct Order { id: u64 }
+    /// # define_crosslink! {
+    /// #     link_id: "BillingLink",
+    /// #     Checkout { sends: Order, receives: () },
+    /// #     Billing { sends: (), receives: Order },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use billing_link::{BillingRecv, CheckoutSend};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # billing_link::setup_billing_link(&mut router, None);
+    /// # for id in [1, 1, 2] {
+    /// #     router.try_send::<CheckoutSend, _>(Order { id })?;
+    /// # }
+    /// let mut orders = router.take_deduped_receiver::<BillingRecv, Order, _>(|o| o.id, 1024)?;
+    /// # assert_eq!(orders.recv().await, Some(Order { id: 1 }));
+    /// # assert_eq!(orders.recv().await, Some(Order { id: 2 }));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `window` is 0.
+    #[track_caller]
+    pub fn take_deduped_receiver<ReceiverMarker, Msg, K>(
+        &self,
+        key_fn: impl Fn(&Msg) -> K + Send + Sync + 'static,
+        window: usize,
+    ) -> Result<DedupReceiver<Msg, K>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+        K: Hash + Eq + Clone,
+    {
+        assert!(window > 0, "dedup window must be greater than zero");
+        let receiver = self.take_receiver::<ReceiverMarker, Msg>()?;
+        Ok(DedupReceiver::new(receiver, key_fn, window))
+    }
+
+    /// Registers a sharded pathway: `shards` channels of `buffer` messages each, all fed
+    /// through `SenderMarker`, for as many identical consumers.
+    ///
+    /// Producers pick a shard per message with [`send_sharded`](Self::send_sharded),
+    /// by key, and each consumer takes its own shard's receiver with
+    /// [`take_shard_receiver`](Self::take_shard_receiver). Messages with the same key
+    /// always land on the same shard, so they're received in the order they were sent.
+    /// Plain `send` and `try_send` on `SenderMarker` fail with
+    /// `CommsError::AmbiguousPathway`.
+    ///
+    /// The shard count is fixed at registration, and a key's shard is `key % shards`.
+    /// There's no rebalancing: a slow or stopped shard holds up its keys only, and a
+    /// shard whose receiver is never taken just fills up. Changing the count moves most
+    /// keys to another shard, losing their ordering across the change.
+    ///
+    /// # Panics
+    /// Panics if `shards` or `buffer` is 0.
+    pub fn register_sharded_pathway<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        shards: usize,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        assert!(shards > 0, "a sharded pathway needs at least one shard");
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+        if self.shards.contains_key(&TypeId::of::<ReceiverMarker>()) {
+            return Err(CommsError::PathwayAlreadyRegistered(format!(
+                "Receiver for marker type '{}' already registered.",
+                std::any::type_name::<ReceiverMarker>()
+            )));
+        }
+
+        let marker_name = std::any::type_name::<SenderMarker>();
+        let pathway = SenderSlot::new::<Msg>(
+            marker_name,
+            Box::new(KeyedSender::<Msg> {
+                marker_name,
+                _msg: PhantomData,
+            }),
+        );
+
+        let mut senders = Vec::with_capacity(shards);
+        let mut receivers = Vec::with_capacity(shards);
+        for _ in 0..shards {
+            let (sender, receiver) = mpsc::channel::<Msg>(buffer);
+            let mut shard = SenderSlot::new::<Msg>(
+                marker_name,
+                Box::new(ConcreteSender::<TokioBackend, Msg> { sender }),
+            );
+            shard.counters = Arc::clone(&pathway.counters);
+            shard.gate = Arc::clone(&pathway.gate);
+            senders.push(shard);
+            receivers.push(ReceiverSlot::new::<Msg>(
+                std::any::type_name::<ReceiverMarker>(),
+                Box::new(ConcreteReceiver::<TokioBackend, Msg> { receiver }),
+            ));
+        }
+
+        self.typed_senders
+            .insert(TypeId::of::<SenderMarker>(), pathway);
+        let shards = Arc::new(Shards { senders, receivers });
+        self.shards
+            .insert(TypeId::of::<SenderMarker>(), Arc::clone(&shards));
+        self.shards.insert(TypeId::of::<ReceiverMarker>(), shards);
+        Ok(())
+    }
+
+    /// Sends a message on a sharded pathway, to shard `key % shards`.
+    ///
+    /// Goes through the same hooks as `send`: it waits for capacity on the chosen shard,
+    /// and while the pathway is paused.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send_sharded<SenderMarker, Msg>(
+        &self,
+        message: Msg,
+        key: u64,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let slot = self
+            .shards_of::<SenderMarker>()
+            .map(|shards| &shards.senders[shards.index(key)]);
+        let slot = slot.and_then(|slot| dispatch::checked_sender::<SenderMarker, Msg>(Some(slot)));
+        dispatch::send::<SenderMarker, Msg>(slot, &self.hooks, message).await
+    }
+
+    /// Takes the receiver of shard `index` of a sharded pathway.
+    #[track_caller]
+    pub fn take_shard_receiver<ReceiverMarker, Msg>(
+        &self,
+        index: usize,
+    ) -> Result<mpsc::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let shards = self.shards_of::<ReceiverMarker>()?;
+        let slot = shards.receivers.get(index).ok_or_else(|| {
+            CommsError::PathwayNotFound(
+                format!(
+                    "Sharded pathway '{}' has {} shards, there's no shard {}.",
+                    std::any::type_name::<ReceiverMarker>(),
+                    shards.receivers.len(),
+                    index
+                ),
+                Some(TypeId::of::<ReceiverMarker>()),
+            )
+        })?;
+        dispatch::take_receiver::<TokioBackend, ReceiverMarker, Msg>(Some(slot))
+    }
+
+    /// Returns how many shards the sharded pathway registered under `Marker`, either
+    /// of its markers, has, or `None` if it isn't a sharded pathway.
+    pub fn shard_count<Marker>(&self) -> Option<usize>
+    where
+        Marker: Any + Send + Sync + 'static,
+    {
+        self.shards_of::<Marker>()
+            .ok()
+            .map(|shards| shards.receivers.len())
+    }
+
+    fn shards_of<Marker>(&self) -> Result<&Shards, CommsError>
+    where
+        Marker: Any + Send + Sync + 'static,
+    {
+        self.shards
+            .get(&TypeId::of::<Marker>())
+            .map(|shards| &**shards)
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!(
+                        "No sharded pathway for marker '{}'.",
+                        std::any::type_name::<Marker>()
+                    ),
+                    Some(TypeId::of::<Marker>()),
+                )
+            })
+    }
+
+    /// Registers a pathway whose bounded buffer of `buffer` messages spills into an
+    /// unbounded overflow queue instead of making producers wait, for links that are
+    /// mostly quiet but see the odd burst.
+    ///
+    /// Both `send` and `try_send` go straight into the buffer while it has room, and
+    /// into the overflow queue otherwise, so neither ever waits nor fails with
+    /// `ChannelFull`. A background task moves overflowed messages back into the buffer
+    /// as the consumer frees it up, and while any are queued, new messages line up
+    /// behind them, so a producer's messages arrive in the order they were sent.
+    /// [`overflow_depth`](Self::overflow_depth) reports how many are waiting there.
+    ///
+    /// This trades backpressure for burst tolerance: nothing slows a producer down, so
+    /// a consumer that stops keeping up lets the overflow queue, and the process's
+    /// memory, grow without bound. Watch `overflow_depth`, or the
+    /// `crosslink_overflow_depth` gauge with the `metrics` feature, for that.
+    ///
+    /// The pathway is registered as a whole, so `reset_pathway` and `reconfigure_buffer`
+    /// rebuild it as a spillover pathway, with a drain task of its own. Resizing fails
+    /// while messages are waiting in the overflow queue, since the old task would
+    /// drain them into the discarded channel.
+    ///
+    /// # Panics
+    /// Panics if `buffer` is 0, or if called outside of a Tokio runtime, since it spawns
+    /// the task draining the overflow queue. Resetting or resizing the pathway spawns a
+    /// new one, and panics outside of a runtime too.
+    #[track_caller]
+    pub fn register_spillover_pathway<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        let marker_name = std::any::type_name::<SenderMarker>();
+        let (sender, receiver) = mpsc::channel::<Msg>(buffer);
+        let (overflow, overflow_rx) = mpsc::unbounded_channel();
+        let depth = Arc::new(OverflowDepth::new(marker_name));
+
+        let task = TaskName::new(None, "overflow", || marker_name);
+        task.spawn(overflow::drain_overflow(
+            overflow_rx,
+            sender.clone(),
+            Arc::clone(&depth),
+        ));
+
+        self.typed_senders.insert(
+            TypeId::of::<SenderMarker>(),
+            SenderSlot::new::<Msg>(
+                marker_name,
+                Box::new(OverflowSender {
+                    sender,
+                    overflow,
+                    depth: Arc::clone(&depth),
+                }),
+            ),
+        );
+        self.register_receiver_with::<TokioBackend, ReceiverMarker, Msg>(receiver)?;
+        self.link_pathway::<SenderMarker, ReceiverMarker, Msg, Msg>(
+            buffer,
+            Self::register_spillover_pathway::<SenderMarker, ReceiverMarker, Msg>,
+        );
+        self.overflow_depths
+            .insert(TypeId::of::<SenderMarker>(), depth);
+        Ok(())
+    }
+
+    /// How many messages are waiting in the overflow queue of the spillover pathway
+    /// `SenderMarker` sends on, or `None` if it isn't one.
+    ///
+    /// Messages already in the bounded buffer aren't counted. A message leaves the count
+    /// just after the background task moves it into the buffer, so the consumer may
+    /// receive it a moment before it does.
+    pub fn overflow_depth<SenderMarker>(&self) -> Option<usize>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+    {
+        self.overflow_depths
+            .get(&TypeId::of::<SenderMarker>())
+            .map(|depth| depth.get())
+    }
+
+    /// The deepest the overflow queue of the spillover pathway `SenderMarker` sends on
+    /// has been so far, or `None` if it isn't one.
+    pub fn overflow_peak<SenderMarker>(&self) -> Option<usize>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+    {
+        self.overflow_depths
+            .get(&TypeId::of::<SenderMarker>())
+            .map(|depth| depth.peak())
+    }
+
+    /// Takes the receiver for a pathway as a [`SharedReceiver`],
+    /// so several worker tasks can consume from it as competing consumers.
+    #[track_caller]
+    pub fn shared_receiver<ReceiverMarker, Msg>(&self) -> Result<SharedReceiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let receiver = self.take_receiver::<ReceiverMarker, Msg>()?;
+        Ok(SharedReceiver::new(receiver))
+    }
+
+    /// Starts tracking the high-water mark of `ReceiverMarker`'s pathway: the most
+    /// messages ever found waiting in its buffer, as reported by
+    /// [`high_water_mark`](Self::high_water_mark).
+    ///
+    /// The receive loops of `spawn_consumer`, `connect_external_sink` and the relays
+    /// sample the buffer's length each time they take a message out, which is an atomic
+    /// load and a `fetch_max` per message; receivers taken with `take_receiver` and read
+    /// by hand aren't sampled. Every sample that finds the buffer full is also reported
+    /// as a saturated receiver, with the `tracing` and `metrics` features. Call this
+    /// before spawning the helper: loops already running don't pick it up.
+    pub fn track_high_water_mark<ReceiverMarker>(&mut self) -> Result<(), CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+    {
+        let receiver_marker = TypeId::of::<ReceiverMarker>();
+        if !self.typed_receivers.contains_key(&receiver_marker) {
+            return Err(CommsError::PathwayNotFound(
+                format!(
+                    "No receiver registered for marker '{}'.",
+                    std::any::type_name::<ReceiverMarker>()
+                ),
+                Some(receiver_marker),
+            ));
+        }
+
+        // Labelled by the sender marker, like every other signal about the pathway.
+        let pathway = self
+            .pathway_links
+            .iter()
+            .find(|(_, link)| link.receiver_marker == receiver_marker)
+            .and_then(|(sender_marker, _)| self.typed_senders.get(sender_marker))
+            .map_or(std::any::type_name::<ReceiverMarker>(), |slot| {
+                slot.marker_name
+            });
+        self.high_water_marks
+            .entry(receiver_marker)
+            .or_insert_with(|| Arc::new(HighWaterMark::new(pathway)));
+        Ok(())
+    }
+
+    /// The most messages seen waiting in `ReceiverMarker`'s buffer so far, or `None`
+    /// unless [`track_high_water_mark`](Self::track_high_water_mark) was called for it.
+    ///
+    /// A mark equal to the pathway's buffer size means the consumer fell behind far
+    /// enough for producers to wait, or to drop messages with `try_send`.
+    pub fn high_water_mark<ReceiverMarker>(&self) -> Option<usize>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+    {
+        self.high_water_marks
+            .get(&TypeId::of::<ReceiverMarker>())
+            .map(|peak| peak.get())
+    }
+
+    fn tracked_high_water_mark<ReceiverMarker: Any>(&self) -> Option<Arc<HighWaterMark>> {
+        self.high_water_marks
+            .get(&TypeId::of::<ReceiverMarker>())
+            .cloned()
+    }
+
+    /// Spawns a task forwarding every message from one link's receiver into another
+    /// link's sender, e.g. to chain `A -> B -> C`.
+    ///
+    /// Forwarding uses `send().await`, so the relay deliberately blocks while the
+    /// downstream buffer is full. Backpressure from a slow consumer at the end of the
+    /// chain thus propagates all the way back to the original producer, instead of
+    /// being absorbed by the relay.
+    ///
+    /// Messages are forwarded one at a time, in the order they were received, so the
+    /// relay never reorders or duplicates them, nor drops any while the downstream link
+    /// is up. Every relay below keeps the same order, minus what it filters out.
+    ///
+    /// The task exits once the source link is closed or the downstream link is gone.
+    ///
+    /// Like every spawning helper, it takes an optional task `name`, see
+    /// [Task Names](crate#task-names).
+    #[track_caller]
+    pub fn pipe<FromMarker, ToMarker, Msg>(
+        &self,
+        name: Option<&str>,
+    ) -> Result<JoinHandle<()>, CommsError>
+    where
+        FromMarker: Any + Send + Sync + 'static,
+        ToMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.spawn_relay::<FromMarker, Msg, ToMarker, Msg>(|msg| msg, name)
+    }
+
+    /// Spawns a task forwarding every message from one link's receiver into another
+    /// link's sender, mapping each one with `f` on the way, e.g. to turn raw
+    /// readings into normalized samples.
+    ///
+    /// Same as [`Router::pipe`] otherwise: forwarding waits on downstream
+    /// backpressure, and the task exits once either side is gone.
+    #[track_caller]
+    pub fn spawn_relay<FromMarker, FromMsg, ToMarker, ToMsg>(
+        &self,
+        f: impl Fn(FromMsg) -> ToMsg + Send + 'static,
+        name: Option<&str>,
+    ) -> Result<JoinHandle<()>, CommsError>
+    where
+        FromMarker: Any + Send + Sync + 'static,
+        FromMsg: Send + 'static + Debug + Sync,
+        ToMarker: Any + Send + Sync + 'static,
+        ToMsg: ConcreteSenderTrait,
+    {
+        self.spawn_forwarder::<FromMarker, FromMsg, ToMarker, ToMsg>(move |msg| Some(f(msg)), name)
+    }
+
+    /// Spawns a task forwarding messages from one link's receiver into another
+    /// link's sender, like [`Router::pipe`], but only those satisfying `pred`.
+    ///
+    /// Messages failing `pred` are dropped right away, e.g. to filter out noise
+    /// between links.
+    #[track_caller]
+    pub fn spawn_filter_relay<FromMarker, ToMarker, Msg>(
+        &self,
+        pred: impl Fn(&Msg) -> bool + Send + 'static,
+        name: Option<&str>,
+    ) -> Result<JoinHandle<()>, CommsError>
+    where
+        FromMarker: Any + Send + Sync + 'static,
+        ToMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.spawn_forwarder::<FromMarker, Msg, ToMarker, Msg>(
+            move |msg| pred(&msg).then_some(msg),
+            name,
+        )
+    }
+
+    /// Shared task behind the relays: forwards whatever `f` returns, skipping `None`s.
+    #[track_caller]
+    fn spawn_forwarder<FromMarker, FromMsg, ToMarker, ToMsg>(
+        &self,
+        f: impl Fn(FromMsg) -> Option<ToMsg> + Send + 'static,
+        name: Option<&str>,
+    ) -> Result<JoinHandle<()>, CommsError>
+    where
+        FromMarker: Any + Send + Sync + 'static,
+        FromMsg: Send + 'static + Debug + Sync,
+        ToMarker: Any + Send + Sync + 'static,
+        ToMsg: ConcreteSenderTrait,
+    {
+        let slot = self.typed_sender::<ToMarker, ToMsg>()?;
+        let receiver = self.take_receiver::<FromMarker, FromMsg>()?;
+        let peak = self.tracked_high_water_mark::<FromMarker>();
+        let task = TaskName::new(name, "relay", || {
+            format!(
+                "{}->{}",
+                std::any::type_name::<FromMarker>(),
+                std::any::type_name::<ToMarker>()
+            )
+        });
+        Ok(Self::spawn_forwarding_task(receiver, slot, f, task, peak))
+    }
+
+    /// Forwards everything `receiver` yields into the pathway behind `slot`, through `f`,
+    /// until either side closes, sampling `receiver` into `peak` if given.
+    fn spawn_forwarding_task<FromMsg, ToMsg>(
+        mut receiver: mpsc::Receiver<FromMsg>,
+        slot: &SenderSlot,
+        f: impl Fn(FromMsg) -> Option<ToMsg> + Send + 'static,
+        task: TaskName,
+        peak: Option<Arc<HighWaterMark>>,
+    ) -> JoinHandle<()>
+    where
+        FromMsg: Send + 'static,
+        ToMsg: ConcreteSenderTrait,
+    {
+        let (sender, counters, gate) = (
+            slot.sender.clone_box(),
+            Arc::clone(&slot.counters),
+            Arc::clone(&slot.gate),
+        );
+
+        task.spawn(async move {
+            while let Some(msg) = receiver.recv().await {
+                sample_high_water_mark(peak.as_deref(), &receiver);
+                let Some(msg) = f(msg) else {
+                    continue;
+                };
+                gate.opened().await;
+                let result = sender.send_erased(Box::new(msg)).await;
+                counters.record(&result);
+                if result.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Spawns a task feeding a pathway from a plain `mpsc::Receiver` created outside
+    /// crosslink, e.g. one handed out by a third-party library.
+    ///
+    /// Forwarding uses `send().await`, so backpressure reaches the external channel.
+    /// The task exits once the external channel is closed and drained,
+    /// or the pathway is gone.
+    pub fn connect_external_source<SenderMarker, Msg>(
+        &self,
+        source: mpsc::Receiver<Msg>,
+        name: Option<&str>,
+    ) -> Result<JoinHandle<()>, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let slot = self.typed_sender::<SenderMarker, Msg>()?;
+        let task = TaskName::new(name, "source", std::any::type_name::<SenderMarker>);
+        Ok(Self::spawn_forwarding_task(source, slot, Some, task, None))
+    }
+
+    /// Spawns a task draining a pathway's receiver into a plain `mpsc::Sender`
+    /// created outside crosslink.
+    ///
+    /// The receiver is taken from the router for good. The task exits once the pathway
+    /// is closed and drained, or the external receiver is dropped; in the latter case
+    /// the message in hand is lost along with anything still buffered.
+    #[track_caller]
+    pub fn connect_external_sink<ReceiverMarker, Msg>(
+        &self,
+        sink: mpsc::Sender<Msg>,
+        name: Option<&str>,
+    ) -> Result<JoinHandle<()>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let mut receiver = self.take_receiver::<ReceiverMarker, Msg>()?;
+        let peak = self.tracked_high_water_mark::<ReceiverMarker>();
+        let task = TaskName::new(name, "sink", std::any::type_name::<ReceiverMarker>);
+
+        Ok(task.spawn(async move {
+            while let Some(msg) = receiver.recv().await {
+                sample_high_water_mark(peak.as_deref(), &receiver);
+                if sink.send(msg).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Spawns a task driving the receive loop of `ReceiverMarker`, awaiting `handler`
+    /// on every message until the pathway closes.
+    ///
+    /// This consumes the receiver: the spawned task owns it, so later `take_receiver`
+    /// calls for the marker fail as if it had been taken. Messages are handled one at a
+    /// time, each handler future running to completion before the next message is
+    /// received. Aborting the returned handle drops the receiver, closing the pathway.
+    #[track_caller]
+    pub fn spawn_consumer<ReceiverMarker, Msg, F, Fut>(
+        &self,
+        mut handler: F,
+        name: Option<&str>,
+    ) -> Result<JoinHandle<()>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+        F: FnMut(Msg) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut receiver = self.take_receiver::<ReceiverMarker, Msg>()?;
+        let peak = self.tracked_high_water_mark::<ReceiverMarker>();
+        let task = TaskName::new(name, "consumer", std::any::type_name::<ReceiverMarker>);
+
+        Ok(task.spawn(async move {
+            while let Some(msg) = receiver.recv().await {
+                sample_high_water_mark(peak.as_deref(), &receiver);
+                handler(msg).await;
+            }
+        }))
+    }
+
+    /// Sends a message with `try_send`, retrying with exponential backoff while the
+    /// pathway is full or paused, as set out by `policy`.
+    ///
+    /// A closed pathway fails right away with `SendFailed`. Once `policy.max_attempts`
+    /// attempts found no room, fails with `ChannelFull`, its reason ending with the
+    /// number of attempts made. The message is only kept for another attempt on plain
+    /// and instrumented pathways: elsewhere, e.g. on acked, rendezvous or codec
+    /// pathways, the first failure is final.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use crosslink::{CommsError, Router, define_crosslink, retry::RetryPolicy};
+    /// # #[derive(Debug)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "FooLink",
+    /// #     Foo { sends: Job, receives: () },
+    /// #     Bar { sends: (), receives: Job },
+    /// #     buffer_size: 1,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use foo_link::{BarRecv, FooSend};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # foo_link::setup_foo_link(&mut router, None);
+    /// # let router = router.into_shared();
+    /// # router.try_send::<FooSend, _>(Job(0))?;
+    /// # let mut bar = router.take_receiver::<BarRecv, Job>()?;
+    /// # tokio::spawn(async move { while bar.recv().await.is_some() {} });
+    /// # let msg = Job(1);
+    /// let policy = RetryPolicy {
+    ///     max_attempts: 4,
+    ///     initial_delay: Duration::from_millis(5),
+    ///     multiplier: 3.0,
+    /// };
+    /// router.send_with_retry::<FooSend, _>(msg, policy).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send_with_retry<SenderMarker, Msg>(
+        &self,
+        message: Msg,
+        policy: RetryPolicy,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        dispatch::send_with_retry::<SenderMarker, Msg>(
+            self.typed_sender::<SenderMarker, Msg>(),
+            &self.hooks,
+            message,
+            policy,
+        )
+        .await
+    }
+
+    /// Sends every message of `messages` in order, yielding to the runtime after each
+    /// `yield_every` of them, [`SEND_YIELD_INTERVAL`] if `None`.
+    ///
+    /// A producer sending a large batch into a pathway with room to spare never has to
+    /// wait, and could hold its worker thread until the whole batch is through, keeping
+    /// other tasks from running. The yields hand the thread back now and then. Plain
+    /// `send` never yields on its own, so single latency-sensitive sends pay nothing
+    /// for this. Stops at the first failed send and returns its error, dropping the
+    /// messages after it.
+    ///
+    /// # Panics
+    /// Panics if `yield_every` is `Some(0)`.
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # define_crosslink! {
+    /// #     link_id: "FooLink",
+    /// #     Foo { sends: u32, receives: () },
+    /// #     Bar { sends: (), receives: u32 },
+    /// #     buffer_size: 64,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use foo_link::{BarRecv, FooSend};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # foo_link::setup_foo_link(&mut router, None);
+    /// # let batch = 0..64u32;
+    /// router.send_yielding::<FooSend, _>(batch, Some(16)).await?;
+    /// # assert_eq!(router.receiver_len::<BarRecv>()?, 64);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "a failed send drops the rest of the batch, check the result"]
+    pub async fn send_yielding<SenderMarker, Msg>(
+        &self,
+        messages: impl IntoIterator<Item = Msg>,
+        yield_every: Option<usize>,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let yield_every = yield_every.unwrap_or(SEND_YIELD_INTERVAL);
+        assert!(
+            yield_every > 0,
+            "send_yielding needs a non-zero yield interval"
+        );
+
+        let slot = self
+            .typed_sender::<SenderMarker, Msg>()
+            .map_err(|e| e.with_marker(TypeId::of::<SenderMarker>()))?;
+        for (sent, message) in messages.into_iter().enumerate() {
+            if sent > 0 && sent % yield_every == 0 {
+                tokio::task::yield_now().await;
+            }
+            dispatch::send::<SenderMarker, Msg>(Ok(slot), &self.hooks, message).await?;
+        }
+        Ok(())
+    }
+
+    /// Reserves room for one message on the pathway of `SenderMarker`, waiting for
+    /// capacity like `send` does, and returns it as a permit to commit later with
+    /// [`OwnedPermit::send`].
+    ///
+    /// Unlike the send itself, the permit isn't tied to the router: it can be kept
+    /// around, or moved into the task producing the message, which separates
+    /// admission control from production. Waits while the pathway is paused, and fails
+    /// with `CommsError::SendFailed` if its receiver is already gone. Only plain
+    /// pathways hand out permits: instrumented, acked, rendezvous, codec, sharded and
+    /// spillover ones fail with `CommsError::TypeMismatch`. Committed messages are
+    /// counted in `stats`, but skip the tap.
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug, PartialEq)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "FooLink",
+    /// #     Foo { sends: Job, receives: () },
+    /// #     Bar { sends: (), receives: Job },
+    /// #     buffer_size: 1,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use foo_link::{BarRecv, FooSend};
+    /// # async fn build_job() -> Job { Job(1) }
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # foo_link::setup_foo_link(&mut router, None);
+    /// # let mut bar = router.take_receiver::<BarRecv, Job>()?;
+    /// let permit = router.reserve_owned::<FooSend, Job>().await?;
+    /// tokio::spawn(async move { permit.send(build_job().await) });
+    /// # assert_eq!(bar.recv().await, Some(Job(1)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reserve_owned<SenderMarker, Msg>(&self) -> Result<OwnedPermit<Msg>, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let slot = self.typed_sender::<SenderMarker, Msg>()?;
+        slot.gate.opened().await;
+        let permit = slot
+            .sender
+            .reserve_owned_erased()
+            .await
+            .map_err(|e| e.with_marker(TypeId::of::<SenderMarker>()))?
+            .downcast::<mpsc::OwnedPermit<Msg>>()
+            .map_err(|_| downcast_error::<Msg>())?;
+        Ok(OwnedPermit::new(
+            *permit,
+            Arc::clone(&slot.counters),
+            slot.marker_name,
+        ))
+    }
+
+    /// Calls `f` with the raw Tokio sender behind the pathway of `SenderMarker`, and
+    /// returns what it computes, for Tokio operations the router doesn't wrap, such as
+    /// `same_channel` or `strong_count`.
+    ///
+    /// This deliberately exposes Tokio internals: sending through the raw sender skips
+    /// everything the router does around a send, i.e. pausing, stats, the tap and
+    /// dead letters, and code relying on it is tied to the Tokio backend. Only plain
+    /// pathways have a raw sender of `Msg`s: others fail with
+    /// `CommsError::TypeMismatch`.
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "FooLink",
+    /// #     Foo { sends: Job, receives: () },
+    /// #     Bar { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use foo_link::FooSend;
+    /// # fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # foo_link::setup_foo_link(&mut router, None);
+    /// let shared = router.with_sender::<FooSend, Job, _>(|tx| tx.strong_count() > 1)?;
+    /// # assert!(!shared);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_sender<SenderMarker, Msg, R>(
+        &self,
+        f: impl FnOnce(&mpsc::Sender<Msg>) -> R,
+    ) -> Result<R, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let slot = self.typed_sender::<SenderMarker, Msg>()?;
+        slot.sender
+            .raw_sender()
+            .and_then(|sender| sender.downcast_ref::<mpsc::Sender<Msg>>())
+            .map(f)
+            .ok_or_else(|| {
+                CommsError::TypeMismatch(
+                    format!(
+                        "Pathway '{}' has no raw Tokio sender of '{}': only plain pathways on \
+                        Tokio channels do.",
+                        slot.marker_name,
+                        std::any::type_name::<Msg>()
+                    ),
+                    Some(TypeId::of::<SenderMarker>()),
+                )
+            })
+    }
+
+    /// Registers both halves of an acknowledged pathway.
+    ///
+    /// The channel carries [`Acked<Msg>`], so consumers take the receiver with
+    /// `take_receiver::<ReceiverMarker, Acked<Msg>>()` and ack each message once processed.
+    pub fn __internal_register_acked<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        let (sender, receiver) = mpsc::channel::<Acked<Msg>>(buffer);
+        self.typed_senders.insert(
+            TypeId::of::<SenderMarker>(),
+            SenderSlot::new::<Msg>(
+                std::any::type_name::<SenderMarker>(),
+                Box::new(AckSender::<TokioBackend, Msg> { sender }),
+            ),
+        );
+        self.register_receiver_with::<TokioBackend, ReceiverMarker, Acked<Msg>>(receiver)?;
+        self.link_pathway::<SenderMarker, ReceiverMarker, Msg, Acked<Msg>>(
+            buffer,
+            Self::__internal_register_acked::<SenderMarker, ReceiverMarker, Msg>,
+        );
+
+        Ok(())
+    }
+
+    /// Sends a message on an acknowledged pathway and waits until the consumer
+    /// calls [`Acked::ack`] on it.
+    ///
+    /// Resolves with `CommsError::NotAcked` if the consumer drops the message without
+    /// acking it or fails it, and with `CommsError::SendFailed` if the consumer side is gone.
+    #[must_use = "a failed send drops the message, check the result"]
+    pub async fn send_acked<SenderMarker, Msg>(&self, message: Msg) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        match self
+            .send_awaiting_reply::<SenderMarker, Msg>(message)
+            .await?
+        {
+            Reply::Ack => Ok(()),
+            Reply::Failed(_) => Err(CommsError::NotAcked(format!(
+                "Consumer of '{}' reported a failure processing message of type '{}'.",
+                std::any::type_name::<SenderMarker>(),
+                std::any::type_name::<Msg>()
+            ))),
+        }
+    }
+
+    /// Sends a message on an acknowledged pathway and waits for the consumer's
+    /// processing result, as reported with [`Acked::respond`], [`Acked::ack`] or
+    /// [`Acked::fail`].
+    ///
+    /// The two layers of `Result` keep transport and processing apart:
+    /// - `Err(CommsError)` means the message never got a verdict: the pathway is
+    ///   missing or closed (`SendFailed`), the consumer dropped the message without
+    ///   responding (`NotAcked`), or it failed with an error that isn't an `E`
+    ///   (`TypeMismatch`).
+    /// - `Ok(Err(e))` means the consumer processed the message and it failed with `e`.
+    /// - `Ok(Ok(()))` means it was processed successfully.
+    #[must_use = "a failed send drops the message, and the consumer's error is only seen in the result"]
+    pub async fn send_expecting_result<SenderMarker, Msg, E>(
+        &self,
+        message: Msg,
+    ) -> Result<Result<(), E>, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+        E: Send + 'static,
+    {
+        match self
+            .send_awaiting_reply::<SenderMarker, Msg>(message)
+            .await?
+        {
+            Reply::Ack => Ok(Ok(())),
+            Reply::Failed(error) => match error.downcast::<E>() {
+                Ok(error) => Ok(Err(*error)),
+                Err(_) => Err(CommsError::TypeMismatch(
+                    format!(
+                        "Consumer of '{}' failed with an error that isn't a '{}'.",
+                        std::any::type_name::<SenderMarker>(),
+                        std::any::type_name::<E>()
+                    ),
+                    Some(TypeId::of::<SenderMarker>()),
+                )),
+            },
+        }
+    }
+
+    /// Registers the receiving half of a link's error channel, and returns the sender
+    /// each reporting endpoint is registered with, through
+    /// `__internal_register_error_reporter`.
+    pub fn __internal_register_error_channel<SupervisorMarker, E>(
+        &mut self,
+        buffer: usize,
+    ) -> Result<mpsc::Sender<ErrorReport<E>>, CommsError>
+    where
+        SupervisorMarker: Any + Send + Sync + 'static,
+        E: ConcreteSenderTrait,
+    {
+        let (sender, receiver) = mpsc::channel::<ErrorReport<E>>(buffer);
+        self.register_receiver_with::<TokioBackend, SupervisorMarker, ErrorReport<E>>(receiver)?;
+        Ok(sender)
+    }
+
+    /// Registers an endpoint's side of its link's error channel, tagging whatever it
+    /// reports with `source`.
+    pub fn __internal_register_error_reporter<ReporterMarker, E>(
+        &mut self,
+        sender: mpsc::Sender<ErrorReport<E>>,
+        source: &'static str,
+    ) -> Result<(), CommsError>
+    where
+        ReporterMarker: Any + Send + Sync + 'static,
+        E: ConcreteSenderTrait,
+    {
+        self.ensure_sender_vacant::<ReporterMarker>()?;
+        self.typed_senders.insert(
+            TypeId::of::<ReporterMarker>(),
+            SenderSlot::new::<E>(
+                std::any::type_name::<ReporterMarker>(),
+                Box::new(ReportingSender::<TokioBackend, E> { sender, source }),
+            ),
+        );
+        Ok(())
+    }
+
+    /// Reports `error` to the supervisor of the link `ReporterMarker` belongs to, i.e.
+    /// the `{HandleName}Errors` marker of an endpoint declared with `errors:`.
+    ///
+    /// The error channel is a pathway of its own, next to the endpoint's `sends` and
+    /// `receives`: reporting never competes with domain messages for buffer space,
+    /// and the supervisor reads every endpoint's reports from one receiver, tagged with
+    /// the endpoint they came from. Otherwise it's a plain `send`, waiting for capacity
+    /// when the supervisor falls behind.
+    #[must_use = "a failed report drops the error, check the result"]
+    pub async fn report_error<ReporterMarker, E>(&self, error: E) -> Result<(), CommsError>
+    where
+        ReporterMarker: Any + Send + Sync + 'static,
+        E: ConcreteSenderTrait,
+    {
+        self.send::<ReporterMarker, E>(error).await
+    }
+
+    /// Takes the receiver of a link's error channel, yielding what every endpoint
+    /// declared with `errors:` reported, in the order they were reported.
+    #[track_caller]
+    pub fn take_error_receiver<SupervisorMarker, E>(
+        &self,
+    ) -> Result<mpsc::Receiver<ErrorReport<E>>, CommsError>
+    where
+        SupervisorMarker: Any + Send + Sync + 'static,
+        E: ConcreteSenderTrait,
+    {
+        self.take_receiver::<SupervisorMarker, ErrorReport<E>>()
+    }
+
+    /// Spawns a task sending `message`, returning right away with the task's handle,
+    /// which can be awaited later for the send's result, or dropped to fire and forget.
+    ///
+    /// The task holds a clone of the `Arc`, so the router has to be shared through one,
+    /// e.g. from [`into_shared`](Self::into_shared). The send waits on backpressure in
+    /// the task instead of in the caller. Each detached send runs as a task of its own,
+    /// so several of them on one pathway can land in any order; await each handle
+    /// before the next send where order matters, or use `send` instead. The task is
+    /// named `crosslink-send:{marker}`, see [Task Names](crate#task-names).
+    pub fn send_detached<SenderMarker, Msg>(
+        self: &Arc<Self>,
+        message: Msg,
+    ) -> JoinHandle<Result<(), CommsError>>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let router = Arc::clone(self);
+        let task = TaskName::new(None, "send", std::any::type_name::<SenderMarker>);
+        task.spawn(async move { router.send::<SenderMarker, Msg>(message).await })
+    }
+
+    /// Exposes the pathway of `SenderMarker` as a request/response service: each call
+    /// sends a [`Request`](crate::service::Request) carrying a fresh oneshot channel and
+    /// resolves with whatever the consumer responds on it.
+    ///
+    /// The pathway's message type must be `Request<Req, Resp>`. The service holds on to
+    /// the router, so it can be cloned and moved into other tasks freely; see
+    /// [`PathwayService`] for how concurrent calls behave.
+    pub fn as_service<SenderMarker, Req, Resp>(
+        self: &Arc<Self>,
+    ) -> PathwayService<SenderMarker, Req, Resp>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Req: ConcreteSenderTrait,
+        Resp: ConcreteReceiverTrait,
+    {
+        PathwayService::new(Arc::clone(self))
+    }
+
+    /// Sends an [`Acked`] message and waits for the consumer's reply.
+    async fn send_awaiting_reply<SenderMarker, Msg>(
+        &self,
+        message: Msg,
+    ) -> Result<Reply, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let (acked, ack_rx) = ack::acked(message);
+        let slot = self.typed_sender::<SenderMarker, Msg>()?;
+        slot.gate.opened().await;
+        let result = slot.sender.send_erased(Box::new(acked)).await;
+        slot.counters.record(&result);
+        result.map_err(|e| e.with_marker(TypeId::of::<SenderMarker>()))?;
+
+        ack_rx.await.map_err(|_| {
+            CommsError::NotAcked(format!(
+                "Message of type '{}' sent on '{}' was dropped without being acknowledged.",
+                std::any::type_name::<Msg>(),
+                std::any::type_name::<SenderMarker>()
+            ))
+        })
+    }
+
+    /// Puts a previously taken receiver back into its pathway's slot,
+    /// so it can be taken again.
+    pub fn return_receiver<ReceiverMarker, Msg>(
+        &self,
+        receiver: mpsc::Receiver<Msg>,
+    ) -> Result<(), CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteReceiverTrait,
+    {
+        self.return_receiver_with::<TokioBackend, ReceiverMarker, Msg>(receiver)
+    }
+
+    /// Prepares to move the consumer of `ReceiverMarker` from the task holding its
+    /// receiver to a new one, without losing or reordering buffered messages.
+    ///
+    /// Returns the new consumer's receiver, and a [`ConsumerHandoff`] for the old
+    /// consumer. The protocol goes:
+    /// 1. Start the new consumer on the returned receiver. It gets nothing yet.
+    /// 2. Signal the old consumer, passing it the handoff, e.g. over a oneshot.
+    /// 3. The old consumer finishes the message at hand, stops receiving and calls
+    ///    [`ConsumerHandoff::complete`] with its receiver.
+    ///
+    /// An mpsc receiver can't be split, so a relay task takes the old receiver over and
+    /// forwards everything into the new one, buffered or sent later, in order. Senders
+    /// keep pushing into the old channel all along, so nothing is lost in between.
+    /// Each message reaches exactly one of the two consumers, but both are running
+    /// between steps 1 and 3. An old consumer that calls `complete` before it's done
+    /// with its last message keeps working on it while the new one starts on the next,
+    /// so processing of consecutive messages overlaps.
+    ///
+    /// The relay adds one message of buffering on top of the pathway's, and a hop per
+    /// message, for as long as the pathway lives. `Msg` is what the channel carries, as
+    /// for `take_receiver`. The relay task is named `crosslink-handoff:{marker}`, see
+    /// [Task Names](crate#task-names).
+    ///
+    /// ```
+    /// # use std::{error::Error, sync::atomic::{AtomicU32, Ordering}, time::Duration};
+    /// # use crosslink::{Router, define_crosslink, handoff::ConsumerHandoff};
+    /// # use tokio::sync::mpsc;
+    /// # #[derive(Debug)]
+    /// # pub struct Job(u32);
+    /// # define_crosslink! {
+    /// #     link_id: "JobLink",
+    /// #     Dispatcher { sends: Job, receives: () },
+    /// #     Worker { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # use job_link::{DispatcherSend, WorkerRecv};
+    /// # #[derive(Debug)]
+    /// # enum Control { HandOff(ConsumerHandoff<Job>) }
+    /// # static LAST_HANDLED: AtomicU32 = AtomicU32::new(0);
+    /// # async fn old_worker(mut jobs: mpsc::Receiver<Job>, mut control: mpsc::Receiver<Control>) {
+    /// #     loop {
+    /// #         tokio::select! {
+    /// #             biased;
+    /// #             Some(Control::HandOff(handoff)) = control.recv() => return handoff.complete(jobs),
+    /// #             Some(_) = jobs.recv() => {}
+    /// #         }
+    /// #     }
+    /// # }
+    /// # async fn new_worker(mut jobs: mpsc::Receiver<Job>) {
+    /// #     while let Some(Job(id)) = jobs.recv().await {
+    /// #         LAST_HANDLED.store(id, Ordering::SeqCst);
+    /// #     }
+    /// # }
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// # let mut router = Router::new();
+    /// # job_link::setup_job_link(&mut router, None);
+    /// # let (old_worker_control, control) = mpsc::channel(1);
+    /// # tokio::spawn(old_worker(router.take_receiver::<WorkerRecv, Job>()?, control));
+    /// let (handoff, receiver) = router.prepare_handoff::<WorkerRecv, Job>()?;
+    /// tokio::spawn(new_worker(receiver));
+    /// old_worker_control.send(Control::HandOff(handoff)).await?;
+    /// # router.send::<DispatcherSend, _>(Job(7)).await?;
+    /// # while LAST_HANDLED.load(Ordering::SeqCst) != 7 {
+    /// #     tokio::time::sleep(Duration::from_millis(1)).await;
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Fails if the receiver wasn't taken, since whoever takes it next can simply take
+    /// over, or if the slot holds another message type.
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime, since it spawns the relay.
+    #[track_caller]
+    pub fn prepare_handoff<ReceiverMarker, Msg>(
+        &self,
+    ) -> Result<(ConsumerHandoff<Msg>, mpsc::Receiver<Msg>), CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteReceiverTrait,
+    {
+        let marker_name = std::any::type_name::<ReceiverMarker>();
+        let slot = self
+            .typed_receivers
+            .get(&TypeId::of::<ReceiverMarker>())
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!("No receiver registered for marker '{}'.", marker_name),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                )
+            })?;
+        if slot.msg_type_id != TypeId::of::<Msg>() {
+            return Err(CommsError::TypeMismatch(
+                format!(
+                    "Receiver '{}' carries '{}', not '{}'.",
+                    marker_name,
+                    slot.msg_type_name,
+                    std::any::type_name::<Msg>()
+                ),
+                Some(TypeId::of::<ReceiverMarker>()),
+            ));
+        }
+        if slot.lock().is_some() {
+            return Err(CommsError::RecvFailed(format!(
+                "Receiver '{}' was never taken, there's no consumer to hand off from.",
+                marker_name
+            )));
+        }
+
+        let (old_tx, old_rx) = tokio::sync::oneshot::channel();
+        let (new_tx, new_rx) = mpsc::channel(1);
+        let task = TaskName::new(None, "handoff", || marker_name);
+        task.spawn(handoff::relay(old_rx, new_tx));
+        Ok((ConsumerHandoff::new(old_tx), new_rx))
+    }
+
+    /// Starts building a [`WeightedReceiver`](crate::fan_in::WeightedReceiver) merging
+    /// the receivers of several pathways carrying `Msg`, e.g. one link per producer into
+    /// a single consumer, with a weight per source:
+    ///
+    /// ```
+    /// # use crosslink::{CommsError, Router, define_crosslink};
+    /// # #[derive(Debug, PartialEq)]
+    /// # pub struct Job(u32);
+    /// # pub struct Consumer;
+    /// # define_crosslink! {
+    /// #     link_id: "UrgentLink",
+    /// #     Producer { sends: Job, receives: () },
+    /// #     Consumer { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # define_crosslink! {
+    /// #     link_id: "BulkLink",
+    /// #     Producer { sends: Job, receives: () },
+    /// #     Consumer { sends: (), receives: Job },
+    /// #     buffer_size: 4,
+    /// #     reexport_markers: true,
+    /// # }
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), CommsError> {
+    /// # let mut router = Router::new();
+    /// # urgent_link::setup_urgent_link(&mut router, None);
+    /// # bulk_link::setup_bulk_link(&mut router, None);
+    /// # router.try_send::<urgent_link::ProducerSend, _>(Job(1))?;
+    /// let mut inbox = router
+    ///     .fan_in::<Consumer, Job>()
+    ///     .source::<urgent_link::ConsumerRecv>(3)?
+    ///     .source::<bulk_link::ConsumerRecv>(1)?
+    ///     .build();
+    /// # assert_eq!(inbox.recv().await, Some(Job(1)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// `ConsumerMarker` only names the merged receiver in its `Debug` output, and needn't
+    /// be registered. See `WeightedReceiver` for how sources are scheduled.
+    pub fn fan_in<ConsumerMarker, Msg>(&self) -> FanInBuilder<'_, Msg>
+    where
+        ConsumerMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        FanInBuilder::new(self, std::any::type_name::<ConsumerMarker>())
+    }
+
+    /// Takes two receivers at once, or neither.
+    ///
+    /// If the second take fails, the first receiver is returned to the router
+    /// before the error is reported, so a task never ends up owning half of its inputs.
+    /// The error is always the one from the second take: putting the first receiver
+    /// back into the slot it was just taken from can't find the slot missing or full.
+    #[track_caller]
+    pub fn take_receivers<M1, T1, M2, T2>(
+        &self,
+    ) -> Result<(mpsc::Receiver<T1>, mpsc::Receiver<T2>), CommsError>
+    where
+        M1: Any + Send + Sync + 'static,
+        T1: Send + 'static + Debug + Sync,
+        M2: Any + Send + Sync + 'static,
+        T2: Send + 'static + Debug + Sync,
+    {
+        let first = self.take_receiver::<M1, T1>()?;
+        match self.take_receiver::<M2, T2>() {
+            Ok(second) => Ok((first, second)),
+            Err(e) => {
+                let _ = self.return_receiver::<M1, T1>(first);
+                Err(e)
+            }
+        }
+    }
+
+    /// Copies out the messages currently buffered in a pathway, for debugging.
+    ///
+    /// `mpsc` can't be inspected in place, so this is best-effort: the receiver is
+    /// briefly taken, drained with `try_recv`, and every message is cloned and pushed
+    /// back through the pathway's own sender, in order.
+    /// While that happens the channel is disturbed: messages sent concurrently by
+    /// producers may land in between. Any that can't be pushed back because the buffer
+    /// filled up meanwhile are skipped, and the rest still pushed, so the snapshot
+    /// hands them back in [`PendingSnapshot::unsent`] rather than failing.
+    ///
+    /// Only works while the receiver is still held by the router, so a consumer that
+    /// already took it has to return it first. Meant for development builds only.
+    pub fn snapshot_pending<SenderMarker, ReceiverMarker, Msg>(
+        &self,
+    ) -> Result<PendingSnapshot<Msg>, CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait + Clone,
+    {
+        self.pathway_link::<SenderMarker, ReceiverMarker>()?;
+        let slot = self.typed_sender::<SenderMarker, Msg>()?;
+        let mut receiver = self.take_receiver::<ReceiverMarker, Msg>()?;
+
+        let mut messages = Vec::with_capacity(receiver.len());
+        while let Ok(msg) = receiver.try_recv() {
+            messages.push(msg);
+        }
+        self.return_receiver::<ReceiverMarker, Msg>(receiver)?;
+
+        let unsent = messages
+            .iter()
+            .filter(|msg| {
+                slot.sender
+                    .try_send_erased(Box::new((*msg).clone()))
+                    .is_err()
+            })
+            .cloned()
+            .collect();
+        Ok(PendingSnapshot { messages, unsent })
+    }
+
+    /// Resizes a pathway's buffer to `new_size`, keeping the messages it holds.
+    ///
+    /// Channels can't be resized in place, so this builds a new one and moves every
+    /// buffered message over with `try_recv`/`try_send`, in order, before swapping both
+    /// halves in. It takes `&mut self`, so nothing can send or take the receiver through
+    /// the router halfway through. Send counters and the pause state carry over.
+    ///
+    /// The receiver must still be held by the router: one taken by a consumer can't be
+    /// drained, and this fails as `take_receiver` would. It also fails, leaving the
+    /// pathway untouched, if more than `new_size` messages are buffered. Only pathways
+    /// carrying `Msg` itself, plain or spillover, can be resized, not e.g. acked or
+    /// instrumented ones, and spillover ones only while their overflow queue is empty.
+    /// As with [`reset_pathway`](Self::reset_pathway), sender clones held outside
+    /// the router, e.g. by `pipe` tasks, keep pointing at the old channel, and what they
+    /// send after the swap is lost.
+    ///
+    /// # Panics
+    /// Panics if `new_size` is 0, same as `tokio::sync::mpsc::channel`.
+    pub fn reconfigure_buffer<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        new_size: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let link = self.pathway_link::<SenderMarker, ReceiverMarker>()?;
+        if link.carried_type.0 != TypeId::of::<Msg>() {
+            return Err(CommsError::TypeMismatch(
+                format!(
+                    "Pathway '{}' carries '{}', only plain pathways of '{}' can be resized.",
+                    std::any::type_name::<SenderMarker>(),
+                    link.carried_type.1,
+                    std::any::type_name::<Msg>()
+                ),
+                Some(TypeId::of::<SenderMarker>()),
+            ));
+        }
+        self.typed_sender::<SenderMarker, Msg>()?;
+        if let Some(spilled) = self
+            .overflow_depth::<SenderMarker>()
+            .filter(|&spilled| spilled > 0)
+        {
+            return Err(CommsError::ChannelFull(format!(
+                "Pathway '{}' has {} messages waiting in its overflow queue.",
+                std::any::type_name::<SenderMarker>(),
+                spilled
+            )));
+        }
+
+        let mut old_receiver = self.take_receiver::<ReceiverMarker, Msg>()?;
+        if old_receiver.len() > new_size {
+            let buffered = old_receiver.len();
+            self.return_receiver::<ReceiverMarker, Msg>(old_receiver)?;
+            return Err(CommsError::ChannelFull(format!(
+                "Pathway '{}' holds {} messages, more than the new buffer size of {}.",
+                std::any::type_name::<SenderMarker>(),
+                buffered,
+                new_size
+            )));
+        }
+
+        let mut buffered = Vec::with_capacity(old_receiver.len());
+        while let Ok(msg) = old_receiver.try_recv() {
+            buffered.push(msg);
+        }
+        let old = RemovedPathway {
+            sender: self.typed_senders.remove(&TypeId::of::<SenderMarker>()),
+            receiver: self.typed_receivers.remove(&TypeId::of::<ReceiverMarker>()),
+            link,
+            overflow_depth: self
+                .overflow_depths
+                .get(&TypeId::of::<SenderMarker>())
+                .cloned(),
+        };
+        if let Err(e) = (link.rebuild)(self, new_size) {
+            self.restore_pathway::<SenderMarker, ReceiverMarker, Msg>(old, old_receiver, buffered);
+            return Err(e);
+        }
+
+        let Some(slot) = self.typed_senders.get_mut(&TypeId::of::<SenderMarker>()) else {
+            self.restore_pathway::<SenderMarker, ReceiverMarker, Msg>(old, old_receiver, buffered);
+            return Err(CommsError::InternalInconsistency(format!(
+                "Pathway '{}' wasn't rebuilt.",
+                std::any::type_name::<SenderMarker>()
+            )));
+        };
+        if let Some(old_sender) = &old.sender {
+            slot.counters = Arc::clone(&old_sender.counters);
+            slot.gate = Arc::clone(&old_sender.gate);
+        }
+        let mut pending = buffered.into_iter();
+        while let Some(msg) = pending.next() {
+            let Err(undelivered) = slot.sender.try_send_returning(Box::new(msg)) else {
+                continue;
+            };
+            // Takes back what already made it into the new channel, then the rest, so
+            // the old one gets everything again, in order.
+            let mut messages: Vec<Msg> = match self.take_receiver::<ReceiverMarker, Msg>() {
+                Ok(mut moved) => std::iter::from_fn(|| moved.try_recv().ok()).collect(),
+                Err(_) => Vec::new(),
+            };
+            messages.extend(
+                undelivered
+                    .message
+                    .and_then(|msg| msg.downcast::<Msg>().ok())
+                    .map(|msg| *msg),
+            );
+            messages.extend(pending);
+            self.restore_pathway::<SenderMarker, ReceiverMarker, Msg>(old, old_receiver, messages);
+            return Err(undelivered.error);
+        }
+        Ok(())
+    }
+
+    /// Puts back a pathway `reconfigure_buffer` took apart, with `messages` pushed back
+    /// into its old channel, after a failed rebuild.
+    fn restore_pathway<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        old: RemovedPathway,
+        receiver: mpsc::Receiver<Msg>,
+        messages: Vec<Msg>,
+    ) where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let (sender_marker, receiver_marker) =
+            (TypeId::of::<SenderMarker>(), TypeId::of::<ReceiverMarker>());
+        self.typed_senders.remove(&sender_marker);
+        self.typed_receivers.remove(&receiver_marker);
+        self.overflow_depths.remove(&sender_marker);
+
+        if let Some(sender) = old.sender {
+            // The old channel held all of them before, and nothing took its place since.
+            for msg in messages {
+                let _ = sender.sender.try_send_erased(Box::new(msg));
+            }
+            self.typed_senders.insert(sender_marker, sender);
+        }
+        if let Some(slot) = old.receiver {
+            self.typed_receivers.insert(receiver_marker, slot);
+            let _ = self.return_receiver::<ReceiverMarker, Msg>(receiver);
+        }
+        if let Some(depth) = old.overflow_depth {
+            self.overflow_depths.insert(sender_marker, depth);
+        }
+        self.pathway_links.insert(sender_marker, old.link);
+    }
+
+    /// Registers both halves of a rendezvous pathway.
+    ///
+    /// There's no buffering: `send` only resolves once the consumer has taken the
+    /// message with the [`RendezvousReceiver`] from `take_rendezvous_receiver`, giving
+    /// synchronous-style handoffs.
+    ///
+    /// Beware of deadlocks: two endpoints that both `send` to each other before
+    /// receiving will wait on each other forever.
+    /// `try_send` always fails on these pathways, since it can't wait for the handoff.
+    pub fn __internal_register_rendezvous<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        _buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        // A single slot: the sender waits on the handoff anyway,
+        // so nothing ever queues behind the message in flight.
+        let (sender, receiver) = mpsc::channel::<Handoff<Msg>>(1);
+        self.typed_senders.insert(
+            TypeId::of::<SenderMarker>(),
+            SenderSlot::new::<Msg>(
+                std::any::type_name::<SenderMarker>(),
+                Box::new(RendezvousSender::<TokioBackend, Msg> { sender }),
+            ),
+        );
+        self.register_receiver_with::<TokioBackend, ReceiverMarker, Handoff<Msg>>(receiver)?;
+        self.link_pathway::<SenderMarker, ReceiverMarker, Msg, Handoff<Msg>>(
+            0,
+            Self::__internal_register_rendezvous::<SenderMarker, ReceiverMarker, Msg>,
+        );
+
+        Ok(())
+    }
+
+    /// Takes the receiver of a rendezvous pathway.
+    #[track_caller]
+    pub fn take_rendezvous_receiver<ReceiverMarker, Msg>(
+        &self,
+    ) -> Result<RendezvousReceiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let receiver = self.take_receiver::<ReceiverMarker, Handoff<Msg>>()?;
+        Ok(RendezvousReceiver::new(receiver))
+    }
+
+    /// Registers both halves of a watch pathway, holding `Msg::default()` until the
+    /// first send.
+    ///
+    /// There's no queue: each send replaces the current value, and watchers only ever
+    /// see the latest one. See [Watch Links](crate#watch-links).
+    pub fn __internal_register_watch<SenderMarker, ReceiverMarker, Msg>(
+        &mut self,
+        _buffer: usize,
+    ) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait + Default,
+    {
+        self.ensure_sender_vacant::<SenderMarker>()?;
+        self.ensure_receiver_vacant::<ReceiverMarker>()?;
+
+        let (sender, receiver) = watch::channel(Msg::default());
+        self.typed_senders.insert(
+            TypeId::of::<SenderMarker>(),
+            SenderSlot::new::<Msg>(
+                std::any::type_name::<SenderMarker>(),
+                Box::new(WatchSender { sender }),
+            ),
+        );
+        // Keyed as carrying the receiver itself, so `take_receiver` can't mistake it
+        // for a channel of `Msg`s.
+        self.typed_receivers.insert(
+            TypeId::of::<ReceiverMarker>(),
+            ReceiverSlot::new::<watch::Receiver<Msg>>(
+                std::any::type_name::<ReceiverMarker>(),
+                Box::new(WatchReceiver { receiver }),
+            ),
+        );
+        self.link_pathway::<SenderMarker, ReceiverMarker, Msg, watch::Receiver<Msg>>(
+            1,
+            Self::__internal_register_watch::<SenderMarker, ReceiverMarker, Msg>,
+        );
+
+        Ok(())
+    }
+
+    /// Returns a receiver watching the latest value sent on a watch pathway.
+    ///
+    /// Unlike `take_receiver`, this can be called any number of times: each call hands
+    /// out a new watcher, and the router keeps its own. A new watcher considers the
+    /// current value seen, so its `changed()` only resolves on the next send, while
+    /// `borrow()` returns the current value straight away.
+    pub fn watch_receiver<ReceiverMarker, Msg>(&self) -> Result<watch::Receiver<Msg>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        let marker_name = std::any::type_name::<ReceiverMarker>();
+        let slot = self
+            .typed_receivers
+            .get(&TypeId::of::<ReceiverMarker>())
+            .ok_or_else(|| {
+                CommsError::PathwayNotFound(
+                    format!("No receiver registered for marker '{}'.", marker_name),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                )
+            })?;
+
+        slot.lock()
+            .as_mut()
+            .and_then(|receiver| {
+                receiver
+                    .as_any_mut()
+                    .downcast_mut::<WatchReceiver<Msg>>()
+                    .map(|watch| watch.receiver.clone())
+            })
+            .ok_or_else(|| {
+                CommsError::TypeMismatch(
+                    format!(
+                        "Receiver '{}' isn't a watch pathway of '{}'.",
+                        marker_name,
+                        std::any::type_name::<Msg>()
+                    ),
+                    Some(TypeId::of::<ReceiverMarker>()),
+                )
+            })
+    }
+
+    /// Replaces the value of a watch pathway, waking every watcher.
+    ///
+    /// Never waits: a watcher that hasn't looked at the previous value yet skips it.
+    /// Same as `try_send` on the pathway, so it fails with `CommsError::ChannelFull`
+    /// while the pathway is paused.
+    pub fn update_watch<SenderMarker, Msg>(&self, value: Msg) -> Result<(), CommsError>
+    where
+        SenderMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteSenderTrait,
+    {
+        self.try_send::<SenderMarker, Msg>(value)
+    }
+
+    /// Puts `receiver` in the slot for `ReceiverMarker`, replacing whatever was
+    /// registered there, and returns the previous receiver if it was still in the router.
+    ///
+    /// Meant for injecting test doubles: code under test taking the receiver gets the
+    /// one passed here, fed by a sender the test keeps. This intentionally bypasses the
+    /// conflict check of regular registration. The pathway's own sender keeps
+    /// feeding the old channel.
+    ///
+    /// Fails if the marker is already registered for a different message type.
+    #[cfg(feature = "testing")]
+    pub fn override_receiver<ReceiverMarker, Msg>(
+        &mut self,
+        receiver: mpsc::Receiver<Msg>,
+    ) -> Result<Option<mpsc::Receiver<Msg>>, CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: Send + 'static + Debug + Sync,
+    {
+        let marker_type_id = TypeId::of::<ReceiverMarker>();
+        let previous = match self.typed_receivers.get(&marker_type_id) {
+            None => None,
+            Some(slot) if slot.msg_type_id != TypeId::of::<Msg>() => {
+                return Err(CommsError::TypeMismatch(
+                    format!(
+                        "Receiver '{}' is registered for a type other than '{}'.",
+                        std::any::type_name::<ReceiverMarker>(),
+                        std::any::type_name::<Msg>(),
+                    ),
+                    Some(marker_type_id),
+                ));
+            }
+            // `.ok()` swallows the receiver having been taken already, so there's nothing
+            // to hand back, and it not being a Tokio receiver, which goes down with the slot.
+            Some(_) => self.take_receiver::<ReceiverMarker, Msg>().ok(),
+        };
+
+        self.typed_receivers.remove(&marker_type_id);
+        self.register_receiver_with::<TokioBackend, ReceiverMarker, Msg>(receiver)?;
+        Ok(previous)
+    }
+
+    /// Injects faults into the pathway of `ReceiverMarker`, for testing how its consumer
+    /// copes with latency, lost messages and duplicates.
+    ///
+    /// Takes the pathway's receiver and puts a new one of the same capacity in its
+    /// place, fed by a task that forwards every message with the faults of `config`,
+    /// so a consumer calling `take_receiver` afterwards gets the faulty stream without
+    /// any change to its code. With `config.seed` set, which messages are dropped and
+    /// duplicated is the same on every run; only the timing of the task varies.
+    ///
+    /// Test-only, like everything behind the `testing` feature: production pathways
+    /// should never see it. Call it after setup and before the consumer takes its
+    /// receiver. Only plain pathways carrying `Msg` can be wrapped, and wrapping twice
+    /// stacks the faults.
+    ///
+    /// # Panics
+    /// Panics if a probability isn't between 0 and 1, or if called outside of a Tokio
+    /// runtime, since it spawns the forwarding task.
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    pub fn chaos<ReceiverMarker, Msg>(&mut self, config: ChaosConfig) -> Result<(), CommsError>
+    where
+        ReceiverMarker: Any + Send + Sync + 'static,
+        Msg: ConcreteReceiverTrait + Sync + Clone,
+    {
+        assert!(
+            (0.0..=1.0).contains(&config.drop_prob) && (0.0..=1.0).contains(&config.dup_prob),
+            "chaos probabilities must be between 0 and 1"
+        );
+
+        let upstream = self.take_receiver::<ReceiverMarker, Msg>()?;
+        let (downstream, receiver) = mpsc::channel(upstream.max_capacity());
+        self.return_receiver::<ReceiverMarker, Msg>(receiver)?;
+
+        let task = TaskName::new(None, "chaos", std::any::type_name::<ReceiverMarker>);
+        task.spawn(chaos::inject(upstream, downstream, config));
+        Ok(())
+    }
+
+    /// Attaches a tap receiving a copy of every message successfully sent through
+    /// `send` or `try_send`, on any pathway, e.g. for a central audit logger.
+    ///
+    /// Taps are off by default. Once one is attached, every send renders its message
+    /// with `Debug` into a new `String`, boxes it, and pushes it onto the tap channel,
+    /// which holds up to
+    /// [`TAP_BUFFER`](crate::tap::TAP_BUFFER) copies; further copies are dropped rather
+    /// than slowing the pathways down. Attaching a new tap replaces the previous one.
+    pub fn tap(&mut self) -> mpsc::Receiver<TappedMessage> {
+        let (tap, receiver) = Tap::new();
+        self.hooks.tap = Some(tap);
+        receiver
+    }
+
+    /// Sets the dead-letter channel for messages of type `Msg`, replacing any
+    /// previous one.
+    ///
+    /// From then on, a `send` or `try_send` of a `Msg`, on any pathway, failing because
+    /// the receiving side is gone pushes the message into `sender` before returning
+    /// the usual `SendFailed`. Other failures, such as `try_send` on a full channel,
+    /// drop the message, as they do without a dead-letter channel.
+    ///
+    /// Dead-lettering never waits: if `sender`'s channel is full or closed, the
+    /// message is dropped. Size it for bursts, e.g. a consumer dying under load.
+    /// Messages sent with `send_acked`, messages on rendezvous pathways, and messages
+    /// lost by relays are not dead-lettered.
+    pub fn set_dead_letter<Msg>(&mut self, sender: mpsc::Sender<Msg>)
+    where
+        Msg: ConcreteSenderTrait,
+    {
+        self.hooks.dead_letters.set(sender);
+    }
+
+    /// Shuts the router down once its consumers have worked through their buffers,
+    /// giving up after `timeout`.
+    ///
+    /// Every sender is removed from the router right away, so nothing new can be sent
+    /// through it. The buffered messages of every pathway are then polled every
+    /// [`DRAIN_POLL_INTERVAL`] until all are empty or `timeout` elapses, and the removed
+    /// senders are dropped either way. Consumers then see their channel closed, once
+    /// any relay or external source feeding the same pathway, which hold their own
+    /// senders, has stopped too.
+    ///
+    /// Fails with `CommsError::Timeout`, naming the pathways still holding messages and
+    /// how many, if they didn't drain in time: those messages are left to whoever still
+    /// holds the receivers. Receivers the router still holds are never drained, so
+    /// their pathways time out unless empty. Pathways on backends that can't count
+    /// their buffered messages from the sending side are treated as drained.
+    pub async fn drain_and_shutdown(&mut self, timeout: Duration) -> Result<(), CommsError> {
+        let mut senders: Vec<SenderSlot> =
+            self.typed_senders.drain().map(|(_, slot)| slot).collect();
+        for (_, shards) in self.shards.drain() {
+            // Both markers map to the same shards, only the last reference owns them.
+            if let Some(shards) = Arc::into_inner(shards) {
+                senders.extend(shards.senders);
+            }
+        }
+
+        let still_buffered = |senders: &[SenderSlot]| -> Vec<(&'static str, usize)> {
+            senders
+                .iter()
+                .filter_map(|slot| Some((slot.marker_name, slot.sender.buffered()?)))
+                .filter(|(_, buffered)| *buffered > 0)
+                .collect()
+        };
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut buffered = still_buffered(&senders);
+        while !buffered.is_empty() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep_until(
+                deadline.min(tokio::time::Instant::now() + DRAIN_POLL_INTERVAL),
+            )
+            .await;
+            buffered = still_buffered(&senders);
+        }
+
+        if buffered.is_empty() {
+            return Ok(());
+        }
+
+        // Shards of one pathway share a name: add their counts up.
+        let mut by_pathway: Vec<(&'static str, usize)> = Vec::new();
+        for (name, count) in buffered {
+            match by_pathway.iter_mut().find(|(known, _)| *known == name) {
+                Some((_, total)) => *total += count,
+                None => by_pathway.push((name, count)),
+            }
+        }
+        by_pathway.sort_unstable();
+        let pending: Vec<String> = by_pathway
+            .iter()
+            .map(|(name, count)| format!("'{}' ({})", name, count))
+            .collect();
+        Err(CommsError::Timeout(format!(
+            "Pathways still held messages after {:?}: {}.",
+            timeout,
+            pending.join(", ")
+        )))
+    }
+}
+
+/// Samples a receive loop's buffer right after it took a message out, which still
+/// counts as buffered.
+#[cfg(feature = "tokio")]
+fn sample_high_water_mark<T>(peak: Option<&HighWaterMark>, receiver: &mpsc::Receiver<T>) {
+    if let Some(peak) = peak {
+        peak.observe(receiver.len() + 1, receiver.max_capacity());
+    }
+}
+
+/// How often [`Router::drain_and_shutdown`] checks whether pathways have drained.
+#[cfg(feature = "tokio")]
+pub const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How many messages [`Router::send_yielding`] sends between two yields by default.
+#[cfg(feature = "tokio")]
+pub const SEND_YIELD_INTERVAL: usize = 64;
+
+/// Name given to a task spawned by one of the router's helpers, e.g. for `tokio-console`.
+///
+/// Tokio only supports naming tasks under `--cfg tokio_unstable` with its `tracing`
+/// feature, turned on by ours. Elsewhere names are dropped and tasks spawned as usual.
+#[cfg(feature = "tokio")]
+struct TaskName {
+    #[cfg(all(tokio_unstable, feature = "tracing"))]
+    name: String,
+}
+
+#[cfg(feature = "tokio")]
+impl TaskName {
+    /// Uses `name` if given, `crosslink-{kind}:{pathway}` otherwise.
+    #[cfg(all(tokio_unstable, feature = "tracing"))]
+    fn new<D: std::fmt::Display>(
+        name: Option<&str>,
+        kind: &str,
+        pathway: impl FnOnce() -> D,
+    ) -> Self {
+        Self {
+            name: name.map_or_else(|| format!("crosslink-{kind}:{}", pathway()), str::to_owned),
+        }
+    }
+
+    #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+    fn new<D: std::fmt::Display>(_: Option<&str>, _: &str, _: impl FnOnce() -> D) -> Self {
+        Self {}
+    }
+
+    #[cfg(all(tokio_unstable, feature = "tracing"))]
+    fn spawn<F>(self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        // `Builder::spawn` drops the future if it fails, so it's handed over through a
+        // slot instead, still there for a plain `tokio::spawn` in that case.
+        let slot = Arc::new(std::sync::Mutex::new(Some(future)));
+        let run = |slot: Arc<std::sync::Mutex<Option<F>>>| async move {
+            let future = slot.lock().ok().and_then(|mut future| future.take());
+            match future {
+                Some(future) => future.await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::task::Builder::new()
+            .name(&self.name)
+            .spawn(run(Arc::clone(&slot)))
+            .unwrap_or_else(|_| tokio::spawn(run(slot)))
+    }
+
+    #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+    fn spawn<F>(self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        tokio::spawn(future)
     }
 }
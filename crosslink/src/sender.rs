@@ -2,32 +2,246 @@ use std::{
     any::{Any, TypeId},
     fmt::Debug,
     pin::Pin,
+    sync::Arc,
 };
 
-use tokio::sync::mpsc;
+use crate::{
+    backend::{ChannelBackend, TrySendError},
+    error::CommsError,
+    gate::Gate,
+    stats::PathwayCounters,
+};
 
-use crate::error::CommsError;
+/// A pending [`DynSender::reserve_owned_erased`], resolving to the boxed permit.
+#[cfg(feature = "tokio")]
+pub(crate) type Reservation =
+    Pin<Box<dyn Future<Output = Result<Box<dyn Any + Send>, CommsError>> + Send>>;
 
-pub trait DynSender: Send + Sync + Debug {
+/// The type-erased sending half of a pathway, as stored in the router.
+///
+/// Internal: pathways are only ever built by the router's own `register_*` methods.
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) trait DynSender: Send + Sync + Debug {
     fn send_erased(
         &self,
         msg: Box<dyn Any + Send>,
     ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>>;
+    /// Sends without waiting for capacity, and without allocating a future.
+    fn try_send_erased(&self, msg: Box<dyn Any + Send>) -> Result<(), CommsError>;
     fn accepts_message_type_id(&self) -> TypeId;
     fn message_type_name(&self) -> &'static str;
+    /// Clones the underlying channel sender into a new, independently owned box.
+    fn clone_box(&self) -> Box<dyn DynSender>;
+
+    /// Number of messages buffered in the channel, if the sender can tell.
+    fn buffered(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether the receiving side of the channel is gone, if the sender can tell.
+    fn is_closed(&self) -> Option<bool> {
+        None
+    }
+
+    /// Like [`send_erased`](Self::send_erased), but hands the message back when the
+    /// channel is closed, e.g. to dead-letter it.
+    ///
+    /// By default the message is lost, as with `send_erased`.
+    fn send_returning(
+        &self,
+        msg: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Undelivered>> + Send>> {
+        let send = self.send_erased(msg);
+        Box::pin(async move { send.await.map_err(Undelivered::lost) })
+    }
+
+    /// Like [`try_send_erased`](Self::try_send_erased), but hands the message back when
+    /// the channel is closed or full.
+    fn try_send_returning(&self, msg: Box<dyn Any + Send>) -> Result<(), Undelivered> {
+        self.try_send_erased(msg).map_err(Undelivered::lost)
+    }
+
+    /// Reserves capacity for one message, as a boxed `tokio::sync::mpsc::OwnedPermit`
+    /// for the message type the sender accepts.
+    ///
+    /// Only senders pushing that type into a Tokio channel as is can reserve; by
+    /// default, the reservation is refused.
+    #[cfg(feature = "tokio")]
+    fn reserve_owned_erased(&self) -> Reservation {
+        let error = reserve_unsupported(self.message_type_name());
+        Box::pin(async { Err(error) })
+    }
+
+    /// The backend's own sender, to be downcast to its concrete type, if it takes the
+    /// accepted message type as is. By default, there's no such sender.
+    #[cfg(feature = "tokio")]
+    fn raw_sender(&self) -> Option<&dyn Any> {
+        None
+    }
 }
 
-/// Just a type alias with the required trait bounds.
-/// and a blanket impl for any `T`
-pub trait ConcreteSenderTrait: Send + Sync + 'static + std::fmt::Debug + Clone {}
-impl<T: Send + Sync + 'static + std::fmt::Debug + Clone> ConcreteSenderTrait for T {}
+/// A failed send, along with the message when the sender could hand it back.
+#[derive(Debug)]
+pub struct Undelivered {
+    pub error: CommsError,
+    pub message: Option<Box<dyn Any + Send>>,
+}
+
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+impl Undelivered {
+    pub(crate) fn lost(error: CommsError) -> Self {
+        Self {
+            error,
+            message: None,
+        }
+    }
 
+    pub(crate) fn closed<T: Send + 'static>(message: T) -> Self {
+        Self {
+            error: closed_error::<T>(),
+            message: Some(Box::new(message)),
+        }
+    }
+
+    pub(crate) fn full<T: Send + 'static>(message: T) -> Self {
+        Self {
+            error: try_send_error::<T, ()>(TrySendError::Full(())),
+            message: Some(Box::new(message)),
+        }
+    }
+}
+
+/// Renders a type-erased message with its `Debug` impl, or returns `None` if it isn't
+/// of the type the renderer was made for.
+///
+/// See [`Router::debug_renderer`](crate::Router::debug_renderer).
+pub type DebugRenderer = fn(&dyn Any) -> Option<String>;
+
+fn render_debug<T: Debug + 'static>(message: &dyn Any) -> Option<String> {
+    message
+        .downcast_ref::<T>()
+        .map(|message| format!("{:?}", message))
+}
+
+/// A registered sender, with the counters and pause gate of the pathway it feeds,
+/// and the renderer for the messages it accepts.
 #[derive(Debug)]
-pub(crate) struct ConcreteSender<T: ConcreteSenderTrait> {
-    pub sender: mpsc::Sender<T>,
+pub(crate) struct SenderSlot {
+    pub marker_name: &'static str,
+    pub sender: Box<dyn DynSender>,
+    pub counters: Arc<PathwayCounters>,
+    pub gate: Arc<Gate>,
+    pub render: DebugRenderer,
 }
 
-impl<T: ConcreteSenderTrait> DynSender for ConcreteSender<T> {
+impl SenderSlot {
+    /// `Msg` is what `send` takes on the pathway, e.g. `Msg` rather than `Envelope<Msg>`.
+    pub fn new<Msg: ConcreteSenderTrait>(
+        marker_name: &'static str,
+        sender: Box<dyn DynSender>,
+    ) -> Self {
+        Self {
+            marker_name,
+            sender,
+            counters: Arc::default(),
+            gate: Arc::default(),
+            render: render_debug::<Msg>,
+        }
+    }
+
+    /// Another slot feeding the same channel, sharing this one's counters and gate.
+    pub fn share(&self) -> Self {
+        Self {
+            marker_name: self.marker_name,
+            sender: self.sender.clone_box(),
+            counters: Arc::clone(&self.counters),
+            gate: Arc::clone(&self.gate),
+            render: self.render,
+        }
+    }
+}
+
+/// The bounds a message type must satisfy to be sent.
+///
+/// This is a public bound, not an extension point: the blanket impl covers every type
+/// meeting the bounds, so it can't be implemented by hand, and never needs to be.
+/// Deriving `Debug` on an owned, thread-safe type is all it takes to send it over a
+/// link. [`ConcreteReceiverTrait`](crate::receiver::ConcreteReceiverTrait) is its
+/// receiving counterpart.
+/// Messages are moved through pathways and never cloned, so move-only types, such as
+/// requests carrying a `oneshot::Sender` for the reply, work on every kind of link.
+///
+/// Only a few operations ask for more:
+///
+/// | Operation                      | Message bounds                     |
+/// |--------------------------------|------------------------------------|
+/// | `send`, `try_send`, relays     | `ConcreteSenderTrait`              |
+/// | `send_acked`, rendezvous links | `ConcreteSenderTrait`              |
+/// | `Router::tap`                  | `ConcreteSenderTrait` (via `Debug`) |
+/// | `Router::snapshot_pending`     | `ConcreteSenderTrait + Clone`      |
+pub trait ConcreteSenderTrait: Send + Sync + 'static + std::fmt::Debug {}
+impl<T: Send + Sync + 'static + std::fmt::Debug> ConcreteSenderTrait for T {}
+
+/// Implemented by a type only for itself, so that `define_crosslink!` can have the
+/// compiler check each endpoint receives what the other one sends, aliases included,
+/// and explain a mismatch in terms of the link.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "an endpoint receives `{Self}`, but its peer sends `{Sent}`",
+    label = "the other endpoint doesn't send this type",
+    note = "each endpoint's `receives` must be the other endpoint's `sends`, or messages \
+    sent one way would have no receiver"
+)]
+pub trait __ReceivedFrom<Sent> {}
+impl<T> __ReceivedFrom<T> for T {}
+
+pub(crate) fn downcast_error<T>() -> CommsError {
+    CommsError::TypeMismatch(
+        format!(
+            "Downcast failed. Expected type {} for sender, got different type.",
+            std::any::type_name::<T>()
+        ),
+        None,
+    )
+}
+
+pub(crate) fn closed_error<T>() -> CommsError {
+    CommsError::SendFailed(
+        format!(
+            "Failed to send message of type {}: channel closed",
+            std::any::type_name::<T>(),
+        ),
+        None,
+    )
+}
+
+#[cfg(feature = "tokio")]
+fn reserve_unsupported(type_name: &str) -> CommsError {
+    CommsError::TypeMismatch(
+        format!(
+            "Pathway for {} can't reserve capacity: only plain pathways on Tokio channels can.",
+            type_name,
+        ),
+        None,
+    )
+}
+
+pub(crate) fn try_send_error<T, U>(e: TrySendError<U>) -> CommsError {
+    match e {
+        TrySendError::Full(_) => CommsError::ChannelFull(format!(
+            "No capacity left for message of type {}",
+            std::any::type_name::<T>(),
+        )),
+        TrySendError::Closed(_) => closed_error::<T>(),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ConcreteSender<B: ChannelBackend, T: ConcreteSenderTrait> {
+    pub sender: B::Sender<T>,
+}
+
+impl<B: ChannelBackend, T: ConcreteSenderTrait> DynSender for ConcreteSender<B, T> {
     fn send_erased(
         &self,
         msg_any: Box<dyn Any + Send>,
@@ -36,24 +250,20 @@ impl<T: ConcreteSenderTrait> DynSender for ConcreteSender<T> {
             Ok(concrete_msg) => {
                 let sender_clone = self.sender.clone();
                 Box::pin(async move {
-                    sender_clone.send(*concrete_msg).await.map_err(|e| {
-                        CommsError::SendFailed(format!(
-                            "Failed to send message of type {}: {:?}",
-                            std::any::type_name::<T>(),
-                            e
-                        ))
-                    })
+                    B::send(&sender_clone, *concrete_msg)
+                        .await
+                        .map_err(|_| closed_error::<T>())
                 })
             }
-            Err(_) => Box::pin(async {
-                Err(CommsError::TypeMismatch(format!(
-                    "Downcast failed. Expected type {} for sender, got different type.",
-                    std::any::type_name::<T>()
-                )))
-            }),
+            Err(_) => Box::pin(async { Err(downcast_error::<T>()) }),
         }
     }
 
+    fn try_send_erased(&self, msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        let concrete_msg = msg_any.downcast::<T>().map_err(|_| downcast_error::<T>())?;
+        B::try_send(&self.sender, *concrete_msg).map_err(try_send_error::<T, T>)
+    }
+
     fn accepts_message_type_id(&self) -> TypeId {
         TypeId::of::<T>()
     }
@@ -61,4 +271,68 @@ impl<T: ConcreteSenderTrait> DynSender for ConcreteSender<T> {
     fn message_type_name(&self) -> &'static str {
         std::any::type_name::<T>()
     }
+
+    fn buffered(&self) -> Option<usize> {
+        B::buffered(&self.sender)
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        B::is_closed(&self.sender)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(ConcreteSender::<B, T> {
+            sender: self.sender.clone(),
+        })
+    }
+
+    fn send_returning(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Undelivered>> + Send>> {
+        match msg_any.downcast::<T>() {
+            Ok(concrete_msg) => {
+                let sender_clone = self.sender.clone();
+                Box::pin(async move {
+                    B::send(&sender_clone, *concrete_msg)
+                        .await
+                        .map_err(Undelivered::closed)
+                })
+            }
+            Err(_) => Box::pin(async { Err(Undelivered::lost(downcast_error::<T>())) }),
+        }
+    }
+
+    fn try_send_returning(&self, msg_any: Box<dyn Any + Send>) -> Result<(), Undelivered> {
+        let concrete_msg = msg_any
+            .downcast::<T>()
+            .map_err(|_| Undelivered::lost(downcast_error::<T>()))?;
+        B::try_send(&self.sender, *concrete_msg).map_err(|e| match e {
+            TrySendError::Closed(msg) => Undelivered::closed(msg),
+            TrySendError::Full(msg) => Undelivered::full(msg),
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    fn reserve_owned_erased(&self) -> Reservation {
+        // Reserving is Tokio's own API, so it's only there when `B` is Tokio's backend.
+        let Some(sender) =
+            (&self.sender as &dyn Any).downcast_ref::<tokio::sync::mpsc::Sender<T>>()
+        else {
+            let error = reserve_unsupported(std::any::type_name::<T>());
+            return Box::pin(async { Err(error) });
+        };
+        let sender = sender.clone();
+        Box::pin(async move {
+            match sender.reserve_owned().await {
+                Ok(permit) => Ok(Box::new(permit) as Box<dyn Any + Send>),
+                Err(_) => Err(closed_error::<T>()),
+            }
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    fn raw_sender(&self) -> Option<&dyn Any> {
+        Some(&self.sender)
+    }
 }
@@ -0,0 +1,155 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    error::CommsError, receiver::ConcreteReceiverTrait, router::Router, sender::ConcreteSenderTrait,
+};
+
+/// A request received on a pathway served through a [`PathwayService`], along with the
+/// channel its response goes back on.
+///
+/// Dropping it without calling [`Request::respond`] fails the caller's call with
+/// `CommsError::NotAcked`.
+#[derive(Debug)]
+pub struct Request<Req, Resp> {
+    request: Req,
+    reply: oneshot::Sender<Resp>,
+}
+
+impl<Req, Resp> Request<Req, Resp> {
+    /// The request payload, for inspecting it before responding.
+    pub fn message(&self) -> &Req {
+        &self.request
+    }
+
+    /// Sends `response` back to the caller.
+    ///
+    /// Does nothing if the caller stopped waiting, e.g. because its call was dropped.
+    pub fn respond(self, response: Resp) {
+        let _ = self.reply.send(response);
+    }
+
+    /// Splits the request into its payload and the sender of its response, for consumers
+    /// that need to take the payload by value to build the response.
+    pub fn into_parts(self) -> (Req, oneshot::Sender<Resp>) {
+        (self.request, self.reply)
+    }
+}
+
+/// A pathway carrying [`Request`]s, exposed as a request/response service, as returned by
+/// `Router::as_service`.
+///
+/// `poll_ready` and `call` have the shape of `tower::Service`, which the service
+/// implements with the `tower` feature, to be wrapped in tower middleware.
+///
+/// # Concurrency
+/// Every call sends one `Request` on the pathway and waits on a oneshot channel created
+/// for that call alone. Responses are never matched up by a correlation id: each one goes
+/// back on its own call's oneshot, so any number of calls can be in flight at once, from
+/// clones of the service or from the same one, and the consumer may answer them in any
+/// order. Backpressure comes from the pathway's buffer: a call waits for capacity while
+/// sending, and `poll_ready` doesn't reserve any.
+pub struct PathwayService<SenderMarker, Req, Resp> {
+    router: Arc<Router>,
+    _marker: PhantomData<fn(SenderMarker, Req) -> Resp>,
+}
+
+impl<SenderMarker, Req, Resp> PathwayService<SenderMarker, Req, Resp>
+where
+    SenderMarker: Any + Send + Sync + 'static,
+    Req: ConcreteSenderTrait,
+    Resp: ConcreteReceiverTrait,
+{
+    pub(crate) fn new(router: Arc<Router>) -> Self {
+        Self {
+            router,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Checks that the pathway is registered and carries `Request<Req, Resp>`.
+    ///
+    /// Always ready otherwise: waiting for capacity happens in [`call`](Self::call).
+    pub fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), CommsError>> {
+        if self.router.accepts::<SenderMarker, Request<Req, Resp>>() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(CommsError::PathwayNotFound(
+                format!(
+                    "No pathway '{}' carrying requests of type '{}'.",
+                    std::any::type_name::<SenderMarker>(),
+                    std::any::type_name::<Request<Req, Resp>>()
+                ),
+                Some(std::any::TypeId::of::<SenderMarker>()),
+            )))
+        }
+    }
+
+    /// Sends `request` and resolves with the consumer's response.
+    ///
+    /// Fails with the error of the send if the request can't be delivered, and with
+    /// `CommsError::NotAcked` if the consumer drops it without responding.
+    pub fn call(
+        &mut self,
+        request: Req,
+    ) -> impl Future<Output = Result<Resp, CommsError>> + Send + 'static {
+        let router = Arc::clone(&self.router);
+        async move {
+            let (reply, response) = oneshot::channel();
+            router
+                .send::<SenderMarker, Request<Req, Resp>>(Request { request, reply })
+                .await?;
+            response.await.map_err(|_| {
+                CommsError::NotAcked(format!(
+                    "Request of type '{}' sent on '{}' was dropped without a response.",
+                    std::any::type_name::<Req>(),
+                    std::any::type_name::<SenderMarker>()
+                ))
+            })
+        }
+    }
+}
+
+impl<SenderMarker, Req, Resp> Clone for PathwayService<SenderMarker, Req, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            router: Arc::clone(&self.router),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<SenderMarker, Req, Resp> Debug for PathwayService<SenderMarker, Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PathwayService")
+            .field("marker", &std::any::type_name::<SenderMarker>())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<SenderMarker, Req, Resp> tower::Service<Req> for PathwayService<SenderMarker, Req, Resp>
+where
+    SenderMarker: Any + Send + Sync + 'static,
+    Req: ConcreteSenderTrait,
+    Resp: ConcreteReceiverTrait,
+{
+    type Response = Resp;
+    type Error = CommsError;
+    type Future = std::pin::Pin<Box<dyn Future<Output = Result<Resp, CommsError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), CommsError>> {
+        PathwayService::poll_ready(self, cx)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        Box::pin(PathwayService::call(self, request))
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Debug,
+    marker::PhantomData,
+    pin::Pin,
+};
+
+use crate::{
+    error::CommsError,
+    receiver::ReceiverSlot,
+    sender::{ConcreteSenderTrait, DynSender, SenderSlot},
+};
+
+/// The channels of a sharded pathway, one per shard, indexed by shard.
+///
+/// Every shard's sender shares the counters and pause gate of the pathway's own slot,
+/// so `Router::stats` and `Router::pause` cover all shards at once.
+#[derive(Debug)]
+pub(crate) struct Shards {
+    pub senders: Vec<SenderSlot>,
+    pub receivers: Vec<ReceiverSlot>,
+}
+
+impl Shards {
+    /// The shard messages with `key` go to.
+    pub fn index(&self, key: u64) -> usize {
+        (key % self.senders.len() as u64) as usize
+    }
+}
+
+/// Sender registered for a sharded pathway itself, which can't pick a shard on its own.
+///
+/// Keeps the marker taken and typed for `validate`, `stats` and `pause`, while plain
+/// sends are turned away towards `Router::send_sharded`.
+#[derive(Debug)]
+pub(crate) struct KeyedSender<T> {
+    pub marker_name: &'static str,
+    pub _msg: PhantomData<fn(T)>,
+}
+
+impl<T> KeyedSender<T> {
+    fn needs_key(&self) -> CommsError {
+        CommsError::AmbiguousPathway(format!(
+            "Pathway '{}' is sharded: send with `Router::send_sharded` and a key.",
+            self.marker_name
+        ))
+    }
+}
+
+impl<T: ConcreteSenderTrait> DynSender for KeyedSender<T> {
+    fn send_erased(
+        &self,
+        _msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        let error = self.needs_key();
+        Box::pin(async { Err(error) })
+    }
+
+    fn try_send_erased(&self, _msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        Err(self.needs_key())
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(KeyedSender::<T> {
+            marker_name: self.marker_name,
+            _msg: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use tokio::sync::{
+    Mutex,
+    mpsc::{self, error::SendError},
+};
+
+/// A cheaply-cloneable receiver for using a pathway as a work queue.
+///
+/// Every clone competes for the same messages: each message is delivered to
+/// exactly one of the tasks calling [`SharedReceiver::recv`], never to all of them.
+/// This is not `broadcast`.
+///
+/// Receiving goes through an async mutex, so only one consumer waits on the channel
+/// at a time while the others queue up on the lock. That's fine when handling a
+/// message takes much longer than receiving it. For very high message rates, a
+/// single dispatcher task that owns the receiver and hands work out to workers
+/// avoids the lock contention.
+#[derive(Debug)]
+pub struct SharedReceiver<T> {
+    receiver: Arc<Mutex<mpsc::Receiver<T>>>,
+}
+
+impl<T> Clone for SharedReceiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            receiver: Arc::clone(&self.receiver),
+        }
+    }
+}
+
+impl<T> SharedReceiver<T> {
+    pub(crate) fn new(receiver: mpsc::Receiver<T>) -> Self {
+        Self {
+            receiver: Arc::new(Mutex::new(receiver)),
+        }
+    }
+
+    /// Locks the receiver, waits for the next message and unlocks again.
+    /// Returns `None` once the channel is closed and drained.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: a dropped call releases the lock without having
+    /// taken a message, leaving it to the next `recv`, on this or another worker.
+    pub async fn recv(&self) -> Option<T> {
+        self.receiver.lock().await.recv().await
+    }
+
+    /// Receives the next message for which `pred` holds, sending every other one back
+    /// into the pathway through `requeue`, usually a clone of the pathway's own sender,
+    /// for a peer to pick up. Returns `None` once the channel is closed and drained.
+    ///
+    /// This lets workers sharing one channel each take their own partition of the
+    /// messages, at a price:
+    /// - Requeued messages go to the back of the queue, so they lose their place
+    ///   relative to everything sent since, and messages of one partition only keep
+    ///   their order if nothing else overtook them in between.
+    /// - A message no worker accepts is received and requeued over and over, keeping
+    ///   the workers busy without making progress. The call yields after every requeue
+    ///   so peers get a turn, but the predicates together must cover every message.
+    /// - Requeueing waits for capacity like any send. If the buffer is full and every
+    ///   worker is holding a message to requeue, none of them can go on.
+    ///
+    /// If `requeue`'s channel is closed, the message it was meant to take is returned
+    /// even though `pred` rejected it, rather than lost.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is not cancel safe: a call dropped while requeueing a message loses
+    /// that message.
+    pub async fn recv_filtered<F>(&self, mut pred: F, requeue: &mpsc::Sender<T>) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        loop {
+            let msg = self.recv().await?;
+            if pred(&msg) {
+                return Some(msg);
+            }
+            if let Err(SendError(msg)) = requeue.send(msg).await {
+                return Some(msg);
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+}
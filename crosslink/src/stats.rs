@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::{error::CommsError, telemetry};
+
+/// Always-on send counters for a pathway, shared by every path that sends on it.
+#[derive(Debug, Default)]
+pub(crate) struct PathwayCounters {
+    sent: AtomicU64,
+    send_failures: AtomicU64,
+    full_drops: AtomicU64,
+}
+
+impl PathwayCounters {
+    pub fn record(&self, result: &Result<(), CommsError>) {
+        let counter = match result {
+            Ok(()) => &self.sent,
+            Err(CommsError::ChannelFull(_)) => &self.full_drops,
+            Err(_) => &self.send_failures,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> PathwayStats {
+        PathwayStats {
+            sent: self.sent.load(Ordering::Relaxed),
+            send_failures: self.send_failures.load(Ordering::Relaxed),
+            full_drops: self.full_drops.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time send counters for a pathway, as returned by `Router::stats`.
+///
+/// The counters are read independently and without synchronization, so under heavy
+/// concurrency a snapshot is approximate: it may mix values from slightly different
+/// moments, but never loses increments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathwayStats {
+    /// Messages successfully handed to the channel.
+    pub sent: u64,
+    /// Sends that failed for any reason other than a full buffer,
+    /// e.g. a closed channel or a type mismatch.
+    pub send_failures: u64,
+    /// Messages dropped by `try_send` because the buffer was full.
+    pub full_drops: u64,
+}
+
+/// Peak number of messages buffered in a pathway's receiver, as sampled by the
+/// spawning helpers once `Router::track_high_water_mark` is on for it.
+#[derive(Debug)]
+pub(crate) struct HighWaterMark {
+    pathway: &'static str,
+    peak: AtomicUsize,
+}
+
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+impl HighWaterMark {
+    pub fn new(pathway: &'static str) -> Self {
+        Self {
+            pathway,
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Records that `buffered` messages were waiting, out of at most `capacity`,
+    /// reporting the receiver as saturated if it was full.
+    pub fn observe(&self, buffered: usize, capacity: usize) {
+        self.peak.fetch_max(buffered, Ordering::Relaxed);
+        if buffered >= capacity {
+            telemetry::saturated(self.pathway, capacity);
+        }
+    }
+
+    pub fn get(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+};
+
+use tokio::sync::mpsc;
+
+use crate::sender::DebugRenderer;
+
+/// How many tapped messages can queue up before new copies are dropped.
+pub const TAP_BUFFER: usize = 1024;
+
+/// A copy of a message sent on some pathway, as seen by [`Router::tap`](crate::Router::tap).
+#[derive(Debug)]
+pub struct TappedMessage {
+    /// Type name of the sender marker the message was sent through.
+    pub pathway: &'static str,
+    /// The message as rendered with `Debug` when it was sent.
+    /// Printing it with `{:?}` gives back that rendering verbatim.
+    pub message: Box<dyn Debug + Send>,
+}
+
+/// The router's end of an attached tap.
+#[derive(Debug)]
+pub(crate) struct Tap {
+    sender: mpsc::Sender<TappedMessage>,
+}
+
+impl Tap {
+    pub fn new() -> (Self, mpsc::Receiver<TappedMessage>) {
+        let (sender, receiver) = mpsc::channel(TAP_BUFFER);
+        (Self { sender }, receiver)
+    }
+
+    /// Renders a message for the tap, before it's moved into its pathway.
+    pub fn render(&self, message: &impl Debug) -> Rendered {
+        Rendered(format!("{:?}", message))
+    }
+
+    /// Renders a type-erased message for the tap, or returns `None` if `render`
+    /// wasn't made for its type.
+    pub fn render_erased(&self, render: DebugRenderer, message: &dyn Any) -> Option<Rendered> {
+        render(message).map(Rendered)
+    }
+
+    /// Forwards a copy without ever waiting: a lagging tap loses copies,
+    /// the pathway itself is never slowed down.
+    pub fn forward(&self, pathway: &'static str, message: Rendered) {
+        let _ = self.sender.try_send(TappedMessage {
+            pathway,
+            message: Box::new(message),
+        });
+    }
+}
+
+/// A message already rendered with `Debug`, so taps work without messages being `Clone`.
+pub(crate) struct Rendered(String);
+
+impl Debug for Rendered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
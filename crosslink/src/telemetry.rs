@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use crate::error::CommsError;
+
+// Every signal about a pathway is labelled the same way across the `tracing` and
+// `metrics` features: `pathway` is the type name of the pathway's sender marker.
+
+/// Records the outcome of a send on `pathway`.
+pub(crate) fn sent(pathway: &'static str, result: &Result<(), CommsError>) {
+    #[cfg(feature = "metrics")]
+    match result {
+        Ok(()) => {
+            metrics::counter!("crosslink_messages_sent_total", "pathway" => pathway).increment(1)
+        }
+        Err(_) => {
+            metrics::counter!("crosslink_send_errors_total", "pathway" => pathway).increment(1)
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    match result {
+        Ok(()) => tracing::trace!(pathway, "message sent"),
+        Err(e) => tracing::debug!(pathway, error = %e, "send failed"),
+    }
+
+    #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+    let _ = (pathway, result);
+}
+
+/// Records a message of an instrumented pathway reaching its consumer,
+/// `latency` after it was stamped by the sender.
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) fn received(pathway: &'static str, latency: Duration) {
+    #[cfg(feature = "metrics")]
+    metrics::histogram!("crosslink_pathway_latency_seconds", "pathway" => pathway)
+        .record(latency.as_secs_f64());
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        pathway,
+        latency_us = latency.as_micros() as u64,
+        "message received"
+    );
+
+    #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+    let _ = (pathway, latency);
+}
+
+/// Records a consumer finding its receiver's whole buffer of `capacity` messages in use,
+/// meaning producers were being held back or, with `try_send`, dropping messages.
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) fn saturated(pathway: &'static str, capacity: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("crosslink_receiver_saturated_total", "pathway" => pathway).increment(1);
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(pathway, capacity, "receiver buffer full");
+
+    #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+    let _ = (pathway, capacity);
+}
+
+/// Records the overflow queue of spillover pathway `pathway` now holding `depth` messages.
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) fn overflow_depth(pathway: &'static str, depth: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!("crosslink_overflow_depth", "pathway" => pathway).set(depth as f64);
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(pathway, depth, "overflow depth changed");
+
+    #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+    let _ = (pathway, depth);
+}
+
+/// Records a router dropped while still holding the receivers of `markers`, whose
+/// pathways nothing ever consumed.
+#[cfg(debug_assertions)]
+pub(crate) fn untaken_receivers(markers: &[&'static str]) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(receivers = ?markers, "router dropped with receivers never taken");
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = markers;
+}
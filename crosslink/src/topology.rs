@@ -0,0 +1,50 @@
+/// Every pathway registered on a router, grouped by the link that set it up, as returned
+/// by `Router::topology`, e.g. to render architecture diagrams with external tools.
+///
+/// Names are type names as given by `std::any::type_name`, or `link_id`s and endpoint
+/// handle names as written in `define_crosslink!`. Links and pathways are sorted by
+/// name, so snapshots of the same wiring compare equal. With the `serde` feature, every
+/// type here implements `Serialize`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Topology {
+    pub links: Vec<LinkTopology>,
+    /// Pathways that don't belong to any link, e.g. registered with
+    /// `register_pathway_with` or one half at a time.
+    pub pathways: Vec<PathwayTopology>,
+}
+
+/// A link set up by a `define_crosslink!` setup function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LinkTopology {
+    pub link_id: &'static str,
+    /// The endpoint handle names, e.g. `PingerHandle`.
+    pub endpoints: Vec<&'static str>,
+    /// The link's `meta` block.
+    pub meta: &'static [(&'static str, &'static str)],
+    /// One pathway per direction.
+    pub pathways: Vec<PathwayTopology>,
+}
+
+/// One pathway, from its sender to its receiver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PathwayTopology {
+    /// The endpoint sending on the pathway, if it belongs to a link.
+    pub from: Option<&'static str>,
+    /// The endpoint receiving from the pathway, if it belongs to a link.
+    pub to: Option<&'static str>,
+    /// `None` for a receiver registered on its own.
+    pub sender_marker: Option<&'static str>,
+    /// `None` for a sender registered on its own, or whose receivers the router keeps
+    /// apart, as for sharded pathways.
+    pub receiver_marker: Option<&'static str>,
+    /// What producers send, e.g. `Job` for an instrumented pathway carrying
+    /// `Envelope<Job>`. For a receiver registered on its own, what its channel carries.
+    pub message_type: &'static str,
+    /// Capacity the pathway was registered with, 0 for rendezvous pathways and 1 for
+    /// watch pathways, or `None` when only one half was registered and the router never
+    /// saw the channel.
+    pub buffer_size: Option<usize>,
+}
@@ -0,0 +1,81 @@
+use std::{
+    any::{Any, TypeId},
+    pin::Pin,
+};
+
+use tokio::sync::watch;
+
+use crate::{
+    error::CommsError,
+    receiver::DynReceiver,
+    sender::{ConcreteSenderTrait, DynSender, downcast_error},
+};
+
+/// A sender for watch pathways: every send replaces the current value, right away.
+///
+/// The router keeps a receiver in the pathway's slot for good, so the channel never
+/// closes and sends never fail for lack of watchers.
+#[derive(Debug)]
+pub(crate) struct WatchSender<T> {
+    pub sender: watch::Sender<T>,
+}
+
+impl<T: ConcreteSenderTrait> DynSender for WatchSender<T> {
+    fn send_erased(
+        &self,
+        msg_any: Box<dyn Any + Send>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), CommsError>> + Send>> {
+        let result = self.try_send_erased(msg_any);
+        Box::pin(async { result })
+    }
+
+    fn try_send_erased(&self, msg_any: Box<dyn Any + Send>) -> Result<(), CommsError> {
+        let value = msg_any.downcast::<T>().map_err(|_| downcast_error::<T>())?;
+        self.sender.send_replace(*value);
+        Ok(())
+    }
+
+    fn accepts_message_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn is_closed(&self) -> Option<bool> {
+        Some(self.sender.is_closed())
+    }
+
+    fn clone_box(&self) -> Box<dyn DynSender> {
+        Box::new(WatchSender::<T> {
+            sender: self.sender.clone(),
+        })
+    }
+}
+
+/// The receiver a watch pathway keeps in its slot, handed out as clones rather than
+/// taken.
+#[derive(Debug)]
+pub(crate) struct WatchReceiver<T> {
+    pub receiver: watch::Receiver<T>,
+}
+
+impl<T: ConcreteSenderTrait> DynReceiver for WatchReceiver<T> {
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    /// Nothing ever queues up: the current value isn't a backlog.
+    fn len(&self) -> usize {
+        0
+    }
+
+    fn is_lent(&self) -> bool {
+        true
+    }
+}